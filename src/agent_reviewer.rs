@@ -1,22 +1,75 @@
-//! Agent reviewer trait: runs code review for a PR/MR and returns `ReviewResult`.
+//! Agent reviewer trait: runs code review for a `ReviewTarget` and returns `ReviewResult`.
 //!
 //! The agent decides which MCP tools to call (e.g. fetch PR content, post review).
-//! Implemented by LangGraph ReAct agent. Used by `ReviewPipeline`.
+//! `AsyncAgentReviewer` is implemented by the LangGraph ReAct agent; `BlockingAgentReviewer`
+//! turns it into a blocking `AgentReviewer` for `ReviewPipeline::run` and the CLI.
+
+use async_trait::async_trait;
 
-use crate::pr_url::PrUrl;
 use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
 
-/// Runs agent code review for the given PR/MR. The agent decides when to call MCP (fetch/post).
+/// Runs agent code review for the given target. The agent decides when to call MCP (fetch/post).
 pub trait AgentReviewer: Send + Sync {
     /// Performs review; `project_path` is optional and may be used for repo context.
-    /// The agent fetches PR content and posts the result via its tools (MCP).
+    /// The agent fetches the target's content and, if the target supports it, posts the result
+    /// via its tools (MCP).
     fn review(
         &self,
         project_path: Option<&std::path::Path>,
-        pr: &PrUrl,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError>;
+}
+
+/// Async version of `AgentReviewer`, for embedding in async applications (no runtime of its own).
+#[async_trait]
+pub trait AsyncAgentReviewer: Send + Sync {
+    /// Same contract as `AgentReviewer::review`.
+    async fn review(
+        &self,
+        project_path: Option<&std::path::Path>,
+        target: &ReviewTarget,
     ) -> Result<ReviewResult, ReviewError>;
 }
 
+/// Blocking `AgentReviewer` over an `AsyncAgentReviewer`, driven on a runtime owned by the adapter.
+/// Calling it from inside a tokio runtime returns an error; await the async reviewer there instead.
+pub struct BlockingAgentReviewer<A> {
+    agent: A,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl<A> BlockingAgentReviewer<A> {
+    /// Wraps `agent` with a new multi-threaded runtime.
+    pub fn new(agent: A) -> Result<Self, ReviewError> {
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| ReviewError {
+            message: e.to_string(),
+        })?;
+        Ok(Self { agent, runtime })
+    }
+
+    /// The wrapped async reviewer.
+    pub fn inner(&self) -> &A {
+        &self.agent
+    }
+}
+
+impl<A: AsyncAgentReviewer> AgentReviewer for BlockingAgentReviewer<A> {
+    fn review(
+        &self,
+        project_path: Option<&std::path::Path>,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(ReviewError {
+                message: "BlockingAgentReviewer called inside a tokio runtime; use AsyncAgentReviewer"
+                    .to_string(),
+            });
+        }
+        self.runtime.block_on(self.agent.review(project_path, target))
+    }
+}
+
 /// Errors from the agent review step (e.g. opencode-sdk session failure).
 #[derive(Debug)]
 pub struct ReviewError {
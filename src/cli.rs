@@ -1,25 +1,119 @@
-//! CLI parsing and entry: accepts PR/MR URL, builds pipeline, runs review.
+//! CLI parsing and entry: accepts a PR/MR URL (or shorthand), commit / compare URL or local
+//! range, builds pipeline, runs review.
 //!
-//! Called from `main.rs`. Uses `PrUrl::parse` and `ReviewPipeline::run`.
+//! Called from `main.rs`. Uses `ReviewTarget::resolve` and `ReviewPipeline::run`.
 
-use crate::pr_url::PrUrl;
+use std::path::Path;
+
+use crate::known_hosts::KnownHosts;
+use crate::pr_url::{PrUrl, PrUrlParseError};
 use crate::review_pipeline::ReviewPipeline;
+use crate::review_target::ReviewTarget;
+
+/// First argument that starts the stdio MCP server instead of a single review.
+pub const MCP_SERVE_COMMAND: &str = "mcp-serve";
+/// Flag that bypasses the persistent fetch cache.
+pub const NO_CACHE_FLAG: &str = "--no-cache";
+/// Flag that refetches and overwrites cached entries.
+pub const REFRESH_CACHE_FLAG: &str = "--refresh-cache";
+/// Flag that adds the commit-history review.
+pub const COMMIT_HYGIENE_FLAG: &str = "--commit-hygiene";
+
+/// Switches accepted anywhere on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CliFlags {
+    /// `--no-cache`: neither read nor write the fetch cache.
+    pub no_cache: bool,
+    /// `--refresh-cache`: ignore cached entries but store the fresh fetch.
+    pub refresh_cache: bool,
+    /// `--commit-hygiene`: also review commit messages and history.
+    pub commit_hygiene: bool,
+}
+
+/// Removes known flags from `args`, returning the remaining args (program name first) and the flags.
+pub fn split_flags(args: &[String]) -> (Vec<String>, CliFlags) {
+    let mut flags = CliFlags::default();
+    let rest = args
+        .iter()
+        .filter(|arg| match arg.as_str() {
+            NO_CACHE_FLAG => {
+                flags.no_cache = true;
+                false
+            }
+            REFRESH_CACHE_FLAG => {
+                flags.refresh_cache = true;
+                false
+            }
+            COMMIT_HYGIENE_FLAG => {
+                flags.commit_hygiene = true;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect();
+    (rest, flags)
+}
+
+/// Parses CLI args (single positional PR/MR URL or shorthand like `#123`) and returns `PrUrl` if valid.
+/// Only the default public hosts (`KnownHosts::default`) are accepted; use
+/// `parse_pr_url_from_args_with_hosts` with `KnownHosts::from_env` for `QUICK_REVIEW_HOSTS`.
+/// Shorthands resolve against the current directory.
+pub fn parse_pr_url_from_args(args: &[String]) -> Result<PrUrl, CliError> {
+    parse_pr_url_from_args_with_hosts(args, &KnownHosts::default(), Path::new("."))
+}
 
-/// Parses CLI args (e.g. single positional PR/MR URL) and returns `PrUrl` if valid.
-pub fn parse_pr_url_from_args(args: &[String]) -> Option<PrUrl> {
-    let url = args.get(1)?;
-    PrUrl::parse(url)
+/// Like `parse_pr_url_from_args`, but accepts any host in `hosts` (e.g. from `KnownHosts::from_env`)
+/// and resolves shorthands (`#123`, `!45`, `owner/repo#123`) from the `origin` remote of `project_path`.
+pub fn parse_pr_url_from_args_with_hosts(
+    args: &[String],
+    hosts: &KnownHosts,
+    project_path: &Path,
+) -> Result<PrUrl, CliError> {
+    let url = args.get(1).ok_or(CliError::MissingUrl)?;
+    PrUrl::resolve(url, project_path, hosts).map_err(CliError::InvalidUrl)
+}
+
+/// Parses the single positional argument as any `ReviewTarget`: a PR/MR, commit or compare URL,
+/// a PR shorthand, or a local range like `main..feature` in `project_path`.
+pub fn parse_review_target_from_args_with_hosts(
+    args: &[String],
+    hosts: &KnownHosts,
+    project_path: &Path,
+) -> Result<ReviewTarget, CliError> {
+    let input = args.get(1).ok_or(CliError::MissingUrl)?;
+    ReviewTarget::resolve(input, project_path, hosts).map_err(CliError::InvalidUrl)
 }
 
 /// Runs the pipeline with the given reviewer. Prints result summary to stdout.
-pub fn run_pipeline<A>(pipeline: &ReviewPipeline<A>, pr: &PrUrl) -> Result<(), Box<dyn std::error::Error>>
+pub fn run_pipeline<A>(pipeline: &ReviewPipeline<A>, target: &ReviewTarget) -> Result<(), Box<dyn std::error::Error>>
 where
     A: crate::agent_reviewer::AgentReviewer,
 {
-    let result = pipeline.run(pr)?;
+    let result = pipeline.run(target)?;
     println!("{}", result.summary);
     for c in &result.line_comments {
         println!("  {}:{} - {}", c.path, c.line, c.body);
     }
     Ok(())
 }
+
+/// Errors from CLI argument parsing.
+#[derive(Debug)]
+pub enum CliError {
+    /// No PR/MR URL (or other review target) argument was given.
+    MissingUrl,
+    /// The argument is not a recognized PR/MR, commit or compare URL, shorthand or local range.
+    InvalidUrl(PrUrlParseError),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::MissingUrl => write!(f, "missing PR/MR URL or range"),
+            CliError::InvalidUrl(e) => write!(f, "invalid review target: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
@@ -0,0 +1,128 @@
+//! Commit-history checks: flags WIP and fixup commits, empty or vague messages, large commits
+//! without an explanatory body and large commits spread over many top-level directories.
+//!
+//! The checks are heuristics on `CommitInfo`; `get_commits` shows their flags next to each
+//! commit, and the commit-hygiene review (`LangGraphReviewAgent::with_commit_hygiene`) asks the
+//! agent to judge the rest (whether a message explains the change, whether changes flagged as
+//! mixed are really unrelated).
+
+use crate::review_input::CommitInfo;
+
+/// Changed lines above which a commit should explain itself in a body.
+pub const LARGE_COMMIT_LINES: u32 = 100;
+
+/// Top-level directories from which a large commit is flagged as mixing unrelated changes.
+pub const MIXED_COMMIT_DIRECTORIES: usize = 4;
+
+/// Subjects (lowercase, without trailing period) that say nothing about the change.
+const VAGUE_SUBJECTS: &[&str] = &[
+    "fix",
+    "fixes",
+    "fix bug",
+    "fix bugs",
+    "bug fix",
+    "bugfix",
+    "update",
+    "updates",
+    "change",
+    "changes",
+    "misc",
+    "minor",
+    "minor changes",
+    "minor fixes",
+    "small fixes",
+    "cleanup",
+    "refactor",
+    "more fixes",
+    "address comments",
+    "address review comments",
+    "review comments",
+    "oops",
+    "stuff",
+];
+
+/// One problem with a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitIssue {
+    /// Subject marks the commit as work in progress (`WIP`, `[WIP]`).
+    Wip,
+    /// `fixup!` / `squash!` / `amend!` commit meant to be folded before merging.
+    Fixup,
+    /// No message at all.
+    EmptyMessage,
+    /// Subject does not say what changed (`fix`, `updates`, a single word).
+    VagueSubject,
+    /// More than `LARGE_COMMIT_LINES` changed lines and no body.
+    MissingBody,
+    /// More than `LARGE_COMMIT_LINES` changed lines in at least `MIXED_COMMIT_DIRECTORIES`
+    /// top-level directories.
+    MixedChanges,
+}
+
+impl std::fmt::Display for CommitIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            CommitIssue::Wip => "work in progress",
+            CommitIssue::Fixup => "fixup/squash commit",
+            CommitIssue::EmptyMessage => "empty message",
+            CommitIssue::VagueSubject => "vague subject",
+            CommitIssue::MissingBody => "large change without a body",
+            CommitIssue::MixedChanges => "large change across unrelated directories",
+        };
+        f.write_str(text)
+    }
+}
+
+/// Issues found in `commit`, in the order of `CommitIssue`'s variants.
+pub fn check(commit: &CommitInfo) -> Vec<CommitIssue> {
+    let subject = commit.subject();
+    if subject.is_empty() {
+        return vec![CommitIssue::EmptyMessage];
+    }
+    let lower = subject.to_lowercase();
+    let mut issues = Vec::new();
+    if is_wip(&lower) {
+        issues.push(CommitIssue::Wip);
+    }
+    if ["fixup!", "squash!", "amend!"].iter().any(|p| lower.starts_with(p)) {
+        issues.push(CommitIssue::Fixup);
+    }
+    if issues.is_empty() && is_vague(&lower) {
+        issues.push(CommitIssue::VagueSubject);
+    }
+    let large = commit.stats.is_some_and(|s| s.changed() > LARGE_COMMIT_LINES);
+    if large && commit.body().is_empty() {
+        issues.push(CommitIssue::MissingBody);
+    }
+    if large && top_level_directories(&commit.files) >= MIXED_COMMIT_DIRECTORIES {
+        issues.push(CommitIssue::MixedChanges);
+    }
+    issues
+}
+
+/// Distinct first path components of `files`; files at the repository root are not counted.
+fn top_level_directories(files: &[String]) -> usize {
+    let mut dirs: Vec<&str> = files
+        .iter()
+        .filter_map(|f| f.split_once('/').map(|(dir, _)| dir))
+        .collect();
+    dirs.sort_unstable();
+    dirs.dedup();
+    dirs.len()
+}
+
+/// `wip`, `wip: ...`, `wip ...` or `[wip]` anywhere.
+fn is_wip(lower: &str) -> bool {
+    let rest = lower.strip_prefix("wip");
+    lower.contains("[wip]") || rest.is_some_and(|r| !r.starts_with(|c: char| c.is_alphanumeric()))
+}
+
+/// A known content-free subject or a single word; a `type(scope):` prefix is ignored.
+fn is_vague(lower: &str) -> bool {
+    let text = match lower.split_once(": ") {
+        Some((prefix, rest)) if !prefix.contains(' ') => rest,
+        _ => lower,
+    };
+    let text = text.trim().trim_end_matches('.');
+    VAGUE_SUBJECTS.contains(&text) || text.split_whitespace().count() < 2
+}
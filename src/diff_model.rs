@@ -0,0 +1,514 @@
+//! Parsed unified diff: files → hunks → lines, with old/new line numbers.
+//!
+//! `parse` accepts `git diff` output (including extended headers, binary and rename-only
+//! sections) and plain `diff -u` output. `ReviewInput::with_diff` runs it automatically, so
+//! consumers can ask which lines a diff touches (e.g. to validate line comments) instead of
+//! re-reading the raw text. `text_diff` goes the other way and produces diffs.
+//!
+//! Each file also gets a `FileStatus` (added, renamed, binary, ...). `reviewable_diff` drops
+//! sections with no text changes to review (binary files, pure renames and copies).
+
+use std::ops::Range;
+
+/// Kind of one diff line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineKind {
+    /// Unchanged line shown for context (` `).
+    Context,
+    /// Line only in the new version (`+`).
+    Added,
+    /// Line only in the old version (`-`).
+    Removed,
+}
+
+/// One line of a hunk. Removed lines have no new line number, added lines no old one.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    /// Line text without the leading marker.
+    pub text: String,
+}
+
+/// One `@@ -old_start,old_count +new_start,new_count @@` hunk.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    /// Text after the closing `@@` (usually the enclosing function), trimmed.
+    pub section: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// How a file changed, from its diff headers.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    /// Moved from `from`, possibly with edits.
+    Renamed {
+        from: String,
+    },
+    /// Copied from `from`, possibly with edits.
+    Copied {
+        from: String,
+    },
+    /// Binary content changed (no text diff).
+    Binary,
+    /// Only the file mode changed (e.g. made executable).
+    ModeChange,
+}
+
+impl std::fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileStatus::Added => write!(f, "added"),
+            FileStatus::Modified => write!(f, "modified"),
+            FileStatus::Deleted => write!(f, "deleted"),
+            FileStatus::Renamed { from } => write!(f, "renamed from {}", from),
+            FileStatus::Copied { from } => write!(f, "copied from {}", from),
+            FileStatus::Binary => write!(f, "binary"),
+            FileStatus::ModeChange => write!(f, "mode change"),
+        }
+    }
+}
+
+/// Lines added and removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DiffStat {
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+impl DiffStat {
+    /// Totals over parsed files.
+    pub fn of(files: &[DiffFile]) -> Self {
+        let mut stat = DiffStat::default();
+        for line in files.iter().flat_map(DiffFile::lines) {
+            match line.kind {
+                LineKind::Added => stat.additions += 1,
+                LineKind::Removed => stat.deletions += 1,
+                LineKind::Context => {}
+            }
+        }
+        stat
+    }
+
+    /// `additions + deletions`.
+    pub fn changed(&self) -> u32 {
+        self.additions + self.deletions
+    }
+}
+
+/// One file of a diff. `old_path` is `None` for added files, `new_path` for deleted ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DiffFile {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    /// From `old mode` / `deleted file mode`.
+    pub old_mode: Option<String>,
+    /// From `new mode` / `new file mode`.
+    pub new_mode: Option<String>,
+    /// `copy from` / `copy to` rather than a rename.
+    pub copied: bool,
+    /// `Binary files ... differ` or a `GIT binary patch`.
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+    /// Byte range of this file's section in the parsed text.
+    #[serde(skip)]
+    span: Range<usize>,
+}
+
+impl DiffFile {
+    /// New path, or the old path for deleted files.
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// This file's section of `diff`, the text it was parsed from (empty for other texts).
+    pub fn section<'a>(&self, diff: &'a str) -> &'a str {
+        diff.get(self.span.clone()).unwrap_or_default()
+    }
+
+    /// All hunk lines in order.
+    pub fn lines(&self) -> impl Iterator<Item = &DiffLine> {
+        self.hunks.iter().flat_map(|h| h.lines.iter())
+    }
+
+    /// The hunk line shown at new-side `line` (added or context), if any.
+    pub fn new_side_line(&self, line: u32) -> Option<&DiffLine> {
+        self.lines().find(|l| l.new_line == Some(line))
+    }
+
+    /// True if new-side `line` appears in a hunk (added or context), i.e. a review comment on
+    /// it can be anchored to the diff.
+    pub fn has_new_line(&self, line: u32) -> bool {
+        self.new_side_line(line).is_some()
+    }
+
+    /// Change status. Binary wins over added / deleted / renamed; a mode change with edits is
+    /// `Modified`.
+    pub fn status(&self) -> FileStatus {
+        match (&self.old_path, &self.new_path) {
+            _ if self.binary => FileStatus::Binary,
+            (None, _) => FileStatus::Added,
+            (_, None) => FileStatus::Deleted,
+            (Some(old), Some(new)) if old != new && self.copied => {
+                FileStatus::Copied { from: old.clone() }
+            }
+            (Some(old), Some(new)) if old != new => FileStatus::Renamed { from: old.clone() },
+            _ if self.hunks.is_empty() && self.old_mode != self.new_mode => FileStatus::ModeChange,
+            _ => FileStatus::Modified,
+        }
+    }
+
+    /// False for binary files and renames / copies without edits, whose diff text has nothing
+    /// to review.
+    pub fn is_reviewable(&self) -> bool {
+        match self.status() {
+            FileStatus::Binary => false,
+            FileStatus::Renamed { .. } | FileStatus::Copied { .. } => !self.hunks.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// New-side line numbers of added lines.
+    pub fn added_lines(&self) -> Vec<u32> {
+        self.lines()
+            .filter(|l| l.kind == LineKind::Added)
+            .filter_map(|l| l.new_line)
+            .collect()
+    }
+}
+
+/// Parses a unified diff into files. Text outside file sections (e.g. a mail header) is ignored.
+pub fn parse(diff: &str) -> Vec<DiffFile> {
+    let lines: Vec<(usize, &str)> = diff
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect();
+    let mut files: Vec<DiffFile> = Vec::new();
+    // Position in the current hunk; while lines are left, markers like `---` are content.
+    let mut cursor = HunkCursor::default();
+    let mut i = 0;
+    while i < lines.len() {
+        let (offset, raw) = lines[i];
+        let line = raw.trim_end_matches(['\n', '\r']);
+        if cursor.in_hunk() {
+            if let Some(file) = files.last_mut() {
+                if cursor.push(file, line) {
+                    file.span.end = offset + raw.len();
+                    i += 1;
+                    continue;
+                }
+            }
+            cursor = HunkCursor::default();
+        }
+        let in_section = files.last().is_some_and(|f| f.span.end == offset);
+        let git_header = in_section && in_git_header(&files, diff);
+        let plain_start = line.starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|(_, next)| next.starts_with("+++ "));
+        if line.starts_with("diff --git ") {
+            let (old_path, new_path) = git_header_paths(line).unwrap_or_default();
+            files.push(DiffFile {
+                old_path: Some(old_path),
+                new_path: Some(new_path),
+                span: offset..offset + raw.len(),
+                ..DiffFile::default()
+            });
+        } else if plain_start && !git_header {
+            let (next_offset, next) = lines[i + 1];
+            let old = header_path(&line[4..]);
+            let new = header_path(
+                next.trim_end_matches(['\n', '\r'])
+                    .get(4..)
+                    .unwrap_or_default(),
+            );
+            let (old_path, new_path) = strip_plain_prefixes(old, new);
+            files.push(DiffFile {
+                old_path,
+                new_path,
+                span: offset..next_offset + next.len(),
+                ..DiffFile::default()
+            });
+            i += 1;
+        } else if let (true, Some(file)) = (in_section, files.last_mut()) {
+            if let Some(hunk) = parse_hunk_header(line) {
+                cursor = HunkCursor::new(&hunk);
+                file.hunks.push(hunk);
+                file.span.end = offset + raw.len();
+            } else if file.hunks.is_empty() || line.starts_with('\\') {
+                // Extended headers, or `\ No newline at end of file` after a hunk's last line.
+                apply_extended_header(file, line);
+                file.span.end = offset + raw.len();
+            }
+            // Other text after the last hunk (e.g. a mail signature) ends the section.
+        }
+        i += 1;
+    }
+    files
+}
+
+/// `diff` (the text `files` were parsed from) without the sections of files that are not
+/// reviewable; each is replaced by a `path: status, diff omitted` line.
+pub fn reviewable_diff(diff: &str, files: &[DiffFile]) -> String {
+    let mut out = String::with_capacity(diff.len());
+    let mut copied = 0;
+    for file in files.iter().filter(|f| !f.is_reviewable()) {
+        out.push_str(diff.get(copied..file.span.start).unwrap_or_default());
+        out.push_str(&format!(
+            "{}: {}, diff omitted\n",
+            file.path(),
+            file.status()
+        ));
+        copied = file.span.end;
+    }
+    out.push_str(diff.get(copied..).unwrap_or_default());
+    out
+}
+
+/// True if the last file is a `diff --git` section still in its extended headers, where
+/// `--- a/..` / `+++ b/..` belong to it rather than starting a new file.
+fn in_git_header(files: &[DiffFile], diff: &str) -> bool {
+    files
+        .last()
+        .is_some_and(|f| f.hunks.is_empty() && f.section(diff).starts_with("diff --git "))
+}
+
+/// Next line numbers and lines left on each side of the current hunk.
+#[derive(Default)]
+struct HunkCursor {
+    old: u32,
+    new: u32,
+    old_left: u32,
+    new_left: u32,
+}
+
+impl HunkCursor {
+    fn new(hunk: &Hunk) -> Self {
+        Self {
+            old: hunk.old_start,
+            new: hunk.new_start,
+            old_left: hunk.old_count,
+            new_left: hunk.new_count,
+        }
+    }
+
+    fn in_hunk(&self) -> bool {
+        self.old_left > 0 || self.new_left > 0
+    }
+
+    /// Adds `line` to the file's last hunk; false if it is not a hunk line (the hunk ended early).
+    fn push(&mut self, file: &mut DiffFile, line: &str) -> bool {
+        let Some(hunk) = file.hunks.last_mut() else {
+            return false;
+        };
+        let (kind, text) = match line.chars().next() {
+            Some('+') => (LineKind::Added, &line[1..]),
+            Some('-') => (LineKind::Removed, &line[1..]),
+            Some(' ') => (LineKind::Context, &line[1..]),
+            // `\ No newline at end of file`
+            Some('\\') => return true,
+            // Some tools strip the space of empty context lines.
+            None => (LineKind::Context, ""),
+            Some(_) => return false,
+        };
+        let old_line = (kind != LineKind::Added).then_some(self.old);
+        let new_line = (kind != LineKind::Removed).then_some(self.new);
+        if old_line.is_some() {
+            self.old += 1;
+            self.old_left = self.old_left.saturating_sub(1);
+        }
+        if new_line.is_some() {
+            self.new += 1;
+            self.new_left = self.new_left.saturating_sub(1);
+        }
+        hunk.lines.push(DiffLine {
+            kind,
+            old_line,
+            new_line,
+            text: text.to_string(),
+        });
+        true
+    }
+}
+
+/// `@@ -a[,b] +c[,d] @@ section`; missing counts are 1.
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |r: &str| -> Option<(u32, u32)> {
+        match r.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = range(old)?;
+    let (new_start, new_count) = range(new)?;
+    Some(Hunk {
+        old_start,
+        old_count,
+        new_start,
+        new_count,
+        section: section.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// Updates paths and flags from a `git diff` extended header line (`---`, `+++`, modes, new /
+/// deleted file, rename, copy, binary).
+fn apply_extended_header(file: &mut DiffFile, line: &str) {
+    if let Some(mode) = line.strip_prefix("new file mode ") {
+        file.old_path = None;
+        file.new_mode = Some(mode.trim().to_string());
+    } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+        file.new_path = None;
+        file.old_mode = Some(mode.trim().to_string());
+    } else if let Some(mode) = line.strip_prefix("old mode ") {
+        file.old_mode = Some(mode.trim().to_string());
+    } else if let Some(mode) = line.strip_prefix("new mode ") {
+        file.new_mode = Some(mode.trim().to_string());
+    } else if let Some(p) = line
+        .strip_prefix("rename from ")
+        .or_else(|| line.strip_prefix("copy from "))
+    {
+        file.copied = line.starts_with("copy");
+        file.old_path = Some(unquote(p));
+    } else if let Some(p) = line
+        .strip_prefix("rename to ")
+        .or_else(|| line.strip_prefix("copy to "))
+    {
+        file.new_path = Some(unquote(p));
+    } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+        file.binary = true;
+    } else if let Some(p) = line.strip_prefix("--- ") {
+        file.old_path = git_side_path(p, "a/");
+    } else if let Some(p) = line.strip_prefix("+++ ") {
+        file.new_path = git_side_path(p, "b/");
+    }
+}
+
+/// `a/path` / `b/path` of a `---` / `+++` line in a git diff; `None` for `/dev/null`.
+fn git_side_path(p: &str, prefix: &str) -> Option<String> {
+    let p = header_path(p)?;
+    Some(p.strip_prefix(prefix).map(String::from).unwrap_or(p))
+}
+
+/// Path of a `---` / `+++` line: unquoted, without a trailing tab and timestamp; `None` for `/dev/null`.
+fn header_path(p: &str) -> Option<String> {
+    let p = if p.starts_with('"') {
+        p
+    } else {
+        p.split('\t').next().unwrap_or(p)
+    };
+    let p = unquote(p.trim_end());
+    (p != "/dev/null").then_some(p)
+}
+
+/// Drops `a/` and `b/` from plain diff paths when both sides use them.
+fn strip_plain_prefixes(
+    old: Option<String>,
+    new: Option<String>,
+) -> (Option<String>, Option<String>) {
+    let prefixed = old.as_deref().is_none_or(|p| p.starts_with("a/"))
+        && new.as_deref().is_none_or(|p| p.starts_with("b/"))
+        && (old.is_some() || new.is_some());
+    if !prefixed {
+        return (old, new);
+    }
+    let strip = |p: Option<String>| p.map(|p| p[2..].to_string());
+    (strip(old), strip(new))
+}
+
+/// Old and new path of `diff --git a/{old} b/{new}` (quoted paths allowed). Unquoted paths with
+/// spaces are ambiguous; they are split in the middle, which is right unless the file was renamed.
+fn git_header_paths(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("diff --git ")?;
+    let (a, b) = if rest.starts_with('"') {
+        let end = closing_quote(rest)?;
+        (
+            unquote(&rest[..=end]),
+            unquote(rest[end + 1..].trim_start()),
+        )
+    } else if let Some(pos) = rest.find(" \"b/") {
+        (rest[..pos].to_string(), unquote(&rest[pos + 1..]))
+    } else {
+        let half = rest.len().checked_sub(1)? / 2;
+        match (rest.get(..half), rest.get(half + 1..)) {
+            (Some(a), Some(b)) if a.get(2..) == b.get(2..) => (a.to_string(), b.to_string()),
+            _ => {
+                let (a, b) = rest.split_once(" b/")?;
+                (a.to_string(), format!("b/{}", b))
+            }
+        }
+    };
+    Some((
+        a.strip_prefix("a/").unwrap_or(&a).to_string(),
+        b.strip_prefix("b/").unwrap_or(&b).to_string(),
+    ))
+}
+
+fn closing_quote(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Undoes git's C-style path quoting (`"a/\303\244 b.txt"`); other text is returned as is.
+fn unquote(s: &str) -> String {
+    let Some(inner) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) else {
+        return s.to_string();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some(d @ '0'..='7') => {
+                let mut value = d.to_digit(8).unwrap_or_default();
+                for _ in 0..2 {
+                    if let Some(d) = chars.peek().and_then(|c| c.to_digit(8)) {
+                        value = value * 8 + d;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
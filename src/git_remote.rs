@@ -0,0 +1,151 @@
+//! Git remote lookup: reads the `origin` URL from a checkout's `.git/config`.
+//!
+//! Used by `PrUrl::resolve` to expand shorthand references (`#123`, `!45`, `owner/repo#123`)
+//! into a full `PrUrl`. Handles SSH (`git@host:owner/repo.git`, `ssh://`) and HTTPS remotes.
+
+use std::path::{Path, PathBuf};
+
+/// Host and repository path of a git remote (e.g. `github.com` + `owner/repo`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRemote {
+    pub host: String,
+    /// Repository path without `.git`; may contain nested groups (GitLab).
+    pub path: String,
+}
+
+impl GitRemote {
+    /// Splits `path` into (owner/namespace, repo). Returns `None` if there is no `/`.
+    pub fn owner_and_repo(&self) -> Option<(String, String)> {
+        let (owner, repo) = self.path.rsplit_once('/')?;
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        Some((owner.to_string(), repo.to_string()))
+    }
+}
+
+/// Parses a remote URL in SSH (`git@host:path`, `ssh://user@host:port/path`), HTTPS, HTTP or
+/// `git://` form. Credentials and SSH ports are dropped; a trailing `.git` is stripped. Azure
+/// DevOps SSH remotes (`ssh.dev.azure.com:v3/{org}/{project}/{repo}`) become their web form
+/// (`dev.azure.com` + `{org}/{project}/_git/{repo}`).
+pub fn parse_remote_url(url: &str) -> Option<GitRemote> {
+    let url = url.trim();
+    let (host, path) = if let Some((scheme, rest)) = url.split_once("://") {
+        if !matches!(scheme, "https" | "http" | "ssh" | "git" | "git+ssh") {
+            return None;
+        }
+        let (authority, path) = rest.split_once('/')?;
+        let authority = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+        // The SSH port is not the web port; HTTP(S) ports are part of the web host.
+        let host = if scheme == "ssh" || scheme == "git+ssh" || scheme == "git" {
+            authority.split(':').next().unwrap_or(authority)
+        } else {
+            authority
+        };
+        (host, path)
+    } else {
+        // scp-like syntax: [user@]host:path
+        let (authority, path) = url.split_once(':')?;
+        let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+        (host, path)
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    if host.eq_ignore_ascii_case("ssh.dev.azure.com") {
+        let mut parts = path.strip_prefix("v3/")?.split('/');
+        let (org, project, repo) = (parts.next()?, parts.next()?, parts.next()?);
+        return Some(GitRemote {
+            host: "dev.azure.com".to_string(),
+            path: format!("{}/{}/_git/{}", org, project, repo),
+        });
+    }
+    Some(GitRemote {
+        host: host.to_ascii_lowercase(),
+        path: path.to_string(),
+    })
+}
+
+/// Reads the URL of `remote` (e.g. `origin`) from the git config of the checkout at `project_path`.
+pub fn read_remote_url(project_path: &Path, remote: &str) -> Result<String, GitRemoteError> {
+    let git_dir = find_git_dir(project_path)?;
+    let config_path = git_dir.join("config");
+    let config = std::fs::read_to_string(&config_path).map_err(|e| GitRemoteError {
+        message: format!("cannot read {}: {}", config_path.display(), e),
+    })?;
+    let section = format!("[remote \"{}\"]", remote);
+    let mut in_section = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == section;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "url" {
+                return Ok(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    Err(GitRemoteError {
+        message: format!("remote '{}' has no url in {}", remote, config_path.display()),
+    })
+}
+
+/// Reads and parses the `origin` remote of the checkout at `project_path`.
+pub fn read_origin(project_path: &Path) -> Result<GitRemote, GitRemoteError> {
+    let url = read_remote_url(project_path, "origin")?;
+    parse_remote_url(&url).ok_or_else(|| GitRemoteError {
+        message: format!("unrecognized remote URL '{}'", url),
+    })
+}
+
+/// Walks up from `start` to find `.git` (a directory, or a `gitdir:` file for worktrees/submodules).
+fn find_git_dir(start: &Path) -> Result<PathBuf, GitRemoteError> {
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    for dir in start.ancestors() {
+        let candidate = dir.join(".git");
+        if candidate.is_dir() {
+            return Ok(candidate);
+        }
+        if candidate.is_file() {
+            let text = std::fs::read_to_string(&candidate).map_err(|e| GitRemoteError {
+                message: format!("cannot read {}: {}", candidate.display(), e),
+            })?;
+            let gitdir = text
+                .lines()
+                .find_map(|l| l.strip_prefix("gitdir:"))
+                .map(str::trim)
+                .ok_or_else(|| GitRemoteError {
+                    message: format!("{} has no gitdir line", candidate.display()),
+                })?;
+            let gitdir = dir.join(gitdir);
+            // Worktrees keep config in the common dir (`commondir` file); fall back to gitdir itself.
+            let common = std::fs::read_to_string(gitdir.join("commondir"))
+                .map(|c| gitdir.join(c.trim()))
+                .unwrap_or(gitdir);
+            return Ok(common);
+        }
+    }
+    Err(GitRemoteError {
+        message: format!("{} is not inside a git repository", start.display()),
+    })
+}
+
+/// Errors reading the git remote (no repository, no remote, unparseable URL).
+#[derive(Debug)]
+pub struct GitRemoteError {
+    pub message: String,
+}
+
+impl std::fmt::Display for GitRemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for GitRemoteError {}
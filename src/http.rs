@@ -0,0 +1,310 @@
+//! Minimal blocking HTTP client for forge REST APIs (JSON and raw-text bodies).
+//!
+//! Wraps a `ureq::Agent` with default headers (auth, user agent). Used by the REST
+//! providers in `providers`; every failure is mapped to `McpError` with method, URL and status,
+//! classified by `McpErrorKind` (with `Retry-After`, in seconds or as an HTTP date, or the
+//! rate-limit reset as `retry_after`). The
+//! quota from the last response's rate-limit headers is available via `rate_limit`.
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use serde_json::Value;
+
+use crate::mcp_provider::{McpError, McpErrorKind, RateLimit};
+
+/// Default per-request timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Wait GitHub asks for after a secondary rate limit without `Retry-After`.
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Blocking HTTP client with default headers applied to every request.
+#[derive(Clone)]
+pub struct HttpClient {
+    agent: ureq::Agent,
+    headers: Vec<(String, String)>,
+    /// Quota from the most recent response; shared by clones.
+    rate_limit: Arc<Mutex<Option<RateLimit>>>,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    /// Creates a client with a 60s timeout and a `quick-review` user agent.
+    pub fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(DEFAULT_TIMEOUT)
+            .redirect_auth_headers(ureq::RedirectAuthHeaders::SameHost)
+            .build();
+        Self {
+            agent,
+            headers: vec![(
+                "User-Agent".to_string(),
+                concat!("quick-review/", env!("CARGO_PKG_VERSION")).to_string(),
+            )],
+            rate_limit: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Quota reported by the most recent response that carried rate-limit headers.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Builder-style: add a header sent with every request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Builder-style: `Authorization: Bearer <token>`.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header("Authorization", format!("Bearer {}", token))
+    }
+
+    /// Builder-style: `Authorization: Basic <base64(user:password)>`.
+    pub fn with_basic_auth(self, user: &str, password: &str) -> Self {
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+        self.with_header("Authorization", format!("Basic {}", encoded))
+    }
+
+    /// GET and parse the response body as JSON.
+    pub fn get_json(&self, url: &str) -> Result<Value, McpError> {
+        let body = self.send("GET", url, Some("application/json"), None)?;
+        parse_json("GET", url, &body)
+    }
+
+    /// GET and return the raw body (e.g. a unified diff), with an optional `Accept` header.
+    /// Invalid UTF-8 is replaced lossily.
+    pub fn get_text(&self, url: &str, accept: Option<&str>) -> Result<String, McpError> {
+        let bytes = self.send("GET", url, accept, None)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// GET and return the raw body bytes (e.g. a file blob that may be binary).
+    pub fn get_bytes(&self, url: &str, accept: Option<&str>) -> Result<Vec<u8>, McpError> {
+        self.send("GET", url, accept, None)
+    }
+
+    /// POST a JSON body and parse the JSON response (empty responses become `Value::Null`).
+    pub fn post_json(&self, url: &str, body: &Value) -> Result<Value, McpError> {
+        let resp = self.send("POST", url, Some("application/json"), Some(body))?;
+        parse_json("POST", url, &resp)
+    }
+
+    /// POST a JSON body and return the raw response text (for APIs with non-JSON prefixes, e.g. Gerrit).
+    pub fn post_json_for_text(&self, url: &str, body: &Value) -> Result<String, McpError> {
+        let resp = self.send("POST", url, Some("application/json"), Some(body))?;
+        Ok(String::from_utf8_lossy(&resp).into_owned())
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        accept: Option<&str>,
+        body: Option<&Value>,
+    ) -> Result<Vec<u8>, McpError> {
+        let mut req = self.agent.request(method, url);
+        for (name, value) in &self.headers {
+            req = req.set(name, value);
+        }
+        if let Some(accept) = accept {
+            req = req.set("Accept", accept);
+        }
+        let result = match body {
+            Some(body) => req
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None => req.call(),
+        };
+        let resp = match &result {
+            Ok(resp) | Err(ureq::Error::Status(_, resp)) => Some(resp),
+            Err(_) => None,
+        };
+        let rate_limit = resp.and_then(rate_limit_from_headers);
+        if let Some(rate_limit) = rate_limit {
+            *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner()) = Some(rate_limit);
+        }
+        match result {
+            Ok(resp) => {
+                let mut bytes = Vec::new();
+                resp.into_reader().read_to_end(&mut bytes).map_err(|e| {
+                    McpError::new(format!("{} {}: reading body: {}", method, url, e))
+                })?;
+                Ok(bytes)
+            }
+            Err(ureq::Error::Status(code, resp)) => {
+                let retry_after = resp.header("Retry-After").and_then(parse_retry_after);
+                let body = resp.into_string().unwrap_or_default();
+                let (kind, retry_after) = classify_status(code, &body, retry_after, rate_limit);
+                Err(McpError::new(format!(
+                    "{} {}: HTTP {}: {}",
+                    method,
+                    url,
+                    code,
+                    truncate(body.trim(), 300)
+                ))
+                .with_kind(kind)
+                .with_retry_after(retry_after))
+            }
+            Err(ureq::Error::Transport(t)) => {
+                let kind = transport_kind(&t);
+                Err(McpError::new(format!("{} {}: {}", method, url, t)).with_kind(kind))
+            }
+        }
+    }
+}
+
+/// Kind and wait for an HTTP error status. 403 counts as a rate limit when the quota is
+/// exhausted or the body mentions a (GitHub secondary) rate limit. Without `Retry-After`, rate
+/// limits wait for the quota reset if the headers give one.
+fn classify_status(
+    code: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+    rate_limit: Option<RateLimit>,
+) -> (McpErrorKind, Option<Duration>) {
+    let exhausted = rate_limit.is_some_and(|r| r.remaining == 0);
+    let until_reset = rate_limit
+        .and_then(|r| r.reset_at)
+        .map(|reset| Duration::from_secs(reset.saturating_sub(now_secs())));
+    if code == 429 || (code == 403 && exhausted) {
+        return (McpErrorKind::RateLimited, retry_after.or(until_reset));
+    }
+    if code == 403 && body.to_ascii_lowercase().contains("rate limit") {
+        return (
+            McpErrorKind::RateLimited,
+            retry_after.or(Some(SECONDARY_RATE_LIMIT_WAIT)),
+        );
+    }
+    let kind = match code {
+        401 | 403 => McpErrorKind::Auth,
+        404 | 410 => McpErrorKind::NotFound,
+        408 => McpErrorKind::Timeout,
+        500..=599 => McpErrorKind::Server,
+        _ => McpErrorKind::Client,
+    };
+    (kind, retry_after)
+}
+
+/// `Timeout` for I/O timeouts, `Network` for other connection failures, `Other` for local
+/// problems (bad URL, proxy configuration).
+fn transport_kind(t: &ureq::Transport) -> McpErrorKind {
+    use std::error::Error as _;
+    match t.kind() {
+        ureq::ErrorKind::Io => {
+            let timed_out = t
+                .source()
+                .and_then(|e| e.downcast_ref::<std::io::Error>())
+                .is_some_and(|e| {
+                    matches!(
+                        e.kind(),
+                        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+                    )
+                });
+            if timed_out {
+                McpErrorKind::Timeout
+            } else {
+                McpErrorKind::Network
+            }
+        }
+        ureq::ErrorKind::Dns
+        | ureq::ErrorKind::ConnectionFailed
+        | ureq::ErrorKind::ProxyConnect
+        | ureq::ErrorKind::BadStatus
+        | ureq::ErrorKind::BadHeader => McpErrorKind::Network,
+        _ => McpErrorKind::Other,
+    }
+}
+
+/// `X-RateLimit-*` (GitHub, Gitea) or `RateLimit-*` (GitLab) headers; `None` without a remaining count.
+fn rate_limit_from_headers(resp: &ureq::Response) -> Option<RateLimit> {
+    let header = |name: &str| {
+        resp.header(&format!("X-RateLimit-{}", name))
+            .or_else(|| resp.header(&format!("RateLimit-{}", name)))
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+    Some(RateLimit {
+        limit: header("Limit"),
+        remaining: header("Remaining")?,
+        reset_at: header("Reset"),
+    })
+}
+
+/// `Retry-After` as delay-seconds or an HTTP date (`Sun, 06 Nov 1994 08:49:37 GMT`); a date in
+/// the past means no wait.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = http_date_secs(value)?;
+    Some(Duration::from_secs(at.saturating_sub(now_secs())))
+}
+
+/// Seconds since the Unix epoch of an IMF-fixdate (the HTTP date format).
+fn http_date_secs(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_weekday, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let day: u64 = day.parse().ok()?;
+    let year: u64 = year.parse().ok()?;
+    let mut hms = time.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, s) = (hms.next()??, hms.next()??, hms.next()??);
+    if year < 1970 || !(1..=31).contains(&day) || h > 23 || m > 59 || s > 60 {
+        return None;
+    }
+    // Days from 1970-01-01 to the date (proleptic Gregorian, years starting in March).
+    let (y, mp) = if month > 2 { (year, month - 3) } else { (year - 1, month + 9) };
+    let era_days = y / 400 * 146_097;
+    let yoe = y % 400;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era_days + doe).checked_sub(719_468)?;
+    Some(days * 86_400 + h * 3_600 + m * 60 + s)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn parse_json(method: &str, url: &str, body: &[u8]) -> Result<Value, McpError> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(body).map_err(|e| {
+        McpError::new(format!("{} {}: invalid JSON: {}", method, url, e)).with_kind(McpErrorKind::Parse)
+    })
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
+}
+
+/// Reads a token from the first set, non-empty environment variable in `names`.
+pub fn token_from_env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|n| std::env::var(n).ok())
+        .map(|v| v.trim().to_string())
+        .find(|v| !v.is_empty())
+}
@@ -0,0 +1,83 @@
+//! JSON-RPC 2.0 messages as used by MCP over stdio (one JSON object per line).
+//!
+//! Shared by the MCP client (`mcp_client`) and any server side; only builds and classifies
+//! `serde_json::Value`s, no I/O.
+
+use serde_json::{json, Value};
+
+/// Value of the `jsonrpc` field.
+pub const VERSION: &str = "2.0";
+
+/// Standard error codes.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Request expecting a response with the same `id`.
+pub fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": VERSION, "id": id, "method": method, "params": params })
+}
+
+/// Notification (no `id`, no response).
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": VERSION, "method": method, "params": params })
+}
+
+/// Successful response to request `id`.
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": VERSION, "id": id, "result": result })
+}
+
+/// Error response to request `id` (`Value::Null` if the id could not be read).
+pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": VERSION, "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Kind of an incoming message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming {
+    /// Has `method` and `id`.
+    Request { id: Value, method: String, params: Value },
+    /// Has `method`, no `id`.
+    Notification { method: String, params: Value },
+    /// Has `id` and `result`.
+    Response { id: Value, result: Value },
+    /// Has `id` and `error`.
+    Error { id: Value, code: i64, message: String },
+}
+
+/// Classifies a parsed message; `None` if it is not a JSON-RPC 2.0 object.
+pub fn classify(msg: &Value) -> Option<Incoming> {
+    if msg.get("jsonrpc")?.as_str()? != VERSION {
+        return None;
+    }
+    let id = msg.get("id").cloned();
+    if let Some(method) = msg.get("method").and_then(Value::as_str) {
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+        return Some(match id {
+            Some(id) => Incoming::Request {
+                id,
+                method: method.to_string(),
+                params,
+            },
+            None => Incoming::Notification {
+                method: method.to_string(),
+                params,
+            },
+        });
+    }
+    let id = id?;
+    if let Some(error) = msg.get("error") {
+        return Some(Incoming::Error {
+            id,
+            code: error["code"].as_i64().unwrap_or(INTERNAL_ERROR),
+            message: error["message"].as_str().unwrap_or_default().to_string(),
+        });
+    }
+    Some(Incoming::Response {
+        id,
+        result: msg.get("result").cloned().unwrap_or(Value::Null),
+    })
+}
@@ -0,0 +1,153 @@
+//! Known forge hosts: maps a hostname to its `Platform` and REST API base URL.
+//!
+//! Used by `PrUrl::parse_with_hosts` to recognize self-hosted instances (GitHub Enterprise Server,
+//! on-prem GitLab, Bitbucket Data Center, Gitea / Forgejo, Gerrit, Azure DevOps Server). Defaults cover the public SaaS hosts;
+//! more hosts come from `with_host` or the `QUICK_REVIEW_HOSTS` environment variable.
+
+use crate::pr_url::Platform;
+
+/// Environment variable holding extra hosts, e.g. `github=ghe.corp.com,gitlab=git.corp.com`.
+///
+/// Each entry is `platform=host` or `platform=host=api_base` to override the default API base.
+pub const HOSTS_ENV: &str = "QUICK_REVIEW_HOSTS";
+
+/// One known host: which platform it runs and where its REST API lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownHost {
+    pub host: String,
+    pub platform: Platform,
+    pub api_base: String,
+}
+
+/// List of hosts `PrUrl` parsing accepts. Lookup is by exact host (including port, if any).
+#[derive(Debug, Clone)]
+pub struct KnownHosts {
+    hosts: Vec<KnownHost>,
+}
+
+impl Default for KnownHosts {
+    /// The public SaaS hosts: github.com, gitlab.com, bitbucket.org, codeberg.org (Forgejo),
+    /// review.gerrithub.io and dev.azure.com.
+    fn default() -> Self {
+        Self::empty()
+            .with_host("github.com", Platform::GitHub)
+            .with_host("gitlab.com", Platform::GitLab)
+            .with_host("bitbucket.org", Platform::Bitbucket)
+            .with_host("codeberg.org", Platform::Gitea)
+            .with_host("review.gerrithub.io", Platform::Gerrit)
+            .with_host("dev.azure.com", Platform::azure_devops_unscoped())
+    }
+}
+
+impl KnownHosts {
+    /// Creates the default list (see `Default`).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a list with no hosts at all.
+    pub fn empty() -> Self {
+        Self { hosts: Vec::new() }
+    }
+
+    /// Builder-style: add (or replace) a host with the platform's default API base.
+    pub fn with_host(self, host: impl Into<String>, platform: Platform) -> Self {
+        let host = host.into();
+        let api_base = default_api_base(&platform, &host);
+        self.with_api_base(host, platform, api_base)
+    }
+
+    /// Builder-style: add (or replace) a host with an explicit API base URL.
+    pub fn with_api_base(
+        mut self,
+        host: impl Into<String>,
+        platform: Platform,
+        api_base: impl Into<String>,
+    ) -> Self {
+        let host = host.into().to_ascii_lowercase();
+        let entry = KnownHost {
+            host,
+            platform,
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+        };
+        self.hosts.retain(|h| h.host != entry.host);
+        self.hosts.push(entry);
+        self
+    }
+
+    /// Default hosts plus entries from `QUICK_REVIEW_HOSTS` (if set).
+    pub fn from_env() -> Result<Self, KnownHostsError> {
+        match std::env::var(HOSTS_ENV) {
+            Ok(spec) => Self::default().with_spec(&spec),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Adds entries from a spec string in the `QUICK_REVIEW_HOSTS` format.
+    pub fn with_spec(mut self, spec: &str) -> Result<Self, KnownHostsError> {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = entry.splitn(3, '=');
+            let platform_name = parts.next().unwrap_or("").trim();
+            let host = parts.next().map(str::trim).unwrap_or("");
+            let platform = Platform::from_name(platform_name).ok_or_else(|| KnownHostsError {
+                message: format!("unknown platform '{}' in host entry '{}'", platform_name, entry),
+            })?;
+            if host.is_empty() {
+                return Err(KnownHostsError {
+                    message: format!("missing host in entry '{}'", entry),
+                });
+            }
+            self = match parts.next().map(str::trim) {
+                Some(api_base) if !api_base.is_empty() => self.with_api_base(host, platform, api_base),
+                _ => self.with_host(host, platform),
+            };
+        }
+        Ok(self)
+    }
+
+    /// Looks up a host (case-insensitive).
+    pub fn lookup(&self, host: &str) -> Option<&KnownHost> {
+        let host = host.to_ascii_lowercase();
+        self.hosts.iter().find(|h| h.host == host)
+    }
+
+    /// All known hosts, in insertion order.
+    pub fn hosts(&self) -> &[KnownHost] {
+        &self.hosts
+    }
+}
+
+/// Default REST API base for a platform on a host.
+///
+/// github.com uses `https://api.github.com`; GitHub Enterprise Server uses `https://{host}/api/v3`.
+/// GitLab (SaaS and self-managed) uses `https://{host}/api/v4`.
+/// bitbucket.org uses `https://api.bitbucket.org/2.0`; Bitbucket Data Center uses `https://{host}/rest/api/1.0`.
+/// Gitea / Forgejo use `https://{host}/api/v1`; Gerrit serves REST at the root (`/a/` when
+/// authenticated); Azure DevOps uses `https://{host}` (the
+/// organization and project are part of each API path).
+pub fn default_api_base(platform: &Platform, host: &str) -> String {
+    match platform {
+        Platform::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+        Platform::GitHub => format!("https://{}/api/v3", host),
+        Platform::GitLab => format!("https://{}/api/v4", host),
+        Platform::Bitbucket if host == "bitbucket.org" => "https://api.bitbucket.org/2.0".to_string(),
+        Platform::Bitbucket => format!("https://{}/rest/api/1.0", host),
+        Platform::Gitea => format!("https://{}/api/v1", host),
+        Platform::Gerrit => format!("https://{}", host),
+        Platform::AzureDevOps { .. } => format!("https://{}", host),
+    }
+}
+
+/// Error from parsing a host spec (unknown platform, missing host).
+#[derive(Debug)]
+pub struct KnownHostsError {
+    pub message: String,
+}
+
+impl std::fmt::Display for KnownHostsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for KnownHostsError {}
@@ -1,21 +1,34 @@
 //! quick-review library: types and pipeline for agent-powered PR/MR review.
 //!
-//! Main flow: parse PR/MR URL → fetch content via MCP → run agent review (opencode-sdk) → post back.
+//! Main flow: parse PR/MR URL (or commit / compare / local range) → fetch content via MCP → run agent review (opencode-sdk) → post back.
 //! See `review_pipeline::ReviewPipeline` for the orchestration type.
 
 pub mod agent_reviewer;
 pub mod cli;
+pub mod commit_hygiene;
+pub mod diff_model;
+pub mod git_remote;
+pub mod http;
+pub mod jsonrpc;
+pub mod known_hosts;
+pub mod mcp_client;
 pub mod mcp_provider;
+pub mod mcp_server;
 pub mod pr_url;
+pub mod providers;
 pub mod review_agent;
 pub mod review_input;
 pub mod review_pipeline;
 pub mod review_result;
+pub mod review_target;
+pub mod text_diff;
 
-pub use agent_reviewer::AgentReviewer;
-pub use mcp_provider::McpProvider;
+pub use agent_reviewer::{AgentReviewer, AsyncAgentReviewer};
+pub use known_hosts::KnownHosts;
+pub use mcp_provider::{AsyncMcpProvider, McpProvider};
 pub use pr_url::PrUrl;
 pub use review_agent::LangGraphReviewAgent;
 pub use review_input::ReviewInput;
 pub use review_pipeline::ReviewPipeline;
 pub use review_result::ReviewResult;
+pub use review_target::ReviewTarget;
@@ -1,45 +1,73 @@
-//! Binary entry: parse PR/MR URL from args, run review pipeline.
+//! Binary entry: parse the review target (PR/MR, commit, compare or local range) from args, run review pipeline.
+//! `quick-review mcp-serve` instead serves the review tools over stdio (see `mcp_server`).
 //!
-//! Uses MockMcpProvider and LangGraphReviewAgent (ReAct). The agent decides when to call MCP (fetch/post); replace with real MCP and LLM for production.
+//! Uses LocalGitProvider for local ranges, PatchProvider for patch files / stdin, ForgeProvider (the platform's REST provider, or the MCP server from QUICK_REVIEW_MCP_COMMAND) otherwise, and LangGraphReviewAgent (ReAct). The agent decides when to call MCP (fetch/post); replace the mock LLM for production.
 
 use std::sync::Arc;
 
 use langgraph::{MockLlm, ToolCall};
-use quick_review::cli::{parse_pr_url_from_args, run_pipeline};
-use quick_review::pr_url::PrUrl;
-use quick_review::review_input::ReviewInput;
-use quick_review::review_result::ReviewResult;
+use quick_review::agent_reviewer::BlockingAgentReviewer;
+use quick_review::cli::{
+    parse_review_target_from_args_with_hosts, run_pipeline, split_flags, CliFlags, COMMIT_HYGIENE_FLAG,
+    MCP_SERVE_COMMAND, NO_CACHE_FLAG,
+};
+use quick_review::known_hosts::{KnownHosts, HOSTS_ENV};
+use quick_review::mcp_server::{McpServer, ServeProvider};
+use quick_review::providers::{
+    CachingProvider, ForgeProvider, LocalGitProvider, PatchProvider, RecordingProvider, ReplayProvider,
+    RetryingProvider,
+};
+use quick_review::review_target::ReviewTarget;
 use quick_review::{LangGraphReviewAgent, McpProvider, ReviewPipeline};
 
-/// Placeholder MCP provider: returns fixed input on fetch, no-op post.
-struct MockMcpProvider;
-impl McpProvider for MockMcpProvider {
-    fn fetch(&self, _pr: &PrUrl) -> Result<ReviewInput, quick_review::mcp_provider::McpError> {
-        Ok(ReviewInput::new()
-            .with_title("Mock PR")
-            .with_description("Placeholder")
-            .with_diff(""))
-    }
-    fn post_review(
-        &self,
-        _pr: &PrUrl,
-        _result: &ReviewResult,
-    ) -> Result<(), quick_review::mcp_provider::McpError> {
-        Ok(())
+/// Provider for forge targets (each platform's REST provider, see `ForgeProvider`), retrying
+/// transient failures and rate limits.
+fn forge_provider() -> Result<Arc<dyn McpProvider + Send + Sync>, Box<dyn std::error::Error>> {
+    Ok(Arc::new(RetryingProvider::new(Arc::new(ForgeProvider::from_env()?))))
+}
+
+/// Wraps `provider` in the persistent fetch cache unless `--no-cache` was given.
+fn with_cache(
+    provider: Arc<dyn McpProvider + Send + Sync>,
+    flags: CliFlags,
+) -> Result<Arc<dyn McpProvider + Send + Sync>, Box<dyn std::error::Error>> {
+    if flags.no_cache {
+        return Ok(provider);
     }
+    Ok(Arc::new(CachingProvider::from_env(provider)?.with_refresh(flags.refresh_cache)))
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    let pr = match parse_pr_url_from_args(&args) {
-        Some(p) => p,
-        None => {
-            eprintln!("Usage: quick-review <PR_OR_MR_URL>");
-            eprintln!("Example: quick-review https://github.com/owner/repo/pull/123");
-            std::process::exit(1);
-        }
+/// Replays `QUICK_REVIEW_REPLAY` if set, otherwise `provider`; records to `QUICK_REVIEW_RECORD` if set.
+/// The recorder is returned too, for `report_cassette`.
+#[allow(clippy::type_complexity)]
+fn with_cassettes(
+    provider: Arc<dyn McpProvider + Send + Sync>,
+) -> Result<(Arc<dyn McpProvider + Send + Sync>, Option<Arc<RecordingProvider>>), Box<dyn std::error::Error>> {
+    let provider: Arc<dyn McpProvider + Send + Sync> = match std::env::var_os("QUICK_REVIEW_REPLAY") {
+        Some(path) => Arc::new(ReplayProvider::from_file(std::path::Path::new(&path))?),
+        None => provider,
     };
+    Ok(match std::env::var_os("QUICK_REVIEW_RECORD") {
+        Some(path) => {
+            let recorder = Arc::new(RecordingProvider::new(provider, path));
+            (recorder.clone(), Some(recorder))
+        }
+        None => (provider, None),
+    })
+}
+
+/// Warns if the cassette could not be written; the run's own outcome is unaffected.
+fn report_cassette(recorder: Option<Arc<RecordingProvider>>) {
+    if let Some(e) = recorder.and_then(|r| r.save_error()) {
+        eprintln!("Warning: cassette not saved: {}", e);
+    }
+}
 
+/// Blocking review agent over `mcp` with a mock LLM; `commit_hygiene` adds the commit-history review.
+fn build_agent(
+    mcp: Arc<dyn McpProvider + Send + Sync>,
+    commit_hygiene: bool,
+) -> Result<BlockingAgentReviewer<LangGraphReviewAgent>, Box<dyn std::error::Error>> {
     // Mock LLM: first round get_pr_context, then submit_review (agent decides MCP calls).
     let mock_llm = MockLlm::new(
         "",
@@ -56,10 +84,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
     );
-    let mcp = Arc::new(MockMcpProvider);
-    let agent = LangGraphReviewAgent::new(Arc::new(mock_llm), mcp)
-        .map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?;
-    let pipeline = ReviewPipeline::new(agent);
-    run_pipeline(&pipeline, &pr)?;
-    Ok(())
+    let agent = LangGraphReviewAgent::from_blocking_provider(Arc::new(mock_llm), mcp)
+        .with_commit_hygiene(commit_hygiene);
+    BlockingAgentReviewer::new(agent).map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (args, flags) = split_flags(&std::env::args().collect::<Vec<_>>());
+    let hosts = match KnownHosts::from_env() {
+        Ok(h) => h,
+        Err(e) => {
+            eprintln!("Invalid {}: {}", HOSTS_ENV, e);
+            std::process::exit(1);
+        }
+    };
+    if args.get(1).map(String::as_str) == Some(MCP_SERVE_COMMAND) {
+        // Posting stays off unless a review_pr call asks for it.
+        let (mcp, recorder) = with_cassettes(with_cache(forge_provider()?, flags)?)?;
+        let provider = Arc::new(ServeProvider::new(mcp));
+        let pipeline = ReviewPipeline::new(build_agent(provider.clone(), flags.commit_hygiene)?);
+        let mut server = McpServer::new(pipeline, provider).with_hosts(hosts);
+        let outcome = server.serve(std::io::stdin().lock(), std::io::stdout().lock());
+        report_cassette(recorder);
+        return Ok(outcome?);
+    }
+    let target = match parse_review_target_from_args_with_hosts(&args, &hosts, std::path::Path::new(".")) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!("Usage: quick-review <PR_OR_MR_URL | COMMIT_URL | COMPARE_URL | #N | !N | owner/repo#N | BASE..HEAD>");
+            eprintln!("Example: quick-review https://github.com/owner/repo/pull/123");
+            eprintln!("Inside a checkout: quick-review #123 (platform and repo from the origin remote)");
+            eprintln!("Local range: quick-review main..feature");
+            eprintln!("Patch, mbox series or stdin: quick-review fix.patch | quick-review series.mbox | git diff | quick-review -");
+            eprintln!("Skip the fetch cache: quick-review {} <URL>", NO_CACHE_FLAG);
+            eprintln!("Also review commit messages and history: quick-review {} <URL>", COMMIT_HYGIENE_FLAG);
+            eprintln!("MCP server for editors and agents: quick-review {}", MCP_SERVE_COMMAND);
+            eprintln!("Self-hosted instances: set {}=github=ghe.example.com,gitlab=git.example.com", HOSTS_ENV);
+            std::process::exit(1);
+        }
+    };
+
+    // Local ranges and patches need no forge: read the checkout or patch and print the review.
+    let mcp: Arc<dyn McpProvider + Send + Sync> = match target {
+        ReviewTarget::LocalRange { .. } => Arc::new(LocalGitProvider::new()),
+        ReviewTarget::Patch { .. } => Arc::new(PatchProvider::new()),
+        _ => with_cache(forge_provider()?, flags)?,
+    };
+    let (mcp, recorder) = with_cassettes(mcp)?;
+    let pipeline = ReviewPipeline::new(build_agent(mcp, flags.commit_hygiene)?);
+    let outcome = run_pipeline(&pipeline, &target);
+    report_cassette(recorder);
+    outcome
 }
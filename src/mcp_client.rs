@@ -0,0 +1,273 @@
+//! Minimal MCP client over stdio: spawns a server command and calls its tools via JSON-RPC.
+//!
+//! `McpClient::spawn` starts the process, performs the `initialize` / `notifications/initialized`
+//! handshake and reads `tools/list`. `call_tool` returns the text content of a `tools/call` result;
+//! `isError` results, JSON-RPC errors, a closed pipe and timeouts all become `McpError`.
+//! Used by `providers::McpStdioProvider`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::jsonrpc::{self, Incoming};
+use crate::mcp_provider::{McpError, McpErrorKind};
+
+/// MCP protocol revision sent in `initialize`.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Default time to wait for any single response.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How to start an MCP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpServerConfig {
+    pub command: String,
+    pub args: Vec<String>,
+    /// Extra environment variables for the server (e.g. its token).
+    pub env: Vec<(String, String)>,
+    /// Per-request timeout.
+    pub timeout: Duration,
+}
+
+impl McpServerConfig {
+    /// Config for `command` with no args, no extra env and the default timeout.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Parses a whitespace-separated command line (`github-mcp --stdio`); no shell quoting.
+    pub fn from_command_line(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let command = words.next()?;
+        Some(Self::new(command).with_args(words.map(String::from).collect()))
+    }
+
+    /// Builder-style: set arguments.
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Builder-style: add an environment variable for the server.
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    /// Builder-style: set the per-request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Connected MCP server process. The process is killed on drop.
+pub struct McpClient {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+    timeout: Duration,
+    server_name: String,
+    tools: Vec<String>,
+}
+
+impl McpClient {
+    /// Spawns the server and completes the handshake (`initialize`, `initialized`, `tools/list`).
+    pub fn spawn(config: &McpServerConfig) -> Result<Self, McpError> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(config.env.iter().map(|(k, v)| (k, v)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                McpError::new(format!("starting MCP server '{}': {}", config.command, e))
+            })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (tx, lines) = mpsc::channel();
+        // Reader thread: ends when the server closes stdout (or the client is dropped).
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut client = Self {
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+            timeout: config.timeout,
+            server_name: config.command.clone(),
+            tools: Vec::new(),
+        };
+        let init = client.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "quick-review", "version": env!("CARGO_PKG_VERSION") }
+            }),
+        )?;
+        if let Some(name) = init["serverInfo"]["name"].as_str() {
+            client.server_name = name.to_string();
+        }
+        client.send(&jsonrpc::notification("notifications/initialized", json!({})))?;
+        client.tools = client.list_tools()?;
+        Ok(client)
+    }
+
+    /// Server name from `initialize` (or the command if the server did not send one).
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// Tool names from `tools/list`.
+    pub fn tools(&self) -> &[String] {
+        &self.tools
+    }
+
+    /// True if the server advertised tool `name`.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t == name)
+    }
+
+    /// False once the server process has exited.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Calls a tool and returns its text content (text parts joined by newlines).
+    pub fn call_tool(&mut self, name: &str, arguments: Value) -> Result<String, McpError> {
+        let result = self.request("tools/call", json!({ "name": name, "arguments": arguments }))?;
+        let text = result["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|c| c["type"] == "text")
+            .filter_map(|c| c["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if result["isError"].as_bool().unwrap_or(false) {
+            return Err(McpError::new(format!(
+                "MCP tool '{}' failed: {}",
+                name, text
+            )));
+        }
+        Ok(text)
+    }
+
+    /// All pages of `tools/list`.
+    fn list_tools(&mut self) -> Result<Vec<String>, McpError> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let page = self.request("tools/list", params)?;
+            tools.extend(
+                page["tools"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t["name"].as_str().map(String::from)),
+            );
+            cursor = page["nextCursor"].as_str().map(String::from);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Sends a request and waits for its response, skipping notifications and unrelated lines.
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, McpError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&jsonrpc::request(id, method, params))?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(McpError::new(format!(
+                        "MCP server '{}': no response to {} within {:?}",
+                        self.server_name, method, self.timeout
+                    ))
+                    .with_kind(McpErrorKind::Timeout))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(McpError::new(format!(
+                        "MCP server '{}' exited during {}",
+                        self.server_name, method
+                    ))
+                    .with_kind(McpErrorKind::Network))
+                }
+            };
+            // Servers may log to stdout; anything that is not JSON-RPC is ignored.
+            let parsed = serde_json::from_str::<Value>(&line).ok();
+            let Some(msg) = parsed.as_ref().and_then(jsonrpc::classify) else {
+                continue;
+            };
+            match msg {
+                Incoming::Response { id: rid, result } if rid == id => return Ok(result),
+                Incoming::Error {
+                    id: rid,
+                    code,
+                    message,
+                } if rid == id => {
+                    return Err(McpError::new(format!(
+                        "MCP server '{}': {} failed ({}): {}",
+                        self.server_name, method, code, message
+                    )))
+                }
+                // Server-initiated requests (e.g. `ping`, sampling) are not supported; answer so
+                // the server does not wait on us.
+                Incoming::Request { id: rid, method: m, .. } => {
+                    let reply = if m == "ping" {
+                        jsonrpc::response(rid, json!({}))
+                    } else {
+                        jsonrpc::error_response(rid, jsonrpc::METHOD_NOT_FOUND, "not supported by client")
+                    };
+                    self.send(&reply)?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&mut self, msg: &Value) -> Result<(), McpError> {
+        let mut line = msg.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| {
+                McpError::new(format!(
+                    "writing to MCP server '{}': {}",
+                    self.server_name, e
+                ))
+            })
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -1,25 +1,212 @@
-//! MCP provider trait: fetch PR/MR content and (optionally) post review back.
+//! MCP provider trait: fetch review content for a `ReviewTarget` and (optionally) post review back.
 //!
 //! Implemented by MCP client wrappers (e.g. github-mcp, gitlab-mcp). Used by `ReviewPipeline`.
 //! Tests can use a mock that returns fixed `ReviewInput` and records `post_review` calls.
+//! `AsyncMcpProvider` is the async counterpart used by the review agent; `BlockingMcpProvider`
+//! adapts any blocking provider to it by running calls on tokio's blocking thread pool.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
 
-use crate::pr_url::PrUrl;
 use crate::review_input::ReviewInput;
 use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
 
-/// Fetches PR/MR content from GitHub or GitLab via MCP; may post review back.
+/// Fetches review content (PR/MR, commit, compare or local range) via MCP; may post review back.
 pub trait McpProvider: Send + Sync {
-    /// Fetches review input for the given PR/MR. Caller uses this before invoking `AgentReviewer`.
-    fn fetch(&self, pr: &PrUrl) -> Result<ReviewInput, McpError>;
+    /// Fetches review input for the given target. Caller uses this before invoking `AgentReviewer`.
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError>;
+
+    /// Posts the review result for the target. Only called when `supports_posting` is true.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError>;
+
+    /// True if `post_review` can deliver a review of `target`. Defaults to
+    /// `ReviewTarget::supports_posting` (PR/MR only).
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        target.supports_posting()
+    }
+
+    /// Current head commit SHA of the target if it can be looked up cheaply (one metadata
+    /// request, no diff). Used as a cache key; defaults to `None` (unknown).
+    fn head_sha(&self, _target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        Ok(None)
+    }
 
-    /// Posts the review result to the PR/MR. Optional in v1 (e.g. output to stdout only).
-    fn post_review(&self, pr: &PrUrl, result: &ReviewResult) -> Result<(), McpError>;
+    /// API quota as of the provider's last response, if the forge reports one.
+    fn rate_limit(&self) -> Option<RateLimit> {
+        None
+    }
+}
+
+/// Async version of `McpProvider`, for use from async code (e.g. agent tools) without blocking
+/// runtime workers during network I/O.
+#[async_trait]
+pub trait AsyncMcpProvider: Send + Sync {
+    /// Fetches review input for the given target.
+    async fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError>;
+
+    /// Posts the review result for the target. Only called when `supports_posting` is true.
+    async fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError>;
+
+    /// Same as `McpProvider::supports_posting`.
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        target.supports_posting()
+    }
+
+    /// Same as `McpProvider::head_sha`.
+    async fn head_sha(&self, _target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        Ok(None)
+    }
+
+    /// Same as `McpProvider::rate_limit`.
+    fn rate_limit(&self) -> Option<RateLimit> {
+        None
+    }
+}
+
+/// `AsyncMcpProvider` over a blocking `McpProvider`: each call runs in `tokio::task::spawn_blocking`.
+/// Must be awaited inside a tokio runtime.
+pub struct BlockingMcpProvider {
+    inner: Arc<dyn McpProvider>,
+}
+
+impl BlockingMcpProvider {
+    /// Wraps a blocking provider.
+    pub fn new(inner: Arc<dyn McpProvider>) -> Self {
+        Self { inner }
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&dyn McpProvider) -> Result<T, McpError> + Send + 'static,
+    ) -> Result<T, McpError> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || call(inner.as_ref()))
+            .await
+            .map_err(|e| McpError::new(format!("provider task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl AsyncMcpProvider for BlockingMcpProvider {
+    async fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let target = target.clone();
+        self.run(move |p| p.fetch(&target)).await
+    }
+
+    async fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let (target, result) = (target.clone(), result.clone());
+        self.run(move |p| p.post_review(&target, &result)).await
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.inner.supports_posting(target)
+    }
+
+    async fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let target = target.clone();
+        self.run(move |p| p.head_sha(&target)).await
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// What went wrong, so callers can tell a bad token from a transient failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpErrorKind {
+    /// 401, or 403 that is not a rate limit: bad or missing credentials / permissions.
+    Auth,
+    /// 404 (or the target does not exist on the server).
+    NotFound,
+    /// 429, or GitHub's 403 primary / secondary rate limit.
+    RateLimited,
+    /// Request or response timed out.
+    Timeout,
+    /// Connection-level failure (DNS, refused, reset).
+    Network,
+    /// 5xx from the server.
+    Server,
+    /// Other 4xx: the request itself was rejected.
+    Client,
+    /// Response could not be parsed.
+    Parse,
+    /// Anything else (unsupported target, tool error, I/O).
+    #[default]
+    Other,
+}
+
+impl McpErrorKind {
+    /// True for failures that may succeed on retry (rate limits, timeouts, network, 5xx).
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            McpErrorKind::RateLimited
+                | McpErrorKind::Timeout
+                | McpErrorKind::Network
+                | McpErrorKind::Server
+        )
+    }
+}
+
+/// API quota reported by the forge (`X-RateLimit-*` / `RateLimit-*` headers).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    /// When the quota resets, in seconds since the Unix epoch.
+    pub reset_at: Option<u64>,
 }
 
 /// Errors from MCP operations (network, auth, parse).
 #[derive(Debug)]
 pub struct McpError {
     pub message: String,
+    pub kind: McpErrorKind,
+    /// Wait requested by the server (`Retry-After`, rate-limit reset), if any.
+    pub retry_after: Option<Duration>,
+    /// Part of the operation already took effect (e.g. some review comments were posted), so
+    /// repeating it would repeat that part.
+    pub partial: bool,
+}
+
+impl McpError {
+    /// Error of kind `Other`.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: McpErrorKind::Other,
+            retry_after: None,
+            partial: false,
+        }
+    }
+
+    /// Builder-style: set kind.
+    pub fn with_kind(mut self, kind: McpErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Builder-style: set the server-requested wait.
+    pub fn with_retry_after(mut self, retry_after: Option<Duration>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Builder-style: mark that part of the operation took effect.
+    pub fn with_partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Shorthand for `kind.is_retryable()`.
+    pub fn is_retryable(&self) -> bool {
+        self.kind.is_retryable()
+    }
 }
 
 impl std::fmt::Display for McpError {
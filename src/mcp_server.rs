@@ -0,0 +1,326 @@
+//! MCP server over stdio (`quick-review mcp-serve`): exposes reviews as tools to MCP clients.
+//!
+//! Tools: `review_pr(url, post?)`, `review_diff(diff, title?, description?)` and
+//! `get_last_review(url)`. Each review runs through `ReviewPipeline` and returns the serialized
+//! `ReviewResult` as text content. The pipeline's agent must use the server's `ServeProvider`:
+//! it serves diffs passed to `review_diff` and keeps posting off unless a call sets `post: true`.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use crate::agent_reviewer::AgentReviewer;
+use crate::jsonrpc::{self, Incoming};
+use crate::known_hosts::KnownHosts;
+use crate::mcp_client::PROTOCOL_VERSION;
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::review_input::ReviewInput;
+use crate::review_pipeline::ReviewPipeline;
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// `McpProvider` wrapper used by the server: answers `ReviewTarget::Diff` from diffs handed to
+/// `review_diff`, delegates everything else, and only allows posting while enabled.
+pub struct ServeProvider {
+    inner: Arc<dyn McpProvider>,
+    /// Diffs of `review_diff` calls in progress, keyed by a per-call target title.
+    diffs: Mutex<HashMap<String, ReviewInput>>,
+    next_diff: AtomicU64,
+    posting: AtomicBool,
+}
+
+impl ServeProvider {
+    /// Wraps `inner`; posting starts disabled.
+    pub fn new(inner: Arc<dyn McpProvider>) -> Self {
+        Self {
+            inner,
+            diffs: Mutex::new(HashMap::new()),
+            next_diff: AtomicU64::new(1),
+            posting: AtomicBool::new(false),
+        }
+    }
+
+    /// Stores `input` under a title unique to this call (`title #n`, so calls with the same
+    /// title do not see each other's diff) and returns the target serving it.
+    fn add_diff(&self, title: &str, input: ReviewInput) -> ReviewTarget {
+        let n = self.next_diff.fetch_add(1, Ordering::SeqCst);
+        let title = format!("{} #{}", title, n);
+        self.diffs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(title.clone(), input);
+        ReviewTarget::Diff { title }
+    }
+
+    /// Drops the diff of a finished `review_diff` call.
+    fn remove_diff(&self, target: &ReviewTarget) {
+        if let ReviewTarget::Diff { title } = target {
+            self.diffs.lock().unwrap_or_else(|e| e.into_inner()).remove(title);
+        }
+    }
+
+    fn set_posting(&self, enabled: bool) {
+        self.posting.store(enabled, Ordering::SeqCst);
+    }
+}
+
+impl McpProvider for ServeProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        match target {
+            ReviewTarget::Diff { title } => self
+                .diffs
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(title)
+                .cloned()
+                .ok_or_else(|| McpError::new(format!("no diff named \"{}\" was supplied", title))),
+            _ => self.inner.fetch(target),
+        }
+    }
+
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        if !self.supports_posting(target) {
+            return Err(McpError::new(format!(
+                "posting is not enabled for {}",
+                target
+            )));
+        }
+        self.inner.post_review(target, result)
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.posting.load(Ordering::SeqCst)
+            && !matches!(target, ReviewTarget::Diff { .. })
+            && self.inner.supports_posting(target)
+    }
+
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        match target {
+            ReviewTarget::Diff { .. } => Ok(None),
+            _ => self.inner.head_sha(target),
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// Stdio MCP server answering `initialize`, `tools/list` and `tools/call`.
+pub struct McpServer<A> {
+    pipeline: ReviewPipeline<A>,
+    provider: Arc<ServeProvider>,
+    hosts: KnownHosts,
+    /// Last review per target, keyed by the target's display form (canonical web URL).
+    last_reviews: HashMap<String, ReviewResult>,
+}
+
+impl<A: AgentReviewer> McpServer<A> {
+    /// Creates a server; `provider` must be the one the pipeline's agent was built with.
+    pub fn new(pipeline: ReviewPipeline<A>, provider: Arc<ServeProvider>) -> Self {
+        Self {
+            pipeline,
+            provider,
+            hosts: KnownHosts::default(),
+            last_reviews: HashMap::new(),
+        }
+    }
+
+    /// Builder-style: hosts accepted in `review_pr` / `get_last_review` URLs.
+    pub fn with_hosts(mut self, hosts: KnownHosts) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Reads newline-delimited JSON-RPC from `input` and writes responses to `output` until EOF.
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(msg) => self.handle(&msg),
+                Err(e) => Some(jsonrpc::error_response(
+                    Value::Null,
+                    jsonrpc::PARSE_ERROR,
+                    &e.to_string(),
+                )),
+            };
+            if let Some(reply) = reply {
+                writeln!(output, "{}", reply)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles one message; `None` for notifications and responses.
+    pub fn handle(&mut self, msg: &Value) -> Option<Value> {
+        let Some(incoming) = jsonrpc::classify(msg) else {
+            let id = msg.get("id").cloned().unwrap_or(Value::Null);
+            return Some(jsonrpc::error_response(
+                id,
+                jsonrpc::INVALID_REQUEST,
+                "not a JSON-RPC 2.0 message",
+            ));
+        };
+        let Incoming::Request { id, method, params } = incoming else {
+            return None;
+        };
+        Some(match method.as_str() {
+            "initialize" => jsonrpc::response(
+                id,
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "quick-review", "version": env!("CARGO_PKG_VERSION") }
+                }),
+            ),
+            "ping" => jsonrpc::response(id, json!({})),
+            "tools/list" => jsonrpc::response(id, json!({ "tools": tool_specs() })),
+            "tools/call" => {
+                let name = params["name"].as_str().unwrap_or_default();
+                let args = &params["arguments"];
+                match self.call_tool(name, args) {
+                    Some(Ok(text)) => jsonrpc::response(id, tool_result(text, false)),
+                    Some(Err(message)) => jsonrpc::response(id, tool_result(message, true)),
+                    None => jsonrpc::error_response(
+                        id,
+                        jsonrpc::INVALID_PARAMS,
+                        &format!("unknown tool: {}", name),
+                    ),
+                }
+            }
+            other => jsonrpc::error_response(
+                id,
+                jsonrpc::METHOD_NOT_FOUND,
+                &format!("method not found: {}", other),
+            ),
+        })
+    }
+
+    /// Runs a tool; `None` if there is no such tool, `Err` for tool failures (reported as `isError`).
+    fn call_tool(&mut self, name: &str, args: &Value) -> Option<Result<String, String>> {
+        Some(match name {
+            "review_pr" => self.review_pr(args),
+            "review_diff" => self.review_diff(args),
+            "get_last_review" => self.get_last_review(args),
+            _ => return None,
+        })
+    }
+
+    fn review_pr(&mut self, args: &Value) -> Result<String, String> {
+        let target = self.target_arg(args)?;
+        let post = args["post"].as_bool().unwrap_or(false);
+        self.provider.set_posting(post);
+        let outcome = self.pipeline.run(&target);
+        self.provider.set_posting(false);
+        let result = outcome.map_err(|e| e.to_string())?;
+        let text = result_json(&result);
+        self.last_reviews.insert(target.to_string(), result);
+        Ok(text)
+    }
+
+    fn review_diff(&mut self, args: &Value) -> Result<String, String> {
+        let diff = required_str(args, "diff")?;
+        let title = args["title"].as_str().unwrap_or("Untitled diff");
+        let input = ReviewInput::new()
+            .with_title(title)
+            .with_description(args["description"].as_str().unwrap_or_default())
+            .with_diff(diff);
+        let target = self.provider.add_diff(title, input);
+        let outcome = self.pipeline.run(&target);
+        self.provider.remove_diff(&target);
+        let result = outcome.map_err(|e| e.to_string())?;
+        Ok(result_json(&result))
+    }
+
+    fn get_last_review(&self, args: &Value) -> Result<String, String> {
+        let target = self.target_arg(args)?;
+        self.last_reviews
+            .get(&target.to_string())
+            .map(result_json)
+            .ok_or_else(|| format!("no review of {} in this session", target))
+    }
+
+    /// PR/MR, commit or compare target from the `url` argument (or a PR shorthand). Local
+    /// targets (patch files, `-` for stdin, `base..head` ranges, fixtures) are rejected: clients
+    /// must not read the server's files, and stdin is the JSON-RPC channel.
+    fn target_arg(&self, args: &Value) -> Result<ReviewTarget, String> {
+        let url = required_str(args, "url")?;
+        let project_path = self
+            .pipeline
+            .project_path
+            .as_deref()
+            .unwrap_or(Path::new("."));
+        match ReviewTarget::resolve(url, project_path, &self.hosts) {
+            Ok(target @ ReviewTarget::PullRequest(_))
+            | Ok(target @ ReviewTarget::Commit { .. })
+            | Ok(target @ ReviewTarget::Compare { .. }) => Ok(target),
+            Ok(target) => Err(format!(
+                "invalid review target: {} is not a PR/MR, commit or compare URL",
+                target
+            )),
+            Err(e) => Err(format!("invalid review target: {}", e)),
+        }
+    }
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args[key]
+        .as_str()
+        .ok_or_else(|| format!("missing string argument '{}'", key))
+}
+
+fn result_json(result: &ReviewResult) -> String {
+    serde_json::to_string_pretty(result).unwrap_or_default()
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+/// `tools/list` entries with JSON Schema inputs.
+fn tool_specs() -> Value {
+    json!([
+        {
+            "name": "review_pr",
+            "description": "Review a PR/MR (or commit / compare URL) and return the ReviewResult as JSON. Posts the review only if post is true.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "PR/MR, commit or compare URL" },
+                    "post": { "type": "boolean", "description": "Post the review back to the forge (default false)" }
+                },
+                "required": ["url"]
+            }
+        },
+        {
+            "name": "review_diff",
+            "description": "Review a unified diff and return the ReviewResult as JSON. Never posts.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "diff": { "type": "string", "description": "Unified diff text" },
+                    "title": { "type": "string" },
+                    "description": { "type": "string" }
+                },
+                "required": ["diff"]
+            }
+        },
+        {
+            "name": "get_last_review",
+            "description": "Return the last ReviewResult produced by review_pr for this URL in this session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "url": { "type": "string" } },
+                "required": ["url"]
+            }
+        }
+    ])
+}
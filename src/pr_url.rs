@@ -1,68 +1,643 @@
-//! Parsed PR/MR URL: platform, owner, repo, and PR/MR id.
+//! Parsed PR/MR URL: platform, host, owner, repo, and PR/MR id.
 //!
 //! Used by `McpProvider` to know which PR/MR to fetch and where to post review.
-//! Parsed from strings like `https://github.com/owner/repo/pull/123` or GitLab MR URLs.
+//! Parsed from strings like `https://github.com/owner/repo/pull/123` or GitLab MR URLs;
+//! rejected input is explained by `PrUrlParseError`. Only the PR/MR URL itself is accepted (no
+//! sub-pages, queries or fragments), so it round-trips exactly via `Display`/`FromStr` and serde.
+//! Self-hosted instances are recognized through `KnownHosts`.
+
+use crate::git_remote::read_origin;
+use crate::known_hosts::{default_api_base, KnownHost, KnownHosts};
 
 /// Supported platform for pull/merge requests.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Platform {
     GitHub,
     GitLab,
+    /// Bitbucket Cloud (bitbucket.org) or Bitbucket Data Center / Server.
+    Bitbucket,
+    /// Gitea or Forgejo (same pulls API).
+    Gitea,
+    /// Gerrit Code Review; `PrUrl::id` is the change number, `PrUrl::patchset` the optional patchset.
+    Gerrit,
+    /// Azure DevOps Repos (dev.azure.com or Azure DevOps Server). `organization` is the
+    /// collection on Azure DevOps Server. In a `KnownHosts` entry the fields are empty
+    /// (see `azure_devops_unscoped`); URL parsing fills them in.
+    AzureDevOps {
+        organization: String,
+        project: String,
+        repository: String,
+    },
+}
+
+impl Platform {
+    /// Human-readable name (e.g. for prompts and logs).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::GitHub => "GitHub",
+            Platform::GitLab => "GitLab",
+            Platform::Bitbucket => "Bitbucket",
+            Platform::Gitea => "Gitea",
+            Platform::Gerrit => "Gerrit",
+            Platform::AzureDevOps { .. } => "Azure DevOps",
+        }
+    }
+
+    /// Azure DevOps with no organization/project/repository, as used for host mapping.
+    pub fn azure_devops_unscoped() -> Self {
+        Platform::AzureDevOps {
+            organization: String::new(),
+            project: String::new(),
+            repository: String::new(),
+        }
+    }
+
+    /// True if both are the same platform, ignoring Azure DevOps scoping fields.
+    pub fn same_kind(&self, other: &Platform) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Parses a platform name as used in host config (`github`, `gitlab`, `bitbucket`, `gitea`,
+    /// `forgejo`, `gerrit`, `azure`; case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "github" => Some(Platform::GitHub),
+            "gitlab" => Some(Platform::GitLab),
+            "bitbucket" => Some(Platform::Bitbucket),
+            "gitea" | "forgejo" => Some(Platform::Gitea),
+            "gerrit" => Some(Platform::Gerrit),
+            "azure" | "azuredevops" | "azure-devops" => Some(Platform::azure_devops_unscoped()),
+            _ => None,
+        }
+    }
+
+    /// Public SaaS host for the platform (`github.com`, `gitlab.com`, `bitbucket.org`, `codeberg.org`,
+    /// `review.gerrithub.io`, `dev.azure.com`).
+    pub fn default_host(&self) -> &'static str {
+        match self {
+            Platform::GitHub => "github.com",
+            Platform::GitLab => "gitlab.com",
+            Platform::Bitbucket => "bitbucket.org",
+            Platform::Gitea => "codeberg.org",
+            Platform::Gerrit => "review.gerrithub.io",
+            Platform::AzureDevOps { .. } => "dev.azure.com",
+        }
+    }
 }
 
 /// Parsed PR (GitHub) or MR (GitLab) URL.
-#[derive(Debug, Clone)]
+///
+/// Serializes with all fields (including host and API base), so it can be stored and rebuilt exactly.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct PrUrl {
     pub platform: Platform,
+    /// Web host, e.g. `github.com` or `git.corp.example` (may include a port).
+    pub host: String,
+    /// REST API base URL for `host`, without trailing slash.
+    pub api_base: String,
+    /// Owner (GitHub user/org, Bitbucket Cloud workspace, Bitbucket Data Center project key
+    /// or `~user`) or full namespace path (GitLab, e.g. `org/team/sub`; Azure DevOps `org/project`).
     pub owner: String,
     pub repo: String,
     pub id: String,
+    /// Gerrit patchset number; `None` means the current patchset (and for other platforms).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patchset: Option<u32>,
 }
 
 impl PrUrl {
-    /// Builds a `PrUrl` from known parts. Callers typically use `parse` from a URL string.
+    /// Builds a `PrUrl` from known parts on the platform's public host. Callers typically use `parse` from a URL string.
     pub fn new(platform: Platform, owner: String, repo: String, id: String) -> Self {
+        let host = platform.default_host().to_string();
+        let api_base = default_api_base(&platform, &host);
         Self {
             platform,
+            host,
+            api_base,
             owner,
             repo,
             id,
+            patchset: None,
+        }
+    }
+
+    /// Builder-style: set the Gerrit patchset.
+    pub fn with_patchset(mut self, patchset: u32) -> Self {
+        self.patchset = Some(patchset);
+        self
+    }
+
+    /// Builder-style: set host and API base (for self-hosted instances).
+    pub fn with_host(mut self, host: impl Into<String>, api_base: impl Into<String>) -> Self {
+        self.host = host.into();
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Full project path: `owner/repo` (GitLab: `group/subgroup/.../project`); just `repo` if
+    /// there is no owner (e.g. a top-level Gerrit project).
+    pub fn project_path(&self) -> String {
+        if self.owner.is_empty() {
+            self.repo.clone()
+        } else {
+            format!("{}/{}", self.owner, self.repo)
+        }
+    }
+
+    /// URL-encoded project path as the GitLab API expects in `/projects/:id` (e.g. `org%2Fteam%2Fproject`).
+    pub fn encoded_project_path(&self) -> String {
+        encode_path_segment(&self.project_path())
+    }
+
+    /// Namespace groups from outermost to innermost (GitLab); a single owner on GitHub.
+    pub fn namespace_groups(&self) -> Vec<&str> {
+        self.owner.split('/').filter(|g| !g.is_empty()).collect()
+    }
+
+    /// Canonical web URL of the PR/MR (e.g. `https://github.com/owner/repo/pull/123`).
+    pub fn to_web_url(&self) -> String {
+        let scheme = if self.api_base.starts_with("http://") { "http" } else { "https" };
+        match &self.platform {
+            Platform::GitHub => format!(
+                "{}://{}/{}/{}/pull/{}",
+                scheme, self.host, self.owner, self.repo, self.id
+            ),
+            Platform::GitLab => format!(
+                "{}://{}/{}/{}/-/merge_requests/{}",
+                scheme, self.host, self.owner, self.repo, self.id
+            ),
+            Platform::Bitbucket if self.is_bitbucket_data_center() => {
+                let container = match self.owner.strip_prefix('~') {
+                    Some(user) => format!("users/{}", user),
+                    None => format!("projects/{}", self.owner),
+                };
+                format!(
+                    "{}://{}/{}/repos/{}/pull-requests/{}",
+                    scheme, self.host, container, self.repo, self.id
+                )
+            }
+            Platform::Bitbucket => format!(
+                "{}://{}/{}/{}/pull-requests/{}",
+                scheme, self.host, self.owner, self.repo, self.id
+            ),
+            Platform::Gitea => format!(
+                "{}://{}/{}/{}/pulls/{}",
+                scheme, self.host, self.owner, self.repo, self.id
+            ),
+            Platform::Gerrit => {
+                let mut url = format!(
+                    "{}://{}/c/{}/+/{}",
+                    scheme,
+                    self.host,
+                    self.project_path(),
+                    self.id
+                );
+                if let Some(ps) = self.patchset {
+                    url.push_str(&format!("/{}", ps));
+                }
+                url
+            }
+            Platform::AzureDevOps {
+                organization,
+                project,
+                repository,
+            } => format!(
+                "{}://{}/{}/{}/_git/{}/pullrequest/{}",
+                scheme, self.host, organization, project, repository, self.id
+            ),
+        }
+    }
+
+    /// REST API URL of the PR/MR resource (e.g. `https://api.github.com/repos/owner/repo/pulls/123`).
+    pub fn to_api_url(&self) -> String {
+        match &self.platform {
+            Platform::GitHub => format!(
+                "{}/repos/{}/{}/pulls/{}",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+            Platform::GitLab => format!(
+                "{}/projects/{}/merge_requests/{}",
+                self.api_base,
+                self.encoded_project_path(),
+                self.id
+            ),
+            Platform::Bitbucket if self.is_bitbucket_data_center() => format!(
+                "{}/projects/{}/repos/{}/pull-requests/{}",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+            Platform::Bitbucket => format!(
+                "{}/repositories/{}/{}/pullrequests/{}",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+            Platform::Gitea => format!(
+                "{}/repos/{}/{}/pulls/{}",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+            Platform::Gerrit => format!(
+                "{}/changes/{}~{}",
+                self.api_base,
+                self.encoded_project_path(),
+                self.id
+            ),
+            Platform::AzureDevOps {
+                organization,
+                project,
+                repository,
+            } => format!(
+                "{}/{}/{}/_apis/git/repositories/{}/pullRequests/{}",
+                self.api_base, organization, project, repository, self.id
+            ),
         }
     }
 
-    /// Parses a GitHub PR or GitLab MR URL into `PrUrl`.
-    /// Returns `None` if the URL format is not recognized.
+    /// True for Bitbucket Data Center / Server, whose REST API lives under `/rest/api/`.
+    pub fn is_bitbucket_data_center(&self) -> bool {
+        self.platform == Platform::Bitbucket && self.api_base.contains("/rest/api/")
+    }
+
+    /// Parses a PR/MR/change URL on one of the default `KnownHosts` (github.com, gitlab.com,
+    /// bitbucket.org, codeberg.org, review.gerrithub.io, dev.azure.com) into `PrUrl`; other hosts
+    /// go through `parse_with_hosts`.
     ///
     /// Example GitHub: `https://github.com/owner/repo/pull/123`
     /// Example GitLab: `https://gitlab.com/owner/repo/-/merge_requests/456`
-    pub fn parse(url: &str) -> Option<Self> {
-        let url = url.trim();
-        if let Some(rest) = url.strip_prefix("https://github.com/") {
-            let parts: Vec<&str> = rest.split('/').collect();
-            if parts.len() >= 4 && parts[2] == "pull" {
-                return Some(Self {
-                    platform: Platform::GitHub,
-                    owner: parts[0].to_string(),
-                    repo: parts[1].to_string(),
-                    id: parts[3].to_string(),
-                });
+    /// Example Bitbucket: `https://bitbucket.org/workspace/repo/pull-requests/7`
+    /// Example Gitea / Forgejo: `https://codeberg.org/owner/repo/pulls/8`
+    /// Example Gerrit: `https://review.gerrithub.io/c/owner/repo/+/1234/2`
+    /// Example Azure DevOps: `https://dev.azure.com/org/project/_git/repo/pullrequest/9`
+    ///
+    /// PR sub-pages (`/files`, `/diffs`, ...), query strings and `#fragments` are rejected, so
+    /// `to_web_url` gives back the input.
+    pub fn parse(url: &str) -> Result<Self, PrUrlParseError> {
+        Self::parse_with_hosts(url, &KnownHosts::default())
+    }
+
+    /// Parses a PR/MR URL on any host in `hosts` (e.g. GitHub Enterprise, self-managed GitLab).
+    pub fn parse_with_hosts(url: &str, hosts: &KnownHosts) -> Result<Self, PrUrlParseError> {
+        let (known, parts) = split_url(url, hosts)?;
+        let mut platform = known.platform.clone();
+        let mut patchset = None;
+        let (owner, repo, id) = match known.platform {
+            Platform::GitHub => parse_github_path(&parts)?,
+            Platform::GitLab => parse_gitlab_path(&parts)?,
+            Platform::Bitbucket => parse_bitbucket_path(&parts)?,
+            Platform::Gitea => parse_gitea_path(&parts)?,
+            Platform::Gerrit => {
+                let (owner, repo, id, ps) = parse_gerrit_path(&parts)?;
+                patchset = ps;
+                (owner, repo, id)
             }
+            Platform::AzureDevOps { .. } => {
+                let (organization, project, repository, id) = parse_azure_devops_path(&parts)?;
+                let owner = format!("{}/{}", organization, project);
+                platform = Platform::AzureDevOps {
+                    organization,
+                    project,
+                    repository: repository.clone(),
+                };
+                (owner, repository, id)
+            }
+        };
+        Ok(Self {
+            platform,
+            host: known.host.clone(),
+            api_base: known.api_base.clone(),
+            owner,
+            repo,
+            id,
+            patchset,
+        })
+    }
+
+    /// Resolves a full URL or a shorthand reference against the checkout at `project_path`.
+    ///
+    /// Shorthands: `#123`, `!45` (GitLab MR), `owner/repo#123`, `group/sub/project!45`; GitLab
+    /// remotes take only `!`, the others only `#`.
+    /// Host and platform come from the `origin` remote; owner/repo too unless given. Azure DevOps
+    /// repositories are `{org}/{project}/_git/{repo}` (or `{org}/{project}/{repo}` in a shorthand),
+    /// Gerrit projects any path (an `/a/` prefix of authenticated remotes is dropped).
+    pub fn resolve(
+        input: &str,
+        project_path: &std::path::Path,
+        hosts: &KnownHosts,
+    ) -> Result<Self, PrUrlParseError> {
+        let input = input.trim();
+        if input.contains("://") {
+            return Self::parse_with_hosts(input, hosts);
         }
-        if let Some(rest) = url.strip_prefix("https://gitlab.com/") {
-            let parts: Vec<&str> = rest.split('/').collect();
-            if let Some(pos) = parts.iter().position(|&p| p == "-") {
-                if pos + 2 < parts.len() && parts[pos + 1] == "merge_requests" {
-                    let id = parts[pos + 2].to_string();
-                    let (owner, repo) = (parts[0].to_string(), parts[1].to_string());
-                    return Some(Self {
-                        platform: Platform::GitLab,
-                        owner,
-                        repo,
-                        id,
-                    });
-                }
+        let (repo_part, sigil, id) = match input.rfind(['#', '!']) {
+            Some(pos) => (&input[..pos], &input[pos..pos + 1], &input[pos + 1..]),
+            None if input.is_empty() => return Err(PrUrlParseError::Empty),
+            None => return Self::parse_with_hosts(input, hosts),
+        };
+        let id = parse_id(id)?;
+        let origin = read_origin(project_path).map_err(|e| PrUrlParseError::Remote(e.to_string()))?;
+        let known = hosts
+            .lookup(&origin.host)
+            .ok_or_else(|| PrUrlParseError::UnknownHost(origin.host.clone()))?;
+        // `!N` is a GitLab MR; `#N` there is an issue, not an MR.
+        if (sigil == "!") != (known.platform == Platform::GitLab) {
+            return Err(PrUrlParseError::UnsupportedShorthand(input.to_string()));
+        }
+        let path = if repo_part.is_empty() { origin.path.as_str() } else { repo_part };
+        let (platform, owner, repo) = match &known.platform {
+            Platform::AzureDevOps { .. } => azure_devops_repo(path),
+            Platform::Gerrit => {
+                let project = if repo_part.is_empty() {
+                    path.strip_prefix("a/").unwrap_or(path)
+                } else {
+                    path
+                };
+                let (owner, repo) = project.rsplit_once('/').unwrap_or(("", project));
+                Some((Platform::Gerrit, owner.to_string(), repo.to_string()))
+                    .filter(|(_, _, repo)| !repo.is_empty())
             }
+            platform => path
+                .rsplit_once('/')
+                .filter(|(o, r)| !o.is_empty() && !r.is_empty())
+                .map(|(o, r)| (platform.clone(), o.to_string(), r.to_string())),
+        }
+        .ok_or_else(|| PrUrlParseError::UnsupportedShorthand(input.to_string()))?;
+        Ok(Self {
+            platform,
+            host: known.host.clone(),
+            api_base: known.api_base.clone(),
+            owner,
+            repo,
+            id,
+            patchset: None,
+        })
+    }
+}
+
+/// Azure DevOps platform, owner (`{org}/{project}`) and repository from a repository path:
+/// `{org}/{project}/_git/{repo}`, `{org}/_git/{repo}` (project named like the repository) or
+/// `{org}/{project}/{repo}`.
+fn azure_devops_repo(path: &str) -> Option<(Platform, String, String)> {
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    let (org, project, repo) = match parts.as_slice() {
+        [org, "_git", repo] => (*org, *repo, *repo),
+        [org, project, "_git", repo] | [org, project, repo] => (*org, *project, *repo),
+        _ => return None,
+    };
+    let platform = Platform::AzureDevOps {
+        organization: org.to_string(),
+        project: project.to_string(),
+        repository: repo.to_string(),
+    };
+    Some((platform, format!("{}/{}", org, project), repo.to_string()))
+}
+
+/// Checks the scheme, rejects `?query` / `#fragment`, looks up the host and splits the path
+/// into non-empty segments.
+///
+/// Web URLs are rebuilt with the API base's scheme, so the two must agree: an API base on the web
+/// host takes the URL's scheme (plain-http instances), and a URL whose scheme the API base on
+/// another host cannot follow (e.g. `http://github.com`) is `UnsupportedScheme`.
+pub(crate) fn split_url<'a>(
+    url: &'a str,
+    hosts: &KnownHosts,
+) -> Result<(KnownHost, Vec<&'a str>), PrUrlParseError> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err(PrUrlParseError::Empty);
+    }
+    let (scheme, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        ("https", rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        ("http", rest)
+    } else {
+        return Err(PrUrlParseError::UnsupportedScheme(url.to_string()));
+    };
+    if let Some(pos) = rest.find(['?', '#']) {
+        return Err(PrUrlParseError::QueryOrFragment(rest[pos..].to_string()));
+    }
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let mut known = hosts
+        .lookup(host)
+        .ok_or_else(|| PrUrlParseError::UnknownHost(host.to_string()))?
+        .clone();
+    let (api_scheme, api_rest) = known.api_base.split_once("://").unwrap_or(("https", ""));
+    if api_scheme != scheme {
+        let api_host = api_rest.split('/').next().unwrap_or_default();
+        if !api_host.eq_ignore_ascii_case(host) {
+            return Err(PrUrlParseError::UnsupportedScheme(url.to_string()));
+        }
+        known.api_base = format!("{}://{}", scheme, api_rest);
+    }
+    Ok((known, path.split('/').filter(|p| !p.is_empty()).collect()))
+}
+
+/// `owner/repo/pull/{id}[/subpage]` → (owner, repo, id).
+fn parse_github_path(parts: &[&str]) -> Result<(String, String, String), PrUrlParseError> {
+    if parts.len() < 4 || parts[2] != "pull" {
+        return Err(PrUrlParseError::NotAPullRequest {
+            expected: "/{owner}/{repo}/pull/{id}",
+        });
+    }
+    let id = parse_id(parts[3])?;
+    check_trailing(&parts[4..])?;
+    Ok((parts[0].to_string(), parts[1].to_string(), id))
+}
+
+/// `group/.../project/-/merge_requests/{id}[/subpage]` → (namespace, project, id).
+fn parse_gitlab_path(parts: &[&str]) -> Result<(String, String, String), PrUrlParseError> {
+    let not_mr = PrUrlParseError::NotAPullRequest {
+        expected: "/{namespace}/{project}/-/merge_requests/{id}",
+    };
+    // Namespace may be arbitrarily deep: everything before the last segment ahead of `-`.
+    let pos = parts.iter().position(|&p| p == "-").ok_or(not_mr.clone())?;
+    if pos < 2 || pos + 2 >= parts.len() || parts[pos + 1] != "merge_requests" {
+        return Err(not_mr);
+    }
+    let id = parse_id(parts[pos + 2])?;
+    check_trailing(&parts[pos + 3..])?;
+    Ok((parts[..pos - 1].join("/"), parts[pos - 1].to_string(), id))
+}
+
+/// Bitbucket Cloud `workspace/repo/pull-requests/{id}` or Data Center
+/// `projects/{KEY}/repos/{slug}/pull-requests/{id}` (`users/{user}/...` for personal repos).
+fn parse_bitbucket_path(parts: &[&str]) -> Result<(String, String, String), PrUrlParseError> {
+    let (owner, repo, rest) = match parts {
+        ["projects", key, "repos", slug, rest @ ..] => (key.to_string(), slug.to_string(), rest),
+        ["users", user, "repos", slug, rest @ ..] => (format!("~{}", user), slug.to_string(), rest),
+        [workspace, repo, rest @ ..] => (workspace.to_string(), repo.to_string(), rest),
+        _ => (String::new(), String::new(), &[][..]),
+    };
+    if rest.len() < 2 || rest[0] != "pull-requests" {
+        return Err(PrUrlParseError::NotAPullRequest {
+            expected: "/{workspace}/{repo}/pull-requests/{id} or /projects/{key}/repos/{slug}/pull-requests/{id}",
+        });
+    }
+    let id = parse_id(rest[1])?;
+    check_trailing(&rest[2..])?;
+    Ok((owner, repo, id))
+}
+
+/// `owner/repo/pulls/{id}[/subpage]` → (owner, repo, id).
+fn parse_gitea_path(parts: &[&str]) -> Result<(String, String, String), PrUrlParseError> {
+    if parts.len() < 4 || parts[2] != "pulls" {
+        return Err(PrUrlParseError::NotAPullRequest {
+            expected: "/{owner}/{repo}/pulls/{id}",
+        });
+    }
+    let id = parse_id(parts[3])?;
+    check_trailing(&parts[4..])?;
+    Ok((parts[0].to_string(), parts[1].to_string(), id))
+}
+
+/// `c/{project...}/+/{change}[/{patchset}]` → (parent path, last segment, change, patchset).
+/// Patchset ranges (`1..3`) and file paths after the patchset are rejected.
+fn parse_gerrit_path(
+    parts: &[&str],
+) -> Result<(String, String, String, Option<u32>), PrUrlParseError> {
+    let not_change = PrUrlParseError::NotAPullRequest {
+        expected: "/c/{project}/+/{change}[/{patchset}]",
+    };
+    let plus = parts.iter().position(|&p| p == "+").ok_or(not_change.clone())?;
+    if parts.first() != Some(&"c") || plus < 2 || plus + 1 >= parts.len() {
+        return Err(not_change);
+    }
+    let project = &parts[1..plus];
+    let id = parse_id(parts[plus + 1])?;
+    let patchset = parts
+        .get(plus + 2)
+        .map(|seg| {
+            parse_id(seg)?
+                .parse::<u32>()
+                .map_err(|_| PrUrlParseError::InvalidId(seg.to_string()))
+        })
+        .transpose()?;
+    check_trailing(parts.get(plus + 3..).unwrap_or_default())?;
+    let (owner, repo) = match project.split_last() {
+        Some((repo, owner)) => (owner.join("/"), repo.to_string()),
+        None => return Err(not_change),
+    };
+    Ok((owner, repo, id, patchset))
+}
+
+/// `{org}/{project}/_git/{repo}/pullrequest/{id}` → (org, project, repo, id).
+/// The project segment may be omitted when it equals the repository name.
+fn parse_azure_devops_path(
+    parts: &[&str],
+) -> Result<(String, String, String, String), PrUrlParseError> {
+    let (org, project, repo, rest) = match parts {
+        [org, project, "_git", repo, rest @ ..] => (*org, *project, *repo, rest),
+        [org, "_git", repo, rest @ ..] => (*org, *repo, *repo, rest),
+        _ => ("", "", "", &[][..]),
+    };
+    if rest.len() < 2 || rest[0] != "pullrequest" {
+        return Err(PrUrlParseError::NotAPullRequest {
+            expected: "/{organization}/{project}/_git/{repository}/pullrequest/{id}",
+        });
+    }
+    let id = parse_id(rest[1])?;
+    check_trailing(&rest[2..])?;
+    Ok((org.to_string(), project.to_string(), repo.to_string(), id))
+}
+
+pub(crate) fn parse_id(s: &str) -> Result<String, PrUrlParseError> {
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        Ok(s.to_string())
+    } else {
+        Err(PrUrlParseError::InvalidId(s.to_string()))
+    }
+}
+
+/// Rejects any path segments after the id (sub-pages like `/files` are not part of the URL).
+pub(crate) fn check_trailing(rest: &[&str]) -> Result<(), PrUrlParseError> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(PrUrlParseError::UnexpectedTrailing(rest.join("/")))
+    }
+}
+
+impl std::fmt::Display for PrUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_web_url())
+    }
+}
+
+impl std::str::FromStr for PrUrl {
+    type Err = PrUrlParseError;
+
+    /// Parses with the default hosts; use `parse_with_hosts` for self-hosted instances.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Why a string is not a recognized PR/MR URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrUrlParseError {
+    /// Input was empty or whitespace.
+    Empty,
+    /// URL does not start with `https://` or `http://`.
+    UnsupportedScheme(String),
+    /// Host is not in `KnownHosts` (see `QUICK_REVIEW_HOSTS`).
+    UnknownHost(String),
+    /// Path is not a PR/MR path on this platform (e.g. missing `/pull/`).
+    NotAPullRequest { expected: &'static str },
+    /// PR/MR id is not a number.
+    InvalidId(String),
+    /// Path segments after the PR/MR id (e.g. the `/files` sub-page).
+    UnexpectedTrailing(String),
+    /// `?query` or `#fragment` (e.g. `#discussion_r123`) after the path.
+    QueryOrFragment(String),
+    /// Commit SHA or compare range in a commit / compare URL is malformed.
+    InvalidRevision(String),
+    /// Shorthand form is not valid here (e.g. `!45` on a GitHub remote).
+    UnsupportedShorthand(String),
+    /// Shorthand needs the `origin` remote, which could not be read.
+    Remote(String),
+}
+
+impl std::fmt::Display for PrUrlParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrUrlParseError::Empty => write!(f, "empty PR/MR URL"),
+            PrUrlParseError::UnsupportedScheme(url) => {
+                write!(f, "'{}' is not an http(s) URL", url)
+            }
+            PrUrlParseError::UnknownHost(host) => write!(
+                f,
+                "unknown host '{}' (add it to {})",
+                host,
+                crate::known_hosts::HOSTS_ENV
+            ),
+            PrUrlParseError::NotAPullRequest { expected } => {
+                write!(f, "not a PR/MR URL; expected path {}", expected)
+            }
+            PrUrlParseError::InvalidId(id) => write!(f, "PR/MR id '{}' is not a number", id),
+            PrUrlParseError::UnexpectedTrailing(rest) => {
+                write!(f, "unexpected '/{}' after PR/MR id", rest)
+            }
+            PrUrlParseError::QueryOrFragment(rest) => {
+                write!(f, "unexpected '{}' in PR/MR URL; remove the query and fragment", rest)
+            }
+            PrUrlParseError::InvalidRevision(rev) => write!(f, "invalid revision '{}'", rev),
+            PrUrlParseError::UnsupportedShorthand(s) => write!(
+                f,
+                "unsupported shorthand '{}' (use #N, owner/repo#N, or !N for GitLab)",
+                s
+            ),
+            PrUrlParseError::Remote(msg) => write!(f, "cannot resolve shorthand: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PrUrlParseError {}
+
+/// Percent-encodes a string for use as a single URL path segment (RFC 3986 unreserved chars kept).
+pub fn encode_path_segment(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
         }
-        None
     }
+    out
 }
@@ -0,0 +1,312 @@
+//! Azure DevOps Repos provider: fetches PR iterations and changes; posts review threads.
+//!
+//! Azure DevOps has no unified-diff endpoint, so `fetch` reads the latest iteration's changes,
+//! downloads old/new blobs and builds the diff with `text_diff::unified_diff`; that iteration's
+//! source, target and common commits are the revisions, the PR's `/commits` its commits, and
+//! its `/threads` (without system comments) the existing threads. `post_review`
+//! creates one thread per line comment (`threadContext` on the right side) plus a summary thread,
+//! and refuses to place line comments if the source branch moved since the reviewed
+//! `ReviewResult::head_sha`.
+//! Auth: PAT from `AZURE_DEVOPS_TOKEN` / `AZURE_DEVOPS_EXT_PAT` (basic), or `SYSTEM_ACCESSTOKEN` (bearer).
+
+use serde_json::{json, Value};
+
+use super::{author, opt_str, require_pull_request, str_field, ReviewPoster};
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{
+    CommitInfo, FileContent, ReviewInput, ReviewThread, Revisions, ThreadComment,
+};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+use crate::text_diff::unified_diff;
+
+/// REST API version sent with every request.
+const API_VERSION: &str = "7.1";
+/// Max change entries per page of `/iterations/{id}/changes`.
+const CHANGES_PAGE_TOP: u64 = 2000;
+/// Max commits read from `/pullRequests/{id}/commits` (newest first; older ones are left out).
+const COMMITS_TOP: u64 = 1000;
+
+/// `McpProvider` backed by the Azure DevOps Git REST API.
+pub struct AzureDevOpsProvider {
+    http: HttpClient,
+}
+
+impl AzureDevOpsProvider {
+    /// Creates a provider with a preconfigured HTTP client (auth headers already set).
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Creates a provider with a PAT from `AZURE_DEVOPS_TOKEN` / `AZURE_DEVOPS_EXT_PAT`, or the
+    /// pipeline `SYSTEM_ACCESSTOKEN` (anonymous if none is set).
+    pub fn from_env() -> Self {
+        let http = HttpClient::new();
+        let http = if let Some(pat) = token_from_env(&["AZURE_DEVOPS_TOKEN", "AZURE_DEVOPS_EXT_PAT"]) {
+            http.with_basic_auth("", &pat)
+        } else if let Some(token) = token_from_env(&["SYSTEM_ACCESSTOKEN"]) {
+            http.with_bearer_token(&token)
+        } else {
+            http
+        };
+        Self::new(http)
+    }
+
+    /// `{pr api url}{suffix}?api-version=...{extra_query}`.
+    fn pr_url(pr: &PrUrl, suffix: &str, extra_query: &str) -> String {
+        format!(
+            "{}{}?api-version={}{}",
+            pr.to_api_url(),
+            suffix,
+            API_VERSION,
+            extra_query
+        )
+    }
+
+    /// Repository-level URL (`.../_apis/git/repositories/{repo}{suffix}`).
+    fn repo_url(pr: &PrUrl, suffix: &str, extra_query: &str) -> Result<String, McpError> {
+        match &pr.platform {
+            Platform::AzureDevOps {
+                organization,
+                project,
+                repository,
+            } => Ok(format!(
+                "{}/{}/{}/_apis/git/repositories/{}{}?api-version={}{}",
+                pr.api_base, organization, project, repository, suffix, API_VERSION, extra_query
+            )),
+            _ => Err(McpError::new(format!("not an Azure DevOps PR: {}", pr))),
+        }
+    }
+
+    /// Blob text by object id; `None` for binary content (contains NUL).
+    fn blob_text(&self, pr: &PrUrl, object_id: &str) -> Result<Option<String>, McpError> {
+        let url = Self::repo_url(pr, &format!("/blobs/{}", object_id), "&$format=text")?;
+        let bytes = self.http.get_bytes(&url, Some("text/plain"))?;
+        if bytes.contains(&0) {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// PR commits, oldest first: `commitId`, `comment` (the message) and `author`.
+    fn pr_commits(&self, pr: &PrUrl) -> Result<Vec<CommitInfo>, McpError> {
+        let url = Self::pr_url(pr, "/commits", &format!("&$top={}", COMMITS_TOP));
+        let commits = self.http.get_json(&url)?;
+        let mut commits: Vec<CommitInfo> = commits["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| CommitInfo {
+                sha: str_field(c, "commitId"),
+                author: author(&str_field(&c["author"], "name"), &str_field(&c["author"], "email")),
+                message: str_field(c, "comment"),
+                stats: None,
+                files: Vec::new(),
+            })
+            .collect();
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// PR threads with at least one user comment; `latest` is the newest iteration id.
+    fn pr_threads(&self, pr: &PrUrl, latest: u64) -> Result<Vec<ReviewThread>, McpError> {
+        let threads = self.http.get_json(&Self::pr_url(pr, "/threads", ""))?;
+        Ok(threads["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| thread_from_json(t, latest))
+            .collect())
+    }
+
+    /// Unified diff and head content for one change entry of the latest iteration.
+    fn change_to_file(&self, pr: &PrUrl, change: &Value) -> Result<(FileContent, String), McpError> {
+        let item = &change["item"];
+        let path = str_field(item, "path").trim_start_matches('/').to_string();
+        let original_path = change["originalPath"]
+            .as_str()
+            .map(|p| p.trim_start_matches('/').to_string());
+        let change_type = str_field(change, "changeType");
+        let added = change_type.contains("add");
+        let deleted = change_type.contains("delete");
+        let old_text = match item["originalObjectId"].as_str() {
+            Some(id) if !added => self.blob_text(pr, id)?,
+            _ => Some(String::new()),
+        };
+        let new_text = match item["objectId"].as_str() {
+            Some(id) if !deleted => self.blob_text(pr, id)?,
+            _ => Some(String::new()),
+        };
+        let old_path = if added {
+            None
+        } else {
+            Some(original_path.as_deref().unwrap_or(&path))
+        };
+        let new_path = if deleted { None } else { Some(path.as_str()) };
+        let diff = match (&old_text, &new_text) {
+            (Some(old), Some(new)) => unified_diff(old_path, new_path, old, new),
+            _ => format!(
+                "diff --git a/{0} b/{0}\nBinary files differ\n",
+                path
+            ),
+        };
+        let file = FileContent {
+            path: path.clone(),
+            diff: Some(diff.clone()),
+            content: if deleted { None } else { new_text },
+            status: None,
+        };
+        Ok((file, diff))
+    }
+}
+
+impl McpProvider for AzureDevOpsProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let pr = require_pull_request(target, Platform::azure_devops_unscoped(), "AzureDevOpsProvider")?;
+        let meta = self.http.get_json(&Self::pr_url(pr, "", ""))?;
+        let iterations = self.http.get_json(&Self::pr_url(pr, "/iterations", ""))?;
+        let iteration = iterations["value"]
+            .as_array()
+            .and_then(|v| {
+                v.iter()
+                    .filter(|it| it["id"].is_u64())
+                    .max_by_key(|it| it["id"].as_u64())
+            })
+            .ok_or_else(|| McpError::new(format!("PR {} has no iterations", pr)))?;
+        let latest = iteration["id"].as_u64().unwrap_or_default();
+        let mut files = Vec::new();
+        let mut diff = String::new();
+        let mut skip = 0u64;
+        loop {
+            let page = self.http.get_json(&Self::pr_url(
+                pr,
+                &format!("/iterations/{}/changes", latest),
+                &format!("&$top={}&$skip={}", CHANGES_PAGE_TOP, skip),
+            ))?;
+            for change in page["changeEntries"].as_array().into_iter().flatten() {
+                // Folder entries have no blob to diff.
+                if change["item"]["isFolder"].as_bool().unwrap_or(false) {
+                    continue;
+                }
+                let (file, file_diff) = self.change_to_file(pr, change)?;
+                diff.push_str(&file_diff);
+                files.push(file);
+            }
+            match page["nextSkip"].as_u64() {
+                Some(next) if next > 0 => skip = next,
+                _ => break,
+            }
+        }
+        let branch = |key: &str| {
+            opt_str(&meta[key]).map(|r| r.trim_start_matches("refs/heads/").to_string())
+        };
+        let revisions = Revisions {
+            base_ref: branch("targetRefName"),
+            base_sha: opt_str(&iteration["targetRefCommit"]["commitId"]),
+            head_ref: branch("sourceRefName"),
+            head_sha: opt_str(&iteration["sourceRefCommit"]["commitId"]),
+            merge_base: opt_str(&iteration["commonRefCommit"]["commitId"]),
+        };
+        Ok(ReviewInput::new()
+            .with_title(str_field(&meta, "title"))
+            .with_description(str_field(&meta, "description"))
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(self.pr_commits(pr)?)
+            .with_threads(self.pr_threads(pr, latest)?))
+    }
+
+    /// Creates one active thread per line comment (right file side), then a summary thread.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::azure_devops_unscoped(), "AzureDevOpsProvider")?;
+        let url = Self::pr_url(pr, "/threads", "");
+        let mut poster = ReviewPoster::new(&self.http);
+        if !result.line_comments.is_empty() {
+            let current = self.head_sha(target)?;
+            if let (Some(reviewed), Some(current)) = (result.head_sha.as_deref(), current) {
+                if reviewed != current {
+                    return Err(McpError::new(format!(
+                        "PR {} moved from {} to {} since it was reviewed; \
+                         not posting line comments",
+                        pr, reviewed, current
+                    )));
+                }
+            }
+        }
+        for c in &result.line_comments {
+            let body = json!({
+                "comments": [{ "parentCommentId": 0, "content": c.body, "commentType": 1 }],
+                "status": "active",
+                "threadContext": {
+                    "filePath": format!("/{}", c.path.trim_start_matches('/')),
+                    "rightFileStart": { "line": c.line, "offset": 1 },
+                    "rightFileEnd": { "line": c.line, "offset": 1 }
+                }
+            });
+            poster.post_json(&url, &body)?;
+        }
+        if !result.summary.is_empty() {
+            let body = json!({
+                "comments": [{ "parentCommentId": 0, "content": result.summary, "commentType": 1 }],
+                "status": "active"
+            });
+            poster.post_json(&url, &body)?;
+        }
+        Ok(())
+    }
+
+    /// `lastMergeSourceCommit` of the PR (the source branch head of the latest iteration).
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let pr = match target {
+            ReviewTarget::PullRequest(pr)
+                if pr.platform.same_kind(&Platform::azure_devops_unscoped()) =>
+            {
+                pr
+            }
+            _ => return Ok(None),
+        };
+        let meta = self.http.get_json(&Self::pr_url(pr, "", ""))?;
+        Ok(opt_str(&meta["lastMergeSourceCommit"]["commitId"]))
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
+
+/// `ReviewThread` from a PR thread, `None` if deleted or without user comments. Threads that are
+/// `fixed`, `closed`, `byDesign` or `wontFix` are resolved; threads placed on an iteration older
+/// than `latest` are outdated. Left-side (removed line) threads use `leftFileStart`.
+fn thread_from_json(t: &Value, latest: u64) -> Option<ReviewThread> {
+    if t["isDeleted"] == true {
+        return None;
+    }
+    let comments: Vec<ThreadComment> = t["comments"]
+        .as_array()?
+        .iter()
+        .filter(|c| c["commentType"] != "system" && c["isDeleted"] != true)
+        .map(|c| ThreadComment {
+            author: str_field(&c["author"], "displayName"),
+            body: str_field(c, "content"),
+        })
+        .collect();
+    if comments.is_empty() {
+        return None;
+    }
+    let context = &t["threadContext"];
+    let status = t["status"].as_str().unwrap_or_default();
+    let iteration = &t["pullRequestThreadContext"]["iterationContext"]["secondComparingIteration"];
+    Some(ReviewThread {
+        path: opt_str(&context["filePath"]).map(|p| p.trim_start_matches('/').to_string()),
+        line: context["rightFileStart"]["line"]
+            .as_u64()
+            .or_else(|| context["leftFileStart"]["line"].as_u64())
+            .map(|n| n as u32),
+        resolved: matches!(status, "fixed" | "closed" | "byDesign" | "wontFix"),
+        outdated: iteration.as_u64().is_some_and(|i| i < latest),
+        comments,
+    })
+}
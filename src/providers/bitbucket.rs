@@ -0,0 +1,376 @@
+//! Bitbucket provider: fetches PR metadata, diff and changed files; posts inline comments.
+//!
+//! Supports Bitbucket Cloud (`/2.0/repositories/...`) and Data Center / Server
+//! (`/rest/api/1.0/projects/...`); the flavor is chosen by `PrUrl::is_bitbucket_data_center`.
+//! Revisions come from the source / destination (Cloud) or `fromRef` / `toRef` (Data Center),
+//! commits from `/commits` (both list them newest first, without stats).
+//! Existing threads come from `/comments` (Cloud; replies name their `parent`) or the `COMMENTED`
+//! entries of `/activities` (Data Center; replies are nested).
+//! `post_review` refuses to place line comments if the PR's source commit moved since the
+//! reviewed `ReviewResult::head_sha`.
+//! Auth: `BITBUCKET_TOKEN` (bearer) or `BITBUCKET_USERNAME` + `BITBUCKET_APP_PASSWORD` (basic).
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use super::{author, file_entry, opt_str, require_pull_request, str_field, ReviewPoster};
+use crate::diff_model::LineKind;
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{CommitInfo, ReviewInput, ReviewThread, Revisions, ThreadComment};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// Page size for paginated Data Center endpoints.
+const DC_PAGE_LIMIT: u32 = 500;
+/// Page size for Cloud `/comments` (Cloud's maximum).
+const CLOUD_COMMENTS_PAGELEN: u32 = 100;
+
+/// `McpProvider` backed by the Bitbucket REST API (Cloud and Data Center).
+pub struct BitbucketProvider {
+    http: HttpClient,
+}
+
+impl BitbucketProvider {
+    /// Creates a provider with a preconfigured HTTP client (auth headers already set).
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Creates a provider with auth from `BITBUCKET_TOKEN`, or `BITBUCKET_USERNAME` +
+    /// `BITBUCKET_APP_PASSWORD`. Without either, requests are anonymous (public repos only).
+    pub fn from_env() -> Self {
+        let http = HttpClient::new();
+        let http = if let Some(token) = token_from_env(&["BITBUCKET_TOKEN"]) {
+            http.with_bearer_token(&token)
+        } else if let (Some(user), Some(password)) = (
+            token_from_env(&["BITBUCKET_USERNAME"]),
+            token_from_env(&["BITBUCKET_APP_PASSWORD"]),
+        ) {
+            http.with_basic_auth(&user, &password)
+        } else {
+            http
+        };
+        Self::new(http)
+    }
+
+    fn fetch_cloud(&self, pr: &PrUrl) -> Result<ReviewInput, McpError> {
+        let base = pr.to_api_url();
+        let meta = self.http.get_json(&base)?;
+        let diff = self.http.get_text(&format!("{}/diff", base), Some("text/plain"))?;
+        let mut files = Vec::new();
+        let mut next = Some(format!("{}/diffstat", base));
+        while let Some(url) = next {
+            let page = self.http.get_json(&url)?;
+            for entry in page["values"].as_array().into_iter().flatten() {
+                let path = entry["new"]["path"]
+                    .as_str()
+                    .or_else(|| entry["old"]["path"].as_str())
+                    .unwrap_or_default();
+                if !path.is_empty() {
+                    files.push(file_entry(path));
+                }
+            }
+            next = page["next"].as_str().map(String::from);
+        }
+        // Cloud reports abbreviated commit hashes.
+        let revisions = Revisions {
+            base_ref: opt_str(&meta["destination"]["branch"]["name"]),
+            base_sha: opt_str(&meta["destination"]["commit"]["hash"]),
+            head_ref: opt_str(&meta["source"]["branch"]["name"]),
+            head_sha: opt_str(&meta["source"]["commit"]["hash"]),
+            merge_base: None,
+        };
+        Ok(ReviewInput::new()
+            .with_title(str_field(&meta, "title"))
+            .with_description(str_field(&meta, "description"))
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(self.cloud_commits(&base)?)
+            .with_threads(self.cloud_threads(&base)?))
+    }
+
+    /// Cloud PR commits, oldest first: `hash`, `message` and the `author.raw` signature.
+    fn cloud_commits(&self, base: &str) -> Result<Vec<CommitInfo>, McpError> {
+        let mut commits = Vec::new();
+        let mut next = Some(format!("{}/commits", base));
+        while let Some(url) = next {
+            let page = self.http.get_json(&url)?;
+            for c in page["values"].as_array().into_iter().flatten() {
+                let raw = str_field(&c["author"], "raw");
+                commits.push(CommitInfo {
+                    sha: str_field(c, "hash"),
+                    author: if raw.is_empty() {
+                        str_field(&c["author"]["user"], "display_name")
+                    } else {
+                        raw
+                    },
+                    message: str_field(c, "message"),
+                    stats: None,
+                    files: Vec::new(),
+                });
+            }
+            next = page["next"].as_str().map(String::from);
+        }
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// Cloud PR comments as threads: top-level comments open one (inline ones on their `to`
+    /// line, or `from` for removed lines), replies join their `parent`'s thread. Deleted comments
+    /// are skipped.
+    fn cloud_threads(&self, base: &str) -> Result<Vec<ReviewThread>, McpError> {
+        let mut comments = Vec::new();
+        let mut next = Some(format!("{}/comments?pagelen={}", base, CLOUD_COMMENTS_PAGELEN));
+        while let Some(url) = next {
+            let page = self.http.get_json(&url)?;
+            comments.extend(page["values"].as_array().cloned().unwrap_or_default());
+            next = page["next"].as_str().map(String::from);
+        }
+        // Replies always have a higher id than their parent.
+        comments.sort_by_key(|c| c["id"].as_u64());
+        let mut threads: Vec<ReviewThread> = Vec::new();
+        let mut thread_of: HashMap<u64, usize> = HashMap::new();
+        for c in comments.iter().filter(|c| c["deleted"] != true) {
+            let comment = ThreadComment {
+                author: str_field(&c["user"], "display_name"),
+                body: str_field(&c["content"], "raw"),
+            };
+            let parent = c["parent"]["id"].as_u64().and_then(|id| thread_of.get(&id).copied());
+            let index = match parent {
+                Some(index) => {
+                    threads[index].comments.push(comment);
+                    index
+                }
+                None => {
+                    let inline = &c["inline"];
+                    threads.push(ReviewThread {
+                        path: opt_str(&inline["path"]),
+                        line: inline["to"]
+                            .as_u64()
+                            .or_else(|| inline["from"].as_u64())
+                            .map(|n| n as u32),
+                        resolved: c["resolution"].is_object(),
+                        outdated: inline["outdated"].as_bool().unwrap_or_default(),
+                        comments: vec![comment],
+                    });
+                    threads.len() - 1
+                }
+            };
+            if let Some(id) = c["id"].as_u64() {
+                thread_of.insert(id, index);
+            }
+        }
+        Ok(threads)
+    }
+
+    fn fetch_data_center(&self, pr: &PrUrl) -> Result<ReviewInput, McpError> {
+        let base = pr.to_api_url();
+        let meta = self.http.get_json(&base)?;
+        let diff = self.http.get_text(&format!("{}.diff", base), Some("text/plain"))?;
+        let mut files = Vec::new();
+        let mut start = 0u64;
+        loop {
+            let page = self.http.get_json(&format!(
+                "{}/changes?limit={}&start={}",
+                base, DC_PAGE_LIMIT, start
+            ))?;
+            for change in page["values"].as_array().into_iter().flatten() {
+                if let Some(path) = change["path"]["toString"].as_str() {
+                    files.push(file_entry(path));
+                }
+            }
+            match page["nextPageStart"].as_u64() {
+                Some(n) if !page["isLastPage"].as_bool().unwrap_or(true) => start = n,
+                _ => break,
+            }
+        }
+        let revisions = Revisions {
+            base_ref: opt_str(&meta["toRef"]["displayId"]),
+            base_sha: opt_str(&meta["toRef"]["latestCommit"]),
+            head_ref: opt_str(&meta["fromRef"]["displayId"]),
+            head_sha: opt_str(&meta["fromRef"]["latestCommit"]),
+            merge_base: None,
+        };
+        Ok(ReviewInput::new()
+            .with_title(str_field(&meta, "title"))
+            .with_description(str_field(&meta, "description"))
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(self.data_center_commits(&base)?)
+            .with_threads(self.data_center_threads(&base)?))
+    }
+
+    /// Data Center PR commits, oldest first: `id`, `message` and `author` name and email.
+    fn data_center_commits(&self, base: &str) -> Result<Vec<CommitInfo>, McpError> {
+        let mut commits = Vec::new();
+        let mut start = 0u64;
+        loop {
+            let page = self.http.get_json(&format!(
+                "{}/commits?limit={}&start={}",
+                base, DC_PAGE_LIMIT, start
+            ))?;
+            for c in page["values"].as_array().into_iter().flatten() {
+                let who = &c["author"];
+                commits.push(CommitInfo {
+                    sha: str_field(c, "id"),
+                    author: author(&str_field(who, "name"), &str_field(who, "emailAddress")),
+                    message: str_field(c, "message"),
+                    stats: None,
+                    files: Vec::new(),
+                });
+            }
+            match page["nextPageStart"].as_u64() {
+                Some(n) if !page["isLastPage"].as_bool().unwrap_or(true) => start = n,
+                _ => break,
+            }
+        }
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// Data Center threads from the PR activity (newest first, so reversed): each `COMMENTED` /
+    /// `ADDED` entry is a root comment with its `commentAnchor` and nested replies.
+    fn data_center_threads(&self, base: &str) -> Result<Vec<ReviewThread>, McpError> {
+        let mut threads = Vec::new();
+        let mut start = 0u64;
+        loop {
+            let page = self.http.get_json(&format!(
+                "{}/activities?limit={}&start={}",
+                base, DC_PAGE_LIMIT, start
+            ))?;
+            let added = page["values"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|a| a["action"] == "COMMENTED" && a["commentAction"] == "ADDED");
+            for activity in added {
+                let comment = &activity["comment"];
+                let anchor = &activity["commentAnchor"];
+                let mut comments = Vec::new();
+                flatten_data_center_comment(comment, &mut comments);
+                threads.push(ReviewThread {
+                    path: opt_str(&anchor["path"]),
+                    line: anchor["line"].as_u64().map(|n| n as u32),
+                    resolved: comment["threadResolved"].as_bool().unwrap_or_default()
+                        || comment["state"] == "RESOLVED",
+                    outdated: anchor["orphaned"].as_bool().unwrap_or_default(),
+                    comments,
+                });
+            }
+            match page["nextPageStart"].as_u64() {
+                Some(n) if !page["isLastPage"].as_bool().unwrap_or(true) => start = n,
+                _ => break,
+            }
+        }
+        threads.reverse();
+        Ok(threads)
+    }
+}
+
+/// A Data Center comment followed by its nested replies, depth first.
+fn flatten_data_center_comment(comment: &Value, out: &mut Vec<ThreadComment>) {
+    let author = &comment["author"];
+    out.push(ThreadComment {
+        author: opt_str(&author["displayName"]).unwrap_or_else(|| str_field(author, "name")),
+        body: str_field(comment, "text"),
+    });
+    for reply in comment["comments"].as_array().into_iter().flatten() {
+        flatten_data_center_comment(reply, out);
+    }
+}
+
+impl McpProvider for BitbucketProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let pr = require_pull_request(target, Platform::Bitbucket, "BitbucketProvider")?;
+        if pr.is_bitbucket_data_center() {
+            self.fetch_data_center(pr)
+        } else {
+            self.fetch_cloud(pr)
+        }
+    }
+
+    /// Posts each line comment as an inline comment, then the summary as a general PR comment.
+    /// Data Center anchors are `ADDED` or `CONTEXT` lines, taken from the PR's parsed diff.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::Bitbucket, "BitbucketProvider")?;
+        let url = format!("{}/comments", pr.to_api_url());
+        let dc = pr.is_bitbucket_data_center();
+        let mut input = ReviewInput::new();
+        let mut poster = ReviewPoster::new(&self.http);
+        if !result.line_comments.is_empty() {
+            let current = self.head_sha(target)?;
+            if let (Some(reviewed), Some(current)) = (result.head_sha.as_deref(), current) {
+                // Cloud reports abbreviated hashes.
+                if !reviewed.starts_with(&current) && !current.starts_with(reviewed) {
+                    return Err(McpError::new(format!(
+                        "PR {} moved from {} to {} since it was reviewed; \
+                         not posting line comments",
+                        pr, reviewed, current
+                    )));
+                }
+            }
+            if dc {
+                let diff_url = format!("{}.diff", pr.to_api_url());
+                input = input.with_diff(self.http.get_text(&diff_url, Some("text/plain"))?);
+            }
+        }
+        for c in &result.line_comments {
+            let body = if dc {
+                let added = input
+                    .diff_file(&c.path)
+                    .and_then(|f| f.new_side_line(c.line))
+                    .is_some_and(|l| l.kind == LineKind::Added);
+                json!({
+                    "text": c.body,
+                    "anchor": {
+                        "path": c.path,
+                        "line": c.line,
+                        "lineType": if added { "ADDED" } else { "CONTEXT" },
+                        "fileType": "TO",
+                        "diffType": "EFFECTIVE"
+                    }
+                })
+            } else {
+                json!({
+                    "content": { "raw": c.body },
+                    "inline": { "path": c.path, "to": c.line }
+                })
+            };
+            poster.post_json(&url, &body)?;
+        }
+        if !result.summary.is_empty() {
+            let body = if dc {
+                json!({ "text": result.summary })
+            } else {
+                json!({ "content": { "raw": result.summary } })
+            };
+            poster.post_json(&url, &body)?;
+        }
+        Ok(())
+    }
+
+    /// Source commit of the PR (`source.commit.hash` on Cloud, abbreviated;
+    /// `fromRef.latestCommit` on Data Center).
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        match target {
+            ReviewTarget::PullRequest(pr) if pr.platform == Platform::Bitbucket => {
+                let meta = self.http.get_json(&pr.to_api_url())?;
+                Ok(if pr.is_bitbucket_data_center() {
+                    opt_str(&meta["fromRef"]["latestCommit"])
+                } else {
+                    opt_str(&meta["source"]["commit"]["hash"])
+                })
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
@@ -0,0 +1,245 @@
+//! Persistent fetch cache: a provider decorator that stores `ReviewInput` on disk between runs.
+//!
+//! Entries live under `{dir}/{platform}/{host}/{owner}/{repo}/{pr-N | commit-SHA | compare-A...B}/`
+//! and are named after the head SHA reported by `McpProvider::head_sha`. A new head SHA therefore
+//! misses and replaces older entries. Targets whose head the provider cannot tell are not cached
+//! (a stale entry would be reviewed instead of new pushes), nor are local ranges, patches, fixtures
+//! and supplied diffs. `McpReviewToolSource` keeps
+//! its per-run copy on top of this.
+//!
+//! Review threads are not stored: comments are added and resolved without a new head SHA, so a
+//! cached copy would go stale. A cache hit has no threads and says so in `threads_unavailable`;
+//! `--refresh-cache` fetches them again.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::review_input::ReviewInput;
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// Default entry lifetime.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Overrides the cache directory.
+pub const CACHE_DIR_ENV: &str = "QUICK_REVIEW_CACHE_DIR";
+/// Overrides the TTL in seconds (`0` disables expiry).
+pub const CACHE_TTL_ENV: &str = "QUICK_REVIEW_CACHE_TTL_SECS";
+/// `threads_unavailable` of a cache hit.
+const CACHED_THREADS_NOTE: &str =
+    "the change was loaded from the fetch cache, which does not keep review threads";
+
+/// One stored fetch.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch.
+    stored_at: u64,
+    head_sha: Option<String>,
+    target: ReviewTarget,
+    input: ReviewInput,
+}
+
+/// Provider decorator caching `fetch` results on disk.
+pub struct CachingProvider {
+    inner: Arc<dyn McpProvider>,
+    dir: PathBuf,
+    ttl: Option<Duration>,
+    refresh: bool,
+}
+
+impl CachingProvider {
+    /// Caches `inner`'s fetches under `dir` with the default TTL.
+    pub fn new(inner: Arc<dyn McpProvider>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+            ttl: Some(DEFAULT_TTL),
+            refresh: false,
+        }
+    }
+
+    /// Caches under `default_dir()` with the TTL from `QUICK_REVIEW_CACHE_TTL_SECS` (if set).
+    pub fn from_env(inner: Arc<dyn McpProvider>) -> Result<Self, McpError> {
+        let provider = Self::new(inner, default_dir());
+        match std::env::var(CACHE_TTL_ENV) {
+            Ok(secs) => {
+                let secs: u64 = secs.parse().map_err(|_| {
+                    McpError::new(format!("{}: not a number: {}", CACHE_TTL_ENV, secs))
+                })?;
+                Ok(provider.with_ttl((secs > 0).then(|| Duration::from_secs(secs))))
+            }
+            Err(_) => Ok(provider),
+        }
+    }
+
+    /// Builder-style: entry lifetime; `None` keeps entries until their head SHA changes.
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Builder-style: ignore stored entries and overwrite them with fresh fetches.
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Removes every stored entry for `target` (all head SHAs).
+    pub fn invalidate(&self, target: &ReviewTarget) -> Result<(), McpError> {
+        match self.target_dir(target) {
+            Some(dir) => remove_dir(&dir),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the whole cache directory.
+    pub fn clear(&self) -> Result<(), McpError> {
+        remove_dir(&self.dir)
+    }
+
+    /// Directory holding `target`'s entries; `None` for targets that are not cached.
+    fn target_dir(&self, target: &ReviewTarget) -> Option<PathBuf> {
+        let repo = target.repo()?;
+        let leaf = match target {
+            ReviewTarget::PullRequest(pr) => match pr.patchset {
+                Some(ps) => format!("pr-{}-ps{}", pr.id, ps),
+                None => format!("pr-{}", pr.id),
+            },
+            ReviewTarget::Commit { sha, .. } => format!("commit-{}", sha),
+            ReviewTarget::Compare { base, head, .. } => format!("compare-{}...{}", base, head),
+            ReviewTarget::LocalRange { .. }
+            | ReviewTarget::Patch { .. }
+            | ReviewTarget::Fixture { .. }
+            | ReviewTarget::Diff { .. } => return None,
+        };
+        let mut dir = self.dir.join(segment(&repo.platform.name().to_lowercase()));
+        dir.push(segment(&repo.host));
+        for part in repo.owner.split('/').filter(|p| !p.is_empty()) {
+            dir.push(segment(part));
+        }
+        dir.push(segment(&repo.repo));
+        dir.push(segment(&leaf));
+        Some(dir)
+    }
+
+    fn read_entry(&self, path: &Path, target: &ReviewTarget) -> Option<ReviewInput> {
+        let entry: CacheEntry = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        if entry.target != *target {
+            return None;
+        }
+        if let Some(ttl) = self.ttl {
+            if now_secs().saturating_sub(entry.stored_at) >= ttl.as_secs() {
+                return None;
+            }
+        }
+        Some(ReviewInput {
+            threads_unavailable: Some(CACHED_THREADS_NOTE.to_string()),
+            ..entry.input
+        })
+    }
+
+    /// Stores `input` as the only entry of `dir`. Failures are ignored: the cache is an
+    /// optimization and must not fail a review.
+    fn write_entry(&self, dir: &Path, file: &str, entry: &CacheEntry) {
+        let _ = remove_dir(dir);
+        if std::fs::create_dir_all(dir).is_err() {
+            return;
+        }
+        if let Ok(text) = serde_json::to_string(entry) {
+            let _ = std::fs::write(dir.join(file), text);
+        }
+    }
+}
+
+impl McpProvider for CachingProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let Some(dir) = self.target_dir(target) else {
+            return self.inner.fetch(target);
+        };
+        let Some(head_sha) = self.inner.head_sha(target)? else {
+            return self.inner.fetch(target);
+        };
+        let file = format!("{}.json", segment(&head_sha));
+        if !self.refresh {
+            if let Some(input) = self.read_entry(&dir.join(&file), target) {
+                return Ok(input);
+            }
+        }
+        let input = self.inner.fetch(target)?;
+        let entry = CacheEntry {
+            stored_at: now_secs(),
+            head_sha: Some(head_sha),
+            target: target.clone(),
+            input: ReviewInput {
+                threads: Vec::new(),
+                threads_unavailable: None,
+                ..input.clone()
+            },
+        };
+        self.write_entry(&dir, &file, &entry);
+        Ok(input)
+    }
+
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        self.inner.post_review(target, result)
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.inner.supports_posting(target)
+    }
+
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        self.inner.head_sha(target)
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// `QUICK_REVIEW_CACHE_DIR`, else `$XDG_CACHE_HOME/quick-review`, else `~/.cache/quick-review`
+/// (a temp-dir fallback if `HOME` is unset).
+pub fn default_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("quick-review");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".cache").join("quick-review"),
+        None => std::env::temp_dir().join("quick-review-cache"),
+    }
+}
+
+/// Path segment with anything but `[A-Za-z0-9._-]` replaced by `_` (no `..` traversal).
+fn segment(s: &str) -> String {
+    let cleaned: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+        .collect();
+    match cleaned.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => cleaned,
+    }
+}
+
+fn remove_dir(dir: &Path) -> Result<(), McpError> {
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(McpError::new(format!(
+            "removing cache {}: {}",
+            dir.display(),
+            e
+        ))),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,278 @@
+//! Record/replay providers: cassettes of `fetch` results and `post_review` payloads.
+//!
+//! `RecordingProvider` wraps any provider and appends every interaction to a JSON cassette file,
+//! rewriting it after each call so an aborted run still leaves a usable cassette. A failed write
+//! does not change the call's outcome; it is kept for `save_error()`.
+//! `ReplayProvider` serves a cassette without network access: fetches return the recorded
+//! input (or error, with its kind and requested wait) for the same target, posts are kept in
+//! memory for comparison with the recorded ones.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::mcp_provider::{McpError, McpErrorKind, McpProvider, RateLimit};
+use crate::review_input::ReviewInput;
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// Cassette format version written to `version` (2: errors are objects with kind and wait).
+pub const CASSETTE_VERSION: u32 = 2;
+
+/// One recorded provider call.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "call", rename_all = "snake_case")]
+pub enum Interaction {
+    /// `fetch(target)`; exactly one of `input` / `error` is set.
+    Fetch {
+        target: ReviewTarget,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        input: Option<Box<ReviewInput>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<RecordedError>,
+    },
+    /// `post_review(target, result)` and its outcome.
+    PostReview {
+        target: ReviewTarget,
+        result: ReviewResult,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<RecordedError>,
+    },
+}
+
+/// A recorded `McpError`, so replay fails the same way (retry, auth and partial-post handling
+/// depend on more than the message).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedError {
+    pub message: String,
+    #[serde(default)]
+    pub kind: McpErrorKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<Duration>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+}
+
+impl RecordedError {
+    /// The error as it was returned.
+    pub fn to_error(&self) -> McpError {
+        McpError::new(self.message.clone())
+            .with_kind(self.kind)
+            .with_retry_after(self.retry_after)
+            .with_partial(self.partial)
+    }
+}
+
+impl From<&McpError> for RecordedError {
+    fn from(e: &McpError) -> Self {
+        Self {
+            message: e.message.clone(),
+            kind: e.kind,
+            retry_after: e.retry_after,
+            partial: e.partial,
+        }
+    }
+}
+
+/// Recorded interactions in call order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Cassette {
+    pub version: u32,
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self {
+            version: CASSETTE_VERSION,
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl Cassette {
+    /// Reads a cassette file.
+    pub fn load(path: &Path) -> Result<Self, McpError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| McpError::new(format!("reading cassette {}: {}", path.display(), e)))?;
+        let cassette: Cassette = serde_json::from_str(&text)
+            .map_err(|e| McpError::new(format!("parsing cassette {}: {}", path.display(), e)))?;
+        if cassette.version != CASSETTE_VERSION {
+            return Err(McpError::new(format!(
+                "cassette {} has version {}, expected {}",
+                path.display(),
+                cassette.version,
+                CASSETTE_VERSION
+            )));
+        }
+        Ok(cassette)
+    }
+
+    /// Writes the cassette as pretty JSON.
+    pub fn save(&self, path: &Path) -> Result<(), McpError> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| McpError::new(format!("serializing cassette: {}", e)))?;
+        std::fs::write(path, text + "\n")
+            .map_err(|e| McpError::new(format!("writing cassette {}: {}", path.display(), e)))
+    }
+
+    /// Recorded `post_review` results, in order.
+    pub fn posts(&self) -> Vec<(&ReviewTarget, &ReviewResult)> {
+        self.interactions
+            .iter()
+            .filter_map(|i| match i {
+                Interaction::PostReview { target, result, .. } => Some((target, result)),
+                Interaction::Fetch { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// Provider decorator that records every call of `inner` to a cassette file.
+pub struct RecordingProvider {
+    inner: Arc<dyn McpProvider>,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+    save_error: Mutex<Option<String>>,
+}
+
+impl RecordingProvider {
+    /// Records calls of `inner` to `path` (overwritten on the first call).
+    pub fn new(inner: Arc<dyn McpProvider>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::default()),
+            save_error: Mutex::new(None),
+        }
+    }
+
+    /// Everything recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Why the cassette file could not be written the last time it failed; `None` if every write
+    /// succeeded.
+    pub fn save_error(&self) -> Option<String> {
+        self.save_error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Appends `interaction` and rewrites the file. A write failure is kept for `save_error()`
+    /// instead of failing the call: the provider call itself already succeeded or failed.
+    fn record(&self, interaction: Interaction) {
+        let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+        cassette.interactions.push(interaction);
+        if let Err(e) = cassette.save(&self.path) {
+            *self.save_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.message);
+        }
+    }
+}
+
+impl McpProvider for RecordingProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let outcome = self.inner.fetch(target);
+        self.record(Interaction::Fetch {
+            target: target.clone(),
+            input: outcome.as_ref().ok().cloned().map(Box::new),
+            error: outcome.as_ref().err().map(RecordedError::from),
+        });
+        outcome
+    }
+
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let outcome = self.inner.post_review(target, result);
+        self.record(Interaction::PostReview {
+            target: target.clone(),
+            result: result.clone(),
+            error: outcome.as_ref().err().map(RecordedError::from),
+        });
+        outcome
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.inner.supports_posting(target)
+    }
+
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        self.inner.head_sha(target)
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// Provider that answers from a cassette; never touches the network.
+pub struct ReplayProvider {
+    cassette: Cassette,
+    posted: Mutex<Vec<(ReviewTarget, ReviewResult)>>,
+}
+
+impl ReplayProvider {
+    /// Replays `cassette`.
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            cassette,
+            posted: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Replays the cassette file at `path`.
+    pub fn from_file(path: &Path) -> Result<Self, McpError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// The cassette being replayed (compare `cassette().posts()` with `posted()`).
+    pub fn cassette(&self) -> &Cassette {
+        &self.cassette
+    }
+
+    /// Reviews posted during replay, in order.
+    pub fn posted(&self) -> Vec<(ReviewTarget, ReviewResult)> {
+        self.posted.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn recorded_post(&self, target: &ReviewTarget) -> Option<&Option<RecordedError>> {
+        self.cassette.interactions.iter().find_map(|i| match i {
+            Interaction::PostReview { target: t, error, .. } if t == target => Some(error),
+            _ => None,
+        })
+    }
+}
+
+impl McpProvider for ReplayProvider {
+    /// The first recorded fetch of `target`; repeated fetches get the same answer.
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let recorded = self.cassette.interactions.iter().find_map(|i| match i {
+            Interaction::Fetch { target: t, input, error } if t == target => Some((input, error)),
+            _ => None,
+        });
+        match recorded {
+            Some((Some(input), _)) => Ok(input.as_ref().clone()),
+            Some((None, Some(error))) => Err(error.to_error()),
+            Some((None, None)) => Err(McpError::new("recorded fetch failed")),
+            None => Err(McpError::new(format!(
+                "cassette has no fetch of {}",
+                target
+            ))),
+        }
+    }
+
+    /// Keeps the result for `posted()`; returns the recorded error, if the recorded post failed.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        self.posted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((target.clone(), result.clone()));
+        match self.recorded_post(target) {
+            Some(Some(error)) => Err(error.to_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// True if the target could be posted when recorded (or is a PR/MR).
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.recorded_post(target).is_some() || target.supports_posting()
+    }
+}
@@ -0,0 +1,191 @@
+//! Fixture provider: loads evaluation fixtures (`ReviewTarget::Fixture`) from disk, so curated
+//! datasets run through the normal `ReviewPipeline` without hand-written mocks.
+//!
+//! A fixture directory holds `title.txt`, `description.md` (optional), `diff.patch` and the
+//! head-side contents of files under `files/<path>`. Files in the diff get their section of
+//! `diff.patch` and, if present, their contents; other files under `files/` are added as context
+//! without a diff. An optional `revisions.json` holds `Revisions` fields (`base_sha`, `head_sha`,
+//! ...), an optional `commits.json` an array of `CommitInfo` objects and an optional
+//! `threads.json` an array of `ReviewThread` objects (existing review comments). `post_review` writes the result as JSON to `result.json` in the fixture directory.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::mcp_provider::{McpError, McpProvider};
+use crate::review_input::{CommitInfo, FileContent, ReviewInput, ReviewThread, Revisions};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// PR title; defaults to the directory name.
+pub const TITLE_FILE: &str = "title.txt";
+/// PR description (optional).
+pub const DESCRIPTION_FILE: &str = "description.md";
+/// Unified diff (required).
+pub const DIFF_FILE: &str = "diff.patch";
+/// Base / head refs and SHAs as a `Revisions` JSON object (optional).
+pub const REVISIONS_FILE: &str = "revisions.json";
+/// Commits as a JSON array of `CommitInfo` objects, oldest first (optional).
+pub const COMMITS_FILE: &str = "commits.json";
+/// Existing review threads as a JSON array of `ReviewThread` objects (optional).
+pub const THREADS_FILE: &str = "threads.json";
+/// Directory with head-side file contents, laid out by repository path.
+pub const FILES_DIR: &str = "files";
+/// Written by `post_review`.
+pub const RESULT_FILE: &str = "result.json";
+
+/// `McpProvider` over fixture directories (`ReviewTarget::Fixture` only).
+#[derive(Debug, Default)]
+pub struct FixtureProvider;
+
+impl FixtureProvider {
+    /// Creates the provider.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Fixture targets under `root`, sorted by path: `root` itself if it contains `diff.patch`,
+    /// otherwise each subdirectory that does.
+    pub fn dataset(root: &Path) -> Result<Vec<ReviewTarget>, McpError> {
+        if root.join(DIFF_FILE).is_file() {
+            return Ok(vec![ReviewTarget::Fixture {
+                dir: root.to_path_buf(),
+            }]);
+        }
+        let mut dirs: Vec<PathBuf> = read_dir(root)?
+            .into_iter()
+            .filter(|dir| dir.join(DIFF_FILE).is_file())
+            .collect();
+        dirs.sort();
+        Ok(dirs.into_iter().map(|dir| ReviewTarget::Fixture { dir }).collect())
+    }
+
+    fn fixture_dir(target: &ReviewTarget) -> Result<&Path, McpError> {
+        match target {
+            ReviewTarget::Fixture { dir } => Ok(dir),
+            other => Err(McpError::new(format!(
+                "FixtureProvider can only review fixtures, not a {}: {}",
+                other.kind_name(),
+                other
+            ))),
+        }
+    }
+}
+
+impl McpProvider for FixtureProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let dir = Self::fixture_dir(target)?;
+        let diff = read_text(&dir.join(DIFF_FILE))?
+            .ok_or_else(|| McpError::new(format!("{}: missing {}", target, DIFF_FILE)))?;
+        let title = match read_text(&dir.join(TITLE_FILE))? {
+            Some(title) => title.trim().to_string(),
+            None => dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let description = read_text(&dir.join(DESCRIPTION_FILE))?.unwrap_or_default();
+        let revisions: Revisions = read_json(target, dir, REVISIONS_FILE)?.unwrap_or_default();
+        let commits: Vec<CommitInfo> = read_json(target, dir, COMMITS_FILE)?.unwrap_or_default();
+        let threads: Vec<ReviewThread> = read_json(target, dir, THREADS_FILE)?.unwrap_or_default();
+
+        let mut input = ReviewInput::new()
+            .with_title(title)
+            .with_description(description.trim_end())
+            .with_diff(diff)
+            .with_revisions(revisions)
+            .with_commits(commits)
+            .with_threads(threads);
+        let files_dir = dir.join(FILES_DIR);
+        let files = &mut input.files;
+        for file in files.iter_mut() {
+            if is_relative_inside(&file.path) {
+                file.content = read_text(&files_dir.join(&file.path))?.filter(|t| !t.contains('\0'));
+            }
+        }
+        if files_dir.is_dir() {
+            for path in relative_files(&files_dir)? {
+                if files.iter().any(|f| f.path == path) {
+                    continue;
+                }
+                let content = read_text(&files_dir.join(&path))?.filter(|t| !t.contains('\0'));
+                files.push(FileContent {
+                    path,
+                    diff: None,
+                    content,
+                    status: None,
+                });
+            }
+        }
+        Ok(input)
+    }
+
+    /// Writes the result as pretty JSON to `result.json` in the fixture directory.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let path = Self::fixture_dir(target)?.join(RESULT_FILE);
+        let json = serde_json::to_string_pretty(result)
+            .map_err(|e| McpError::new(format!("serializing review: {}", e)))?;
+        std::fs::write(&path, json + "\n")
+            .map_err(|e| McpError::new(format!("writing review to {}: {}", path.display(), e)))
+    }
+
+    /// Fixtures are "posted" to their `result.json`.
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        matches!(target, ReviewTarget::Fixture { .. })
+    }
+}
+
+/// File text, `None` if it does not exist; invalid UTF-8 is replaced lossily.
+fn read_text(path: &Path) -> Result<Option<String>, McpError> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(McpError::new(format!("reading {}: {}", path.display(), e))),
+    }
+}
+
+/// `name` in `dir` parsed as JSON, `None` if the file does not exist.
+fn read_json<T: serde::de::DeserializeOwned>(
+    target: &ReviewTarget,
+    dir: &Path,
+    name: &str,
+) -> Result<Option<T>, McpError> {
+    read_text(&dir.join(name))?
+        .map(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| McpError::new(format!("{}: invalid {}: {}", target, name, e)))
+        })
+        .transpose()
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, McpError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| McpError::new(format!("reading {}: {}", dir.display(), e)))?;
+    Ok(entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+}
+
+/// Regular files under `root` as sorted `/`-separated relative paths.
+fn relative_files(root: &Path) -> Result<Vec<String>, McpError> {
+    let mut pending = vec![root.to_path_buf()];
+    let mut files = Vec::new();
+    while let Some(dir) = pending.pop() {
+        for path in read_dir(&dir)? {
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                let parts: Vec<String> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                files.push(parts.join("/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// True if a diff path stays inside `files/` (no absolute paths or `..`).
+fn is_relative_inside(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
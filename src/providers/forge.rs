@@ -0,0 +1,110 @@
+//! Forge provider: routes each `ReviewTarget` to the provider for its source.
+//!
+//! PRs/MRs, commits and compares go to the REST provider of their platform (GitHub, GitLab,
+//! Bitbucket, Gitea, Gerrit, Azure DevOps), or to the MCP server from `QUICK_REVIEW_MCP_COMMAND`
+//! for the platform its tools handle; local ranges, patches and fixtures go to `LocalGitProvider`,
+//! `PatchProvider` and `FixtureProvider`. Used by the CLI and `mcp-serve`, where one provider
+//! serves every target.
+
+use super::{
+    AzureDevOpsProvider, BitbucketProvider, FixtureProvider, GerritProvider, GitHubProvider,
+    GitLabProvider, GiteaProvider, LocalGitProvider, McpStdioProvider, PatchProvider,
+};
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::pr_url::Platform;
+use crate::review_input::ReviewInput;
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// `McpProvider` that dispatches on the target's platform (or kind, for local sources).
+pub struct ForgeProvider {
+    github: GitHubProvider,
+    gitlab: GitLabProvider,
+    bitbucket: BitbucketProvider,
+    gitea: GiteaProvider,
+    gerrit: GerritProvider,
+    azure_devops: AzureDevOpsProvider,
+    mcp_stdio: Option<McpStdioProvider>,
+    local_git: LocalGitProvider,
+    patch: PatchProvider,
+    fixture: FixtureProvider,
+}
+
+impl ForgeProvider {
+    /// Providers configured from the environment (each forge's `from_env`, plus
+    /// `McpStdioProvider::from_env`).
+    pub fn from_env() -> Result<Self, McpError> {
+        Ok(Self {
+            github: GitHubProvider::from_env(),
+            gitlab: GitLabProvider::from_env(),
+            bitbucket: BitbucketProvider::from_env(),
+            gitea: GiteaProvider::from_env(),
+            gerrit: GerritProvider::from_env(),
+            azure_devops: AzureDevOpsProvider::from_env(),
+            mcp_stdio: McpStdioProvider::from_env()?,
+            local_git: LocalGitProvider::new(),
+            patch: PatchProvider::new(),
+            fixture: FixtureProvider::new(),
+        })
+    }
+
+    /// Provider for `target`; the MCP server takes PRs/MRs of its platform.
+    fn provider_for(&self, target: &ReviewTarget) -> Result<&dyn McpProvider, McpError> {
+        let platform = match target {
+            ReviewTarget::PullRequest(pr) => &pr.platform,
+            ReviewTarget::Commit { repo, .. } | ReviewTarget::Compare { repo, .. } => &repo.platform,
+            ReviewTarget::LocalRange { .. } => return Ok(&self.local_git),
+            ReviewTarget::Patch { .. } => return Ok(&self.patch),
+            ReviewTarget::Fixture { .. } => return Ok(&self.fixture),
+            ReviewTarget::Diff { .. } => {
+                return Err(McpError::new(format!("no provider can fetch {}", target)))
+            }
+        };
+        if let Some(mcp) = &self.mcp_stdio {
+            if target.as_pull_request().is_some() && mcp.platform().same_kind(platform) {
+                return Ok(mcp);
+            }
+        }
+        Ok(match platform {
+            Platform::GitHub => &self.github,
+            Platform::GitLab => &self.gitlab,
+            Platform::Bitbucket => &self.bitbucket,
+            Platform::Gitea => &self.gitea,
+            Platform::Gerrit => &self.gerrit,
+            Platform::AzureDevOps { .. } => &self.azure_devops,
+        })
+    }
+}
+
+impl McpProvider for ForgeProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        self.provider_for(target)?.fetch(target)
+    }
+
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        self.provider_for(target)?.post_review(target, result)
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.provider_for(target)
+            .is_ok_and(|provider| provider.supports_posting(target))
+    }
+
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        self.provider_for(target)?.head_sha(target)
+    }
+
+    /// Quota of the first forge that has reported one.
+    fn rate_limit(&self) -> Option<RateLimit> {
+        [
+            &self.github as &dyn McpProvider,
+            &self.gitlab,
+            &self.bitbucket,
+            &self.gitea,
+            &self.gerrit,
+            &self.azure_devops,
+        ]
+        .iter()
+        .find_map(|provider| provider.rate_limit())
+    }
+}
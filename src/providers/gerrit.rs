@@ -0,0 +1,277 @@
+//! Gerrit provider: fetches a change's patchset diff; posts a review with a `Code-Review` vote.
+//!
+//! Reads change detail and the base64 `/patch` of the selected patchset (`PrUrl::patchset` or
+//! current) into `ReviewInput`, with the branch, the revision and its parent as revisions and the
+//! revision's commit (stats from the file list) as the only commit, and the change's published
+//! comments (`/comments`) as review threads; posts
+//! through `/revisions/{rev}/review` (the reviewed revision when known) with inline comments and
+//! a vote derived from `ReviewResult::verdict`. Responses carry Gerrit's `)]}'` XSSI prefix.
+//! Auth: `GERRIT_USERNAME` + `GERRIT_HTTP_PASSWORD` (basic, via the `/a/` endpoints).
+
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use serde_json::{json, Value};
+
+use super::{author, file_entry, opt_str, require_pull_request, str_field};
+use crate::diff_model::DiffStat;
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{CommitInfo, ReviewInput, ReviewThread, Revisions, ThreadComment};
+use crate::review_result::{ReviewResult, ReviewVerdict};
+use crate::review_target::ReviewTarget;
+
+/// Label voted on by `post_review`.
+pub const CODE_REVIEW_LABEL: &str = "Code-Review";
+/// Tag on posted reviews so Gerrit UIs can filter bot comments.
+const REVIEW_TAG: &str = "autogenerated:quick-review";
+/// Magic files Gerrit lists alongside real changes.
+const MAGIC_FILES: &[&str] = &["/COMMIT_MSG", "/MERGE_LIST", "/PATCHSET_LEVEL"];
+
+/// `McpProvider` backed by the Gerrit REST API.
+pub struct GerritProvider {
+    http: HttpClient,
+    /// Use the authenticated `/a/` endpoints.
+    authenticated: bool,
+}
+
+impl GerritProvider {
+    /// Creates a provider. Set `authenticated` when `http` carries credentials (uses `/a/` paths).
+    pub fn new(http: HttpClient, authenticated: bool) -> Self {
+        Self { http, authenticated }
+    }
+
+    /// Creates a provider with basic auth from `GERRIT_USERNAME` + `GERRIT_HTTP_PASSWORD`
+    /// (anonymous if unset; anonymous users cannot post).
+    pub fn from_env() -> Self {
+        match (
+            token_from_env(&["GERRIT_USERNAME"]),
+            token_from_env(&["GERRIT_HTTP_PASSWORD"]),
+        ) {
+            (Some(user), Some(password)) => {
+                Self::new(HttpClient::new().with_basic_auth(&user, &password), true)
+            }
+            _ => Self::new(HttpClient::new(), false),
+        }
+    }
+
+    /// `{api_base}[/a]/changes/{project}~{change}`.
+    fn change_url(&self, pr: &PrUrl) -> String {
+        let prefix = if self.authenticated { "/a" } else { "" };
+        format!(
+            "{}{}/changes/{}~{}",
+            pr.api_base,
+            prefix,
+            pr.encoded_project_path(),
+            pr.id
+        )
+    }
+
+    fn get_json(&self, url: &str) -> Result<Value, McpError> {
+        let text = self.http.get_text(url, Some("application/json"))?;
+        parse_gerrit_json(url, &text)
+    }
+
+    /// Revision sha for `pr.patchset` (or the current revision) from change detail.
+    fn select_revision<'a>(pr: &PrUrl, detail: &'a Value) -> Result<(String, &'a Value), McpError> {
+        let revisions = detail["revisions"]
+            .as_object()
+            .ok_or_else(|| McpError::new(format!("change {} has no revisions", pr)))?;
+        let found = match pr.patchset {
+            Some(ps) => revisions
+                .iter()
+                .find(|(_, rev)| rev["_number"].as_u64() == Some(u64::from(ps))),
+            None => detail["current_revision"]
+                .as_str()
+                .and_then(|sha| revisions.get_key_value(sha)),
+        };
+        found
+            .map(|(sha, rev)| (sha.clone(), rev))
+            .ok_or_else(|| McpError::new(format!("patchset not found for change {}", pr)))
+    }
+}
+
+impl McpProvider for GerritProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let pr = require_pull_request(target, Platform::Gerrit, "GerritProvider")?;
+        let change = self.change_url(pr);
+        let detail = self.get_json(&format!("{}?o=ALL_REVISIONS&o=ALL_COMMITS", change))?;
+        let (sha, revision) = Self::select_revision(pr, &detail)?;
+        let message = revision["commit"]["message"].as_str().unwrap_or_default();
+        let description = message
+            .split_once('\n')
+            .map(|(_, body)| body.trim())
+            .unwrap_or_default();
+
+        let patch_url = format!("{}/revisions/{}/patch", change, sha);
+        let encoded = self.http.get_text(&patch_url, Some("text/plain"))?;
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded.split_whitespace().collect::<String>())
+            .map_err(|e| {
+                McpError::new(format!("GET {}: invalid base64 patch: {}", patch_url, e))
+            })?;
+        let diff = String::from_utf8_lossy(&decoded).into_owned();
+
+        let files_json = self.get_json(&format!("{}/revisions/{}/files", change, sha))?;
+        let changed: Vec<(&String, &Value)> = files_json
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(p, _)| !MAGIC_FILES.contains(&p.as_str()))
+            .collect();
+        let files = changed.iter().map(|(p, _)| file_entry(p)).collect();
+        let mut stats = DiffStat::default();
+        for (_, info) in &changed {
+            stats.additions += info["lines_inserted"].as_u64().unwrap_or_default() as u32;
+            stats.deletions += info["lines_deleted"].as_u64().unwrap_or_default() as u32;
+        }
+        let who = &revision["commit"]["author"];
+        let commit = CommitInfo {
+            sha: sha.clone(),
+            author: author(&str_field(who, "name"), &str_field(who, "email")),
+            message: message.trim().to_string(),
+            stats: Some(stats),
+            files: changed.iter().map(|(p, _)| p.to_string()).collect(),
+        };
+
+        let comments = self.get_json(&format!("{}/comments", change))?;
+        let threads = threads_from_comments(&comments, revision["_number"].as_u64());
+
+        let parent = opt_str(&revision["commit"]["parents"][0]["commit"]);
+        let revisions = Revisions {
+            base_ref: opt_str(&detail["branch"]),
+            base_sha: parent.clone(),
+            head_ref: opt_str(&revision["ref"]),
+            head_sha: Some(sha),
+            merge_base: parent,
+        };
+        Ok(ReviewInput::new()
+            .with_title(str_field(&detail, "subject"))
+            .with_description(description)
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(vec![commit])
+            .with_threads(threads))
+    }
+
+    /// Posts one review: summary as message, inline comments grouped by file, and a
+    /// `Code-Review` vote from `code_review_vote` (omitted when 0).
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::Gerrit, "GerritProvider")?;
+        let revision = match (&result.head_sha, pr.patchset) {
+            (Some(sha), _) => sha.clone(),
+            (None, Some(ps)) => ps.to_string(),
+            (None, None) => "current".to_string(),
+        };
+        let mut comments: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+        for c in &result.line_comments {
+            comments
+                .entry(c.path.as_str())
+                .or_default()
+                .push(json!({ "line": c.line, "message": c.body }));
+        }
+        let mut body = json!({
+            "message": result.summary,
+            "comments": comments,
+            "tag": REVIEW_TAG,
+        });
+        let vote = code_review_vote(result);
+        if vote != 0 {
+            body["labels"] = json!({ CODE_REVIEW_LABEL: vote });
+        }
+        let url = format!("{}/revisions/{}/review", self.change_url(pr), revision);
+        let text = self.http.post_json_for_text(&url, &body)?;
+        parse_gerrit_json(&url, &text)?;
+        Ok(())
+    }
+
+    /// Commit of the selected patchset (`/revisions/{n}/commit`), or the change's
+    /// `current_revision`.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let pr = match target {
+            ReviewTarget::PullRequest(pr) if pr.platform == Platform::Gerrit => pr,
+            _ => return Ok(None),
+        };
+        let change = self.change_url(pr);
+        Ok(match pr.patchset {
+            Some(ps) => {
+                let commit = self.get_json(&format!("{}/revisions/{}/commit", change, ps))?;
+                opt_str(&commit["commit"])
+            }
+            None => {
+                let detail = self.get_json(&format!("{}?o=CURRENT_REVISION", change))?;
+                opt_str(&detail["current_revision"])
+            }
+        })
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
+
+/// `Code-Review` vote for a result: +1 for approve, -1 for request changes, 0 otherwise.
+pub fn code_review_vote(result: &ReviewResult) -> i32 {
+    match result.verdict {
+        Some(ReviewVerdict::Approve) => 1,
+        Some(ReviewVerdict::RequestChanges) => -1,
+        Some(ReviewVerdict::Comment) | None => 0,
+    }
+}
+
+/// Threads from `/changes/{id}/comments` (path → comments): replies (`in_reply_to`) join their
+/// root comment's thread, which is resolved unless its latest comment is `unresolved`. Threads
+/// opened on an earlier patch set than `patchset` are outdated; patch-set-level comments have no
+/// path.
+fn threads_from_comments(comments: &Value, patchset: Option<u64>) -> Vec<ReviewThread> {
+    let mut threads: Vec<(String, ReviewThread)> = Vec::new();
+    for (path, list) in comments.as_object().into_iter().flatten() {
+        let mut list: Vec<&Value> = list.as_array().into_iter().flatten().collect();
+        list.sort_by_key(|c| c["updated"].as_str().unwrap_or_default().to_string());
+        // Comment id -> id of the comment that opened its thread.
+        let mut roots: BTreeMap<String, String> = BTreeMap::new();
+        for c in list {
+            let id = str_field(c, "id");
+            let root = match c["in_reply_to"].as_str() {
+                Some(parent) => roots.get(parent).cloned().unwrap_or_else(|| parent.to_string()),
+                None => id.clone(),
+            };
+            roots.insert(id, root.clone());
+            let index = match threads.iter().position(|(r, _)| *r == root) {
+                Some(index) => index,
+                None => {
+                    let opened = c["patch_set"].as_u64();
+                    threads.push((
+                        root,
+                        ReviewThread {
+                            path: (path != "/PATCHSET_LEVEL").then(|| path.clone()),
+                            line: c["line"].as_u64().map(|n| n as u32),
+                            outdated: opened.is_some() && patchset.is_some() && opened < patchset,
+                            ..ReviewThread::default()
+                        },
+                    ));
+                    threads.len() - 1
+                }
+            };
+            let thread = &mut threads[index].1;
+            thread.resolved = !c["unresolved"].as_bool().unwrap_or_default();
+            let who = &c["author"];
+            thread.comments.push(ThreadComment {
+                author: opt_str(&who["username"]).unwrap_or_else(|| str_field(who, "name")),
+                body: str_field(c, "message"),
+            });
+        }
+    }
+    threads.into_iter().map(|(_, thread)| thread).collect()
+}
+
+/// Strips Gerrit's `)]}'` XSSI prefix and parses JSON (empty bodies become `Value::Null`).
+fn parse_gerrit_json(url: &str, text: &str) -> Result<Value, McpError> {
+    let body = text.trim_start().strip_prefix(")]}'").unwrap_or(text).trim();
+    if body.is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(body).map_err(|e| McpError::new(format!("{}: invalid JSON: {}", url, e)))
+}
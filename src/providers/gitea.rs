@@ -0,0 +1,215 @@
+//! Gitea / Forgejo provider: fetches the PR, its diff and changed files; posts one review.
+//!
+//! Maps `GET /repos/{owner}/{repo}/pulls/{n}` (+ `.diff`, `/files`, `/commits`, and `/reviews`
+//! with each review's `/comments` as existing threads) onto `ReviewInput` and `ReviewResult` onto
+//! `POST /repos/{owner}/{repo}/pulls/{n}/reviews` with inline comments.
+//! Auth: `GITEA_TOKEN` or `FORGEJO_TOKEN` (`Authorization: token ...`).
+
+use serde_json::{json, Value};
+
+use super::{file_entry, opt_str, pull_request_revisions, require_pull_request, str_field};
+use super::github::commit_from_json;
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpProvider, RateLimit};
+use crate::pr_url::Platform;
+use crate::review_input::{CommitInfo, ReviewInput, ReviewThread, ThreadComment};
+use crate::review_result::{ReviewResult, ReviewVerdict};
+use crate::review_target::ReviewTarget;
+
+/// Page size for `/pulls/{n}/files`.
+const FILES_PAGE_LIMIT: usize = 50;
+/// Page size for `/pulls/{n}/commits`.
+const COMMITS_PAGE_LIMIT: usize = 50;
+/// Page size for `/pulls/{n}/reviews`.
+const REVIEWS_PAGE_LIMIT: usize = 50;
+
+/// `McpProvider` backed by the Gitea / Forgejo REST API (`/api/v1`).
+pub struct GiteaProvider {
+    http: HttpClient,
+}
+
+impl GiteaProvider {
+    /// Creates a provider with a preconfigured HTTP client (auth headers already set).
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Creates a provider with auth from `GITEA_TOKEN` or `FORGEJO_TOKEN` (anonymous if unset).
+    pub fn from_env() -> Self {
+        let http = HttpClient::new();
+        let http = match token_from_env(&["GITEA_TOKEN", "FORGEJO_TOKEN"]) {
+            Some(token) => http.with_header("Authorization", format!("token {}", token)),
+            None => http,
+        };
+        Self::new(http)
+    }
+
+    /// Commits of the PR at `base`, oldest first (Gitea lists them newest first), with the
+    /// stats and files Gitea includes in each entry.
+    fn commits(&self, base: &str) -> Result<Vec<CommitInfo>, McpError> {
+        let mut commits = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/commits?page={}&limit={}",
+                base, page, COMMITS_PAGE_LIMIT
+            ))?;
+            let entries = batch.as_array().cloned().unwrap_or_default();
+            commits.extend(entries.iter().map(commit_from_json));
+            if entries.len() < COMMITS_PAGE_LIMIT {
+                break;
+            }
+        }
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// Existing threads of the PR at `base`: each submitted review's body as a general thread,
+    /// and its line comments grouped by path and line (Gitea has no reply ids; replies are more
+    /// comments on the same line). Pending reviews are drafts and skipped.
+    fn review_threads(&self, base: &str) -> Result<Vec<ReviewThread>, McpError> {
+        let mut threads = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/reviews?page={}&limit={}",
+                base, page, REVIEWS_PAGE_LIMIT
+            ))?;
+            let reviews = batch.as_array().cloned().unwrap_or_default();
+            for review in reviews.iter().filter(|r| r["state"] != "PENDING") {
+                let body = str_field(review, "body");
+                if !body.trim().is_empty() {
+                    threads.push(ReviewThread {
+                        comments: vec![ThreadComment {
+                            author: str_field(&review["user"], "login"),
+                            body,
+                        }],
+                        ..ReviewThread::default()
+                    });
+                }
+                if review["comments_count"].as_u64().unwrap_or_default() == 0 {
+                    continue;
+                }
+                let id = review["id"].as_u64().unwrap_or_default();
+                let comments =
+                    self.http.get_json(&format!("{}/reviews/{}/comments", base, id))?;
+                let stale = review["stale"].as_bool().unwrap_or_default();
+                for comment in comments.as_array().into_iter().flatten() {
+                    add_line_comment(&mut threads, comment, stale);
+                }
+            }
+            if reviews.len() < REVIEWS_PAGE_LIMIT {
+                break;
+            }
+        }
+        Ok(threads)
+    }
+}
+
+impl McpProvider for GiteaProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let pr = require_pull_request(target, Platform::Gitea, "GiteaProvider")?;
+        let base = pr.to_api_url();
+        let meta = self.http.get_json(&base)?;
+        let diff = self.http.get_text(&format!("{}.diff", base), Some("text/plain"))?;
+        let mut files = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/files?page={}&limit={}",
+                base, page, FILES_PAGE_LIMIT
+            ))?;
+            let entries = batch.as_array().cloned().unwrap_or_default();
+            for entry in &entries {
+                let path = str_field(entry, "filename");
+                if !path.is_empty() {
+                    files.push(file_entry(&path));
+                }
+            }
+            if entries.len() < FILES_PAGE_LIMIT {
+                break;
+            }
+        }
+        Ok(ReviewInput::new()
+            .with_title(str_field(&meta, "title"))
+            .with_description(str_field(&meta, "body"))
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(pull_request_revisions(&meta))
+            .with_commits(self.commits(&base)?)
+            .with_threads(self.review_threads(&base)?))
+    }
+
+    /// Submits one review with an event from the verdict (`APPROVED`, `REQUEST_CHANGES` or
+    /// `COMMENT`): summary as body, line comments on the new side (`new_position`),
+    /// pinned to the reviewed head (`commit_id`).
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::Gitea, "GiteaProvider")?;
+        let comments: Vec<_> = result
+            .line_comments
+            .iter()
+            .map(|c| json!({ "path": c.path, "body": c.body, "new_position": c.line }))
+            .collect();
+        let event = match result.verdict {
+            Some(ReviewVerdict::Approve) => "APPROVED",
+            Some(ReviewVerdict::RequestChanges) => "REQUEST_CHANGES",
+            Some(ReviewVerdict::Comment) | None => "COMMENT",
+        };
+        let mut body = json!({
+            "body": result.summary,
+            "event": event,
+            "comments": comments,
+        });
+        if let Some(sha) = &result.head_sha {
+            body["commit_id"] = json!(sha);
+        }
+        self.http
+            .post_json(&format!("{}/reviews", pr.to_api_url()), &body)?;
+        Ok(())
+    }
+
+    /// `head.sha` of the PR; `None` for other targets.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        match target {
+            ReviewTarget::PullRequest(pr) if pr.platform == Platform::Gitea => {
+                let meta = self.http.get_json(&pr.to_api_url())?;
+                Ok(meta["head"]["sha"].as_str().map(String::from))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
+
+/// Adds a review comment to the thread on its path and line (`position`, or `original_position`
+/// once the line is gone), opening one if there is none. A thread is resolved once a comment has
+/// a `resolver`, and outdated when a comment comes from a stale review (on an older head).
+fn add_line_comment(threads: &mut Vec<ReviewThread>, c: &Value, stale: bool) {
+    let path = opt_str(&c["path"]);
+    let line = c["position"]
+        .as_u64()
+        .filter(|n| *n > 0)
+        .or_else(|| c["original_position"].as_u64())
+        .map(|n| n as u32);
+    let existing = threads
+        .iter()
+        .position(|t| t.path.is_some() && t.path == path && t.line == line);
+    let index = match existing {
+        Some(index) => index,
+        None => {
+            threads.push(ReviewThread {
+                path,
+                line,
+                ..ReviewThread::default()
+            });
+            threads.len() - 1
+        }
+    };
+    let thread = &mut threads[index];
+    thread.resolved |= c["resolver"].is_object();
+    thread.outdated |= stale;
+    thread.comments.push(ThreadComment {
+        author: str_field(&c["user"], "login"),
+        body: str_field(c, "body"),
+    });
+}
@@ -0,0 +1,368 @@
+//! GitHub provider: fetches PR (or commit / compare) metadata, diff and files; posts one review.
+//!
+//! Maps `GET /repos/{owner}/{repo}/pulls/{n}` (JSON and `application/vnd.github.diff`) plus the
+//! paginated `/pulls/{n}/files` (with per-file patches) onto `ReviewInput`, and `ReviewResult`
+//! onto a single batched `POST /pulls/{n}/reviews` with inline comments; PR commits come from the
+//! paginated `/pulls/{n}/commits`, with stats and files from `/commits/{sha}` for the first
+//! `COMMIT_DETAILS_LIMIT` of them. Commit and compare targets use `/commits/{sha}` and `/compare/{base}...{head}`. Base / head refs and SHAs come
+//! from the same JSON (PR JSON has no merge-base). Existing review threads, with their resolved
+//! and outdated state, come from the GraphQL API, which only token holders may use; when it
+//! refuses access or answers with GraphQL errors (e.g. a fine-grained token missing a scope),
+//! `ReviewInput::threads_unavailable` says so.
+//! Auth: `GITHUB_TOKEN` (or `GH_TOKEN`).
+
+use serde_json::{json, Value};
+
+use super::{
+    author, commit_revisions, diff_stat, opt_str, pull_request_revisions,
+    range_title_and_description, require_pull_request, split_commit_message, str_field,
+    COMMIT_DETAILS_LIMIT,
+};
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpErrorKind, McpProvider, RateLimit};
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{
+    CommitInfo, FileContent, ReviewInput, ReviewThread, Revisions, ThreadComment,
+};
+use crate::review_result::{ReviewResult, ReviewVerdict};
+use crate::review_target::{RepoRef, ReviewTarget};
+
+/// Media type that makes GitHub return a unified diff instead of JSON.
+const DIFF_MEDIA_TYPE: &str = "application/vnd.github.diff";
+/// REST API version header value.
+const API_VERSION: &str = "2022-11-28";
+/// Page size for `/pulls/{n}/files` (GitHub's maximum).
+const FILES_PER_PAGE: usize = 100;
+/// Page size for `/pulls/{n}/commits` (GitHub's maximum; PRs list at most 250 commits).
+const COMMITS_PER_PAGE: usize = 100;
+/// PR review threads, 100 per page (GitHub's maximum), each with its first 100 comments. The
+/// REST API has no resolved / outdated state.
+const REVIEW_THREADS_QUERY: &str = r#"query($owner: String!, $repo: String!, $number: Int!, $cursor: String) {
+  repository(owner: $owner, name: $repo) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100, after: $cursor) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          path line originalLine isResolved isOutdated
+          comments(first: 100) { nodes { author { login } body } }
+        }
+      }
+    }
+  }
+}"#;
+
+/// `McpProvider` backed by the GitHub REST API (github.com or GitHub Enterprise Server).
+pub struct GitHubProvider {
+    http: HttpClient,
+}
+
+impl GitHubProvider {
+    /// Creates a provider with a preconfigured HTTP client (auth headers already set).
+    pub fn new(http: HttpClient) -> Self {
+        Self {
+            http: http.with_header("X-GitHub-Api-Version", API_VERSION),
+        }
+    }
+
+    /// Creates a provider with a bearer token from `GITHUB_TOKEN` or `GH_TOKEN` (anonymous if unset;
+    /// anonymous requests are heavily rate limited and cannot post).
+    pub fn from_env() -> Self {
+        let http = HttpClient::new();
+        let http = match token_from_env(&["GITHUB_TOKEN", "GH_TOKEN"]) {
+            Some(token) => http.with_bearer_token(&token),
+            None => http,
+        };
+        Self::new(http)
+    }
+
+    fn fetch_pull_request(&self, pr: &PrUrl) -> Result<ReviewInput, McpError> {
+        let api_url = &pr.to_api_url();
+        let meta = self.http.get_json(api_url)?;
+        let diff = self.http.get_text(api_url, Some(DIFF_MEDIA_TYPE))?;
+        let mut files = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/files?per_page={}&page={}",
+                api_url, FILES_PER_PAGE, page
+            ))?;
+            let entries = batch.as_array().cloned().unwrap_or_default();
+            files.extend(entries.iter().filter_map(file_from_json));
+            if entries.len() < FILES_PER_PAGE {
+                break;
+            }
+        }
+        let mut commits = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/commits?per_page={}&page={}",
+                api_url, COMMITS_PER_PAGE, page
+            ))?;
+            let entries = batch.as_array().cloned().unwrap_or_default();
+            commits.extend(entries.iter().map(commit_from_json));
+            if entries.len() < COMMITS_PER_PAGE {
+                break;
+            }
+        }
+        // The PR commit list has no stats; the single-commit endpoint has stats and files.
+        let repo_url = format!("{}/repos/{}/{}", pr.api_base, pr.owner, pr.repo);
+        for commit in commits.iter_mut().take(COMMIT_DETAILS_LIMIT) {
+            let url = format!("{}/commits/{}", repo_url, commit.sha);
+            let detail = commit_from_json(&self.http.get_json(&url)?);
+            commit.stats = detail.stats;
+            commit.files = detail.files;
+        }
+        let mut input = ReviewInput::new();
+        match self.review_threads(pr) {
+            Ok(threads) => input = input.with_threads(threads),
+            Err(e) if e.kind == McpErrorKind::Auth => {
+                input = input.with_threads_unavailable(format!(
+                    "GitHub's GraphQL API refused access, a token that can read the PR is \
+                     needed: {}",
+                    e
+                ));
+            }
+            Err(e) if matches!(e.kind, McpErrorKind::NotFound | McpErrorKind::Client) => {
+                input = input.with_threads_unavailable(format!(
+                    "GitHub's GraphQL API could not load them: {}",
+                    e
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(input
+            .with_title(str_field(&meta, "title"))
+            .with_description(str_field(&meta, "body"))
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(pull_request_revisions(&meta))
+            .with_commits(commits))
+    }
+
+    /// Review threads of `pr` via GraphQL (`POST {graphql}` with `REVIEW_THREADS_QUERY`).
+    /// GraphQL errors (served with HTTP 200) get a kind from their `type`: `FORBIDDEN` and
+    /// `INSUFFICIENT_SCOPES` are `Auth`, `NOT_FOUND` is `NotFound`, `RATE_LIMITED` is
+    /// `RateLimited`, the rest `Client`.
+    fn review_threads(&self, pr: &PrUrl) -> Result<Vec<ReviewThread>, McpError> {
+        let url = graphql_url(&pr.api_base);
+        let number: u64 = pr
+            .id
+            .parse()
+            .map_err(|_| McpError::new(format!("invalid GitHub PR number: {}", pr.id)))?;
+        let mut threads = Vec::new();
+        let mut cursor = Value::Null;
+        loop {
+            let body = json!({
+                "query": REVIEW_THREADS_QUERY,
+                "variables": {
+                    "owner": pr.owner,
+                    "repo": pr.repo,
+                    "number": number,
+                    "cursor": cursor,
+                },
+            });
+            let resp = self.http.post_json(&url, &body)?;
+            if let Some(error) = resp["errors"].as_array().and_then(|e| e.first()) {
+                let kind = match error["type"].as_str() {
+                    Some("FORBIDDEN" | "INSUFFICIENT_SCOPES") => McpErrorKind::Auth,
+                    Some("NOT_FOUND") => McpErrorKind::NotFound,
+                    Some("RATE_LIMITED") => McpErrorKind::RateLimited,
+                    _ => McpErrorKind::Client,
+                };
+                return Err(McpError::new(format!(
+                    "GitHub GraphQL {}: {}",
+                    url,
+                    error["message"].as_str().unwrap_or("query failed")
+                ))
+                .with_kind(kind));
+            }
+            let page = &resp["data"]["repository"]["pullRequest"]["reviewThreads"];
+            threads.extend(page["nodes"].as_array().into_iter().flatten().map(thread_from_json));
+            if !page["pageInfo"]["hasNextPage"].as_bool().unwrap_or_default() {
+                return Ok(threads);
+            }
+            cursor = page["pageInfo"]["endCursor"].clone();
+        }
+    }
+
+    /// Commit or compare resource: JSON carries commits and files (GitHub lists at most 300 files).
+    fn fetch_range(
+        &self,
+        api_url: &str,
+        commits: Vec<CommitInfo>,
+        base: &str,
+        head: &str,
+        meta: &Value,
+        revisions: Revisions,
+    ) -> Result<ReviewInput, McpError> {
+        let diff = self.http.get_text(api_url, Some(DIFF_MEDIA_TYPE))?;
+        let messages: Vec<(String, String)> =
+            commits.iter().map(|c| split_commit_message(&c.message)).collect();
+        let (title, description) = range_title_and_description(&messages, base, head);
+        let files = meta["files"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(file_from_json)
+            .collect();
+        Ok(ReviewInput::new()
+            .with_title(title)
+            .with_description(description)
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(commits))
+    }
+}
+
+impl McpProvider for GitHubProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        match target {
+            ReviewTarget::Commit { repo, sha } => {
+                let url = format!("{}/commits/{}", repo_api_url(repo)?, sha);
+                let meta = self.http.get_json(&url)?;
+                let commits = vec![commit_from_json(&meta)];
+                let revisions =
+                    commit_revisions(opt_str(&meta["sha"]), opt_str(&meta["parents"][0]["sha"]));
+                self.fetch_range(&url, commits, &format!("{}^", sha), sha, &meta, revisions)
+            }
+            ReviewTarget::Compare { repo, base, head } => {
+                let url = format!("{}/compare/{}...{}", repo_api_url(repo)?, base, head);
+                let meta = self.http.get_json(&url)?;
+                let commits = meta["commits"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(commit_from_json)
+                    .collect();
+                let revisions = Revisions {
+                    base_ref: Some(base.clone()),
+                    base_sha: opt_str(&meta["base_commit"]["sha"]),
+                    head_ref: Some(head.clone()),
+                    head_sha: meta["commits"]
+                        .as_array()
+                        .and_then(|c| c.last())
+                        .and_then(|c| opt_str(&c["sha"])),
+                    merge_base: opt_str(&meta["merge_base_commit"]["sha"]),
+                };
+                self.fetch_range(&url, commits, base, head, &meta, revisions)
+            }
+            _ => {
+                let pr = require_pull_request(target, Platform::GitHub, "GitHubProvider")?;
+                self.fetch_pull_request(pr)
+            }
+        }
+    }
+
+    /// Submits one review: summary as body, event from the verdict (`COMMENT` by default),
+    /// line comments on the right side of the diff, pinned to the reviewed head (`commit_id`).
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::GitHub, "GitHubProvider")?;
+        let comments: Vec<_> = result
+            .line_comments
+            .iter()
+            .map(|c| json!({ "path": c.path, "line": c.line, "side": "RIGHT", "body": c.body }))
+            .collect();
+        let event = match result.verdict {
+            Some(ReviewVerdict::Approve) => "APPROVE",
+            Some(ReviewVerdict::RequestChanges) => "REQUEST_CHANGES",
+            Some(ReviewVerdict::Comment) | None => "COMMENT",
+        };
+        let mut body = json!({
+            "body": result.summary,
+            "event": event,
+            "comments": comments,
+        });
+        if let Some(sha) = &result.head_sha {
+            body["commit_id"] = json!(sha);
+        }
+        self.http
+            .post_json(&format!("{}/reviews", pr.to_api_url()), &body)?;
+        Ok(())
+    }
+
+    /// `head.sha` of the PR; commit targets are their own head.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        match target {
+            ReviewTarget::Commit { sha, .. } => Ok(Some(sha.clone())),
+            ReviewTarget::PullRequest(pr) if pr.platform == Platform::GitHub => {
+                let meta = self.http.get_json(&pr.to_api_url())?;
+                Ok(meta["head"]["sha"].as_str().map(String::from))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
+
+/// `{api_base}/repos/{owner}/{repo}`, erroring unless `repo` is on GitHub.
+fn repo_api_url(repo: &RepoRef) -> Result<String, McpError> {
+    if repo.platform != Platform::GitHub {
+        return Err(McpError::new(format!(
+            "GitHubProvider cannot handle {} repository {}",
+            repo.platform.name(),
+            repo.project_path()
+        )));
+    }
+    Ok(format!("{}/repos/{}/{}", repo.api_base, repo.owner, repo.repo))
+}
+
+/// GraphQL endpoint for a REST API base: `{base}/graphql`, or `/api/graphql` for GitHub
+/// Enterprise Server's `/api/v3`.
+fn graphql_url(api_base: &str) -> String {
+    let base = api_base.trim_end_matches('/');
+    format!("{}/graphql", base.strip_suffix("/v3").unwrap_or(base))
+}
+
+/// `ReviewThread` from a GraphQL `reviewThreads` node; outdated threads keep their original line.
+fn thread_from_json(t: &Value) -> ReviewThread {
+    ReviewThread {
+        path: opt_str(&t["path"]),
+        line: t["line"]
+            .as_u64()
+            .or_else(|| t["originalLine"].as_u64())
+            .map(|n| n as u32),
+        resolved: t["isResolved"].as_bool().unwrap_or_default(),
+        outdated: t["isOutdated"].as_bool().unwrap_or_default(),
+        comments: t["comments"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| ThreadComment {
+                author: str_field(&c["author"], "login"),
+                body: str_field(c, "body"),
+            })
+            .collect(),
+    }
+}
+
+/// `CommitInfo` from a commit object (`sha`, `commit.author`, `commit.message`, and `stats` /
+/// `files` when the API includes them). Gitea / Forgejo serve the same shape.
+pub(crate) fn commit_from_json(c: &Value) -> CommitInfo {
+    let who = &c["commit"]["author"];
+    CommitInfo {
+        sha: str_field(c, "sha"),
+        author: author(&str_field(who, "name"), &str_field(who, "email")),
+        message: str_field(&c["commit"], "message"),
+        stats: diff_stat(&c["stats"]),
+        files: c["files"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|f| f["filename"].as_str().map(String::from))
+            .collect(),
+    }
+}
+
+/// `FileContent` from a `files[]` entry: `filename` and the per-file `patch` (absent for binaries).
+fn file_from_json(entry: &Value) -> Option<FileContent> {
+    let path = entry["filename"].as_str()?;
+    Some(FileContent {
+        path: path.to_string(),
+        diff: entry["patch"].as_str().map(String::from),
+        content: None,
+        status: None,
+    })
+}
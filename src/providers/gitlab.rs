@@ -0,0 +1,415 @@
+//! GitLab provider: fetches the MR, its diffs and `diff_refs`; posts positioned discussions.
+//!
+//! Maps `GET /projects/{id}/merge_requests/{iid}` plus the paginated `/diffs` (falling back to the
+//! older `/changes`), `/commits` (stats and files of the first `COMMIT_DETAILS_LIMIT` from
+//! `/repository/commits/{sha}` and its `/diff`) and `/discussions` (existing threads; system
+//! notes are dropped) onto `ReviewInput`. `post_review` turns each `LineComment` into a discussion
+//! whose `position` carries the MR's `diff_refs` (base, start and head SHAs), then posts the summary
+//! as an MR note and approves on an `approve` verdict; it refuses to place comments if the MR head
+//! moved since the reviewed `ReviewResult::head_sha`. Commit and compare targets use
+//! `/repository/commits/{sha}` and `/repository/compare`. Auth: `GITLAB_TOKEN` (`PRIVATE-TOKEN` header).
+
+use serde_json::{json, Value};
+
+use super::{
+    author, commit_revisions, diff_stat, opt_str, range_title_and_description,
+    require_pull_request, split_commit_message, str_field, ReviewPoster, COMMIT_DETAILS_LIMIT,
+};
+use crate::diff_model::LineKind;
+use crate::http::{token_from_env, HttpClient};
+use crate::mcp_provider::{McpError, McpErrorKind, McpProvider, RateLimit};
+use crate::pr_url::{encode_path_segment, Platform};
+use crate::review_input::{
+    CommitInfo, FileContent, ReviewInput, ReviewThread, Revisions, ThreadComment,
+};
+use crate::review_result::{ReviewResult, ReviewVerdict};
+use crate::review_target::{RepoRef, ReviewTarget};
+
+/// Page size for `/merge_requests/{iid}/diffs`.
+const DIFFS_PER_PAGE: usize = 100;
+/// Page size for `/merge_requests/{iid}/commits`.
+const COMMITS_PER_PAGE: usize = 100;
+/// Page size for `/merge_requests/{iid}/discussions`.
+const DISCUSSIONS_PER_PAGE: usize = 100;
+
+/// `McpProvider` backed by the GitLab REST API (`/api/v4`, gitlab.com or self-managed).
+pub struct GitLabProvider {
+    http: HttpClient,
+}
+
+impl GitLabProvider {
+    /// Creates a provider with a preconfigured HTTP client (auth headers already set).
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Creates a provider with a `PRIVATE-TOKEN` header from `GITLAB_TOKEN` (anonymous if unset).
+    pub fn from_env() -> Self {
+        let http = HttpClient::new();
+        let http = match token_from_env(&["GITLAB_TOKEN"]) {
+            Some(token) => http.with_header("PRIVATE-TOKEN", token),
+            None => http,
+        };
+        Self::new(http)
+    }
+
+    /// MR file diffs from `/diffs` (GitLab 15.7+), or `/changes` on older instances (which answer
+    /// `/diffs` with 404); other errors are returned as is.
+    fn mr_diffs(&self, mr_url: &str) -> Result<Vec<Value>, McpError> {
+        let mut entries = Vec::new();
+        for page in 1.. {
+            let batch = match self.http.get_json(&format!(
+                "{}/diffs?page={}&per_page={}",
+                mr_url, page, DIFFS_PER_PAGE
+            )) {
+                Ok(batch) => batch,
+                Err(e) if page == 1 && e.kind == McpErrorKind::NotFound => {
+                    let changes = self.http.get_json(&format!("{}/changes", mr_url))?;
+                    return Ok(changes["changes"].as_array().cloned().unwrap_or_default());
+                }
+                Err(e) => return Err(e),
+            };
+            let batch = batch.as_array().cloned().unwrap_or_default();
+            let done = batch.len() < DIFFS_PER_PAGE;
+            entries.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// MR commits, oldest first (the API lists newest first). The list has no stats, so the first
+    /// `COMMIT_DETAILS_LIMIT` commits are read one by one from the project's repository.
+    fn mr_commits(&self, mr_url: &str, project_url: &str) -> Result<Vec<CommitInfo>, McpError> {
+        let mut commits = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/commits?page={}&per_page={}",
+                mr_url, page, COMMITS_PER_PAGE
+            ))?;
+            let batch = batch.as_array().cloned().unwrap_or_default();
+            commits.extend(batch.iter().map(commit_from_json));
+            if batch.len() < COMMITS_PER_PAGE {
+                break;
+            }
+        }
+        commits.reverse();
+        for commit in commits.iter_mut().take(COMMIT_DETAILS_LIMIT) {
+            let url = format!("{}/repository/commits/{}", project_url, commit.sha);
+            commit.stats = diff_stat(&self.http.get_json(&url)?["stats"]);
+            commit.files = diff_paths(&self.http.get_json(&format!("{}/diff", url))?);
+        }
+        Ok(commits)
+    }
+
+    /// MR discussions with at least one user note, oldest first. A thread is outdated when its
+    /// position was taken on another head than `head_sha`.
+    fn mr_threads(
+        &self,
+        mr_url: &str,
+        head_sha: Option<&str>,
+    ) -> Result<Vec<ReviewThread>, McpError> {
+        let mut threads = Vec::new();
+        for page in 1.. {
+            let batch = self.http.get_json(&format!(
+                "{}/discussions?page={}&per_page={}",
+                mr_url, page, DISCUSSIONS_PER_PAGE
+            ))?;
+            let batch = batch.as_array().cloned().unwrap_or_default();
+            threads.extend(batch.iter().filter_map(|d| thread_from_json(d, head_sha)));
+            if batch.len() < DISCUSSIONS_PER_PAGE {
+                break;
+            }
+        }
+        Ok(threads)
+    }
+
+    fn fetch_range(
+        &self,
+        commits: Vec<CommitInfo>,
+        base: &str,
+        head: &str,
+        diffs: &[Value],
+        revisions: Revisions,
+    ) -> ReviewInput {
+        let messages: Vec<(String, String)> =
+            commits.iter().map(|c| split_commit_message(&c.message)).collect();
+        let (title, description) = range_title_and_description(&messages, base, head);
+        let (diff, files) = diff_and_files(diffs);
+        ReviewInput::new()
+            .with_title(title)
+            .with_description(description)
+            .with_diff(diff)
+            .with_files(files)
+            .with_revisions(revisions)
+            .with_commits(commits)
+    }
+}
+
+impl McpProvider for GitLabProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        match target {
+            ReviewTarget::Commit { repo, sha } => {
+                let url = format!("{}/repository/commits/{}", project_api_url(repo)?, sha);
+                let meta = self.http.get_json(&url)?;
+                let diffs = self.http.get_json(&format!("{}/diff", url))?;
+                let mut commit = commit_from_json(&meta);
+                commit.files = diff_paths(&diffs);
+                Ok(self.fetch_range(
+                    vec![commit],
+                    &format!("{}^", sha),
+                    sha,
+                    diffs.as_array().map(Vec::as_slice).unwrap_or_default(),
+                    commit_revisions(opt_str(&meta["id"]), opt_str(&meta["parent_ids"][0])),
+                ))
+            }
+            ReviewTarget::Compare { repo, base, head } => {
+                let url = format!(
+                    "{}/repository/compare?from={}&to={}&straight=false",
+                    project_api_url(repo)?,
+                    encode_path_segment(base),
+                    encode_path_segment(head)
+                );
+                let meta = self.http.get_json(&url)?;
+                let commits = meta["commits"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(commit_from_json)
+                    .collect();
+                Ok(self.fetch_range(
+                    commits,
+                    base,
+                    head,
+                    meta["diffs"].as_array().map(Vec::as_slice).unwrap_or_default(),
+                    Revisions {
+                        base_ref: Some(base.clone()),
+                        head_ref: Some(head.clone()),
+                        head_sha: opt_str(&meta["commit"]["id"]),
+                        ..Revisions::default()
+                    },
+                ))
+            }
+            _ => {
+                let pr = require_pull_request(target, Platform::GitLab, "GitLabProvider")?;
+                let mr_url = pr.to_api_url();
+                let project_url =
+                    format!("{}/projects/{}", pr.api_base, pr.encoded_project_path());
+                let meta = self.http.get_json(&mr_url)?;
+                let (diff, files) = diff_and_files(&self.mr_diffs(&mr_url)?);
+                let revisions = merge_request_revisions(&meta);
+                let threads = self.mr_threads(&mr_url, revisions.head_sha.as_deref())?;
+                Ok(ReviewInput::new()
+                    .with_title(str_field(&meta, "title"))
+                    .with_description(str_field(&meta, "description"))
+                    .with_diff(diff)
+                    .with_files(files)
+                    .with_revisions(revisions)
+                    .with_commits(self.mr_commits(&mr_url, &project_url)?)
+                    .with_threads(threads))
+            }
+        }
+    }
+
+    /// Creates one positioned discussion per line comment (placed via the MR's parsed diff, see
+    /// `comment_position`), then the summary note; an `approve` verdict also approves the MR.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, Platform::GitLab, "GitLabProvider")?;
+        let mr_url = pr.to_api_url();
+        let mut poster = ReviewPoster::new(&self.http);
+        if !result.line_comments.is_empty() {
+            let meta = self.http.get_json(&mr_url)?;
+            let refs = &meta["diff_refs"];
+            let Some(current) = refs["head_sha"].as_str() else {
+                return Err(McpError::new(format!(
+                    "MR {} has no diff_refs (diff not generated yet?)",
+                    pr
+                )));
+            };
+            if let Some(reviewed) = result.head_sha.as_deref().filter(|sha| *sha != current) {
+                return Err(McpError::new(format!(
+                    "MR {} moved from {} to {} since it was reviewed; not posting line comments",
+                    pr, reviewed, current
+                )));
+            }
+            let (diff, _) = diff_and_files(&self.mr_diffs(&mr_url)?);
+            let input = ReviewInput::new().with_diff(diff);
+            for c in &result.line_comments {
+                let body = json!({
+                    "body": c.body,
+                    "position": comment_position(refs, &input, &c.path, c.line),
+                });
+                poster.post_json(&format!("{}/discussions", mr_url), &body)?;
+            }
+        }
+        if !result.summary.is_empty() {
+            poster.post_json(
+                &format!("{}/notes", mr_url),
+                &json!({ "body": result.summary }),
+            )?;
+        }
+        if result.verdict == Some(ReviewVerdict::Approve) {
+            poster.post_json(&format!("{}/approve", mr_url), &json!({}))?;
+        }
+        Ok(())
+    }
+
+    /// `sha` of the MR's source branch head; commit targets are their own head.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        match target {
+            ReviewTarget::Commit { sha, .. } => Ok(Some(sha.clone())),
+            ReviewTarget::PullRequest(pr) if pr.platform == Platform::GitLab => {
+                let meta = self.http.get_json(&pr.to_api_url())?;
+                Ok(meta["sha"].as_str().map(String::from))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+}
+
+/// `{api_base}/projects/{encoded path}`, erroring unless `repo` is on GitLab.
+fn project_api_url(repo: &RepoRef) -> Result<String, McpError> {
+    if repo.platform != Platform::GitLab {
+        return Err(McpError::new(format!(
+            "GitLabProvider cannot handle {} repository {}",
+            repo.platform.name(),
+            repo.project_path()
+        )));
+    }
+    Ok(format!("{}/projects/{}", repo.api_base, repo.encoded_project_path()))
+}
+
+/// `CommitInfo` from a commit object (`id`, `author_name`, `author_email`, `message`, and
+/// `stats` on single-commit responses).
+fn commit_from_json(c: &Value) -> CommitInfo {
+    CommitInfo {
+        sha: str_field(c, "id"),
+        author: author(&str_field(c, "author_name"), &str_field(c, "author_email")),
+        message: str_field(c, "message"),
+        stats: diff_stat(&c["stats"]),
+        files: Vec::new(),
+    }
+}
+
+/// New paths of a commit's `/diff` entries.
+fn diff_paths(diffs: &Value) -> Vec<String> {
+    diffs
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|d| str_field(d, "new_path"))
+        .collect()
+}
+
+/// Discussion `position` for a comment on new-side `line` of `path`, from the MR's `diff_refs`
+/// and its parsed diff: context lines carry `old_line` and `new_line`, added lines (and lines
+/// missing from the diff) `new_line` only; `old_path` is the path before a rename. Line comments
+/// address the new side, so removed lines cannot be commented on.
+fn comment_position(refs: &Value, input: &ReviewInput, path: &str, line: u32) -> Value {
+    let file = input.diff_file(path);
+    let old_path = file.and_then(|f| f.old_path.as_deref()).unwrap_or(path);
+    let mut position = json!({
+        "position_type": "text",
+        "base_sha": refs["base_sha"],
+        "start_sha": refs["start_sha"],
+        "head_sha": refs["head_sha"],
+        "old_path": old_path,
+        "new_path": path,
+    });
+    match file.and_then(|f| f.new_side_line(line)) {
+        Some(l) if l.kind == LineKind::Context => {
+            position["old_line"] = json!(l.old_line);
+            position["new_line"] = json!(l.new_line);
+        }
+        _ => position["new_line"] = json!(line),
+    }
+    position
+}
+
+/// `ReviewThread` from a discussion, `None` if it only has system notes (pushes, label changes).
+/// Path and line come from the first note's `position`; resolved means every resolvable note
+/// is.
+fn thread_from_json(discussion: &Value, head_sha: Option<&str>) -> Option<ReviewThread> {
+    let notes: Vec<&Value> = discussion["notes"]
+        .as_array()?
+        .iter()
+        .filter(|n| !n["system"].as_bool().unwrap_or_default())
+        .collect();
+    let position = &notes.first()?["position"];
+    let resolvable: Vec<&&Value> =
+        notes.iter().filter(|n| n["resolvable"].as_bool().unwrap_or_default()).collect();
+    let position_head = position["head_sha"].as_str();
+    Some(ReviewThread {
+        path: opt_str(&position["new_path"]).or_else(|| opt_str(&position["old_path"])),
+        line: position["new_line"]
+            .as_u64()
+            .or_else(|| position["old_line"].as_u64())
+            .map(|n| n as u32),
+        resolved: !resolvable.is_empty()
+            && resolvable.iter().all(|n| n["resolved"].as_bool().unwrap_or_default()),
+        outdated: position_head.is_some() && head_sha.is_some() && position_head != head_sha,
+        comments: notes
+            .iter()
+            .map(|n| ThreadComment {
+                author: str_field(&n["author"], "username"),
+                body: str_field(n, "body"),
+            })
+            .collect(),
+    })
+}
+
+/// Revisions of an MR: branches from `target_branch` / `source_branch`, SHAs from `diff_refs`
+/// (`start_sha` is the target head, `base_sha` the merge-base).
+pub(crate) fn merge_request_revisions(meta: &Value) -> Revisions {
+    let refs = &meta["diff_refs"];
+    Revisions {
+        base_ref: opt_str(&meta["target_branch"]),
+        base_sha: opt_str(&refs["start_sha"]),
+        head_ref: opt_str(&meta["source_branch"]),
+        head_sha: opt_str(&refs["head_sha"]).or_else(|| opt_str(&meta["sha"])),
+        merge_base: opt_str(&refs["base_sha"]),
+    }
+}
+
+/// Full unified diff and per-file entries from GitLab diff objects (`old_path`, `new_path`,
+/// `new_file`, `deleted_file`, `diff`), which carry hunks but no `diff --git` headers.
+fn diff_and_files(entries: &[Value]) -> (String, Vec<FileContent>) {
+    let mut diff = String::new();
+    let mut files = Vec::new();
+    for entry in entries {
+        let new_path = str_field(entry, "new_path");
+        let old_path = str_field(entry, "old_path");
+        let hunks = str_field(entry, "diff");
+        let old_side = if entry["new_file"].as_bool().unwrap_or(false) {
+            "/dev/null".to_string()
+        } else {
+            format!("a/{}", old_path)
+        };
+        let new_side = if entry["deleted_file"].as_bool().unwrap_or(false) {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", new_path)
+        };
+        let mut file_diff = format!(
+            "diff --git a/{} b/{}\n--- {}\n+++ {}\n",
+            old_path, new_path, old_side, new_side
+        );
+        file_diff.push_str(&hunks);
+        if !file_diff.ends_with('\n') {
+            file_diff.push('\n');
+        }
+        diff.push_str(&file_diff);
+        files.push(FileContent {
+            path: new_path,
+            diff: Some(file_diff),
+            content: None,
+            status: None,
+        });
+    }
+    (diff, files)
+}
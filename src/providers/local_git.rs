@@ -0,0 +1,220 @@
+//! Local git provider: builds `ReviewInput` from a checkout and a base/head range; no forge needed.
+//!
+//! Runs the `git` binary in `ReviewTarget::LocalRange::path`: title and description come from the
+//! commit messages of `base..head`, the diff from `git diff base...head` (merge-base, as a PR
+//! would show), `files` from the per-file sections plus head contents, `commits` with
+//! `--numstat` stats, and revisions from `git rev-parse` and `git merge-base`. `post_review` writes a
+//! plain-text report to stdout or a file (`ReviewOutput`) when one is configured.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{range_title_and_description, split_commit_message, write_review};
+use crate::diff_model::DiffStat;
+use crate::mcp_provider::{McpError, McpProvider};
+use crate::review_input::{CommitInfo, ReviewInput, Revisions};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// Where `LocalGitProvider::post_review` writes the review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewOutput {
+    Stdout,
+    /// Overwrites the file on each post.
+    File(PathBuf),
+}
+
+/// `McpProvider` over a local repository (`ReviewTarget::LocalRange` only).
+pub struct LocalGitProvider {
+    /// Where `post_review` writes; `None` disables posting.
+    output: Option<ReviewOutput>,
+}
+
+impl Default for LocalGitProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalGitProvider {
+    /// Creates a provider that does not post: the CLI prints the review itself, and a report on
+    /// stdout as well would print it twice. Set an output with `with_output`.
+    pub fn new() -> Self {
+        Self {
+            output: None,
+        }
+    }
+
+    /// Builder-style: set where reviews are written (enables posting).
+    pub fn with_output(mut self, output: ReviewOutput) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    fn local_range(target: &ReviewTarget) -> Result<(&Path, &str, &str), McpError> {
+        match target {
+            ReviewTarget::LocalRange { path, base, head } => Ok((path, base, head)),
+            other => Err(McpError::new(format!(
+                "LocalGitProvider can only review local ranges, not a {}: {}",
+                other.kind_name(),
+                other
+            ))),
+        }
+    }
+}
+
+impl McpProvider for LocalGitProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let (path, base, head) = Self::local_range(target)?;
+        let base_sha = commit_sha(path, base)?;
+        let head_sha = commit_sha(path, head)?;
+        let merge_base = git(path, &["merge-base", &base_sha, &head_sha])?.trim().to_string();
+        let commits = commits(path, base, head)?;
+        let messages: Vec<(String, String)> =
+            commits.iter().map(|c| split_commit_message(&c.message)).collect();
+        let (title, description) = range_title_and_description(&messages, base, head);
+        let range = format!("{}...{}", base, head);
+        let diff = git(path, &["diff", "--no-color", "--no-ext-diff", &range])?;
+        let mut input = ReviewInput::new()
+            .with_title(title)
+            .with_description(description)
+            .with_diff(diff)
+            .with_commits(commits)
+            .with_revisions(Revisions {
+                base_ref: Some(base.to_string()),
+                base_sha: Some(base_sha),
+                head_ref: Some(head.to_string()),
+                head_sha: Some(head_sha.clone()),
+                merge_base: Some(merge_base),
+            });
+        for i in 0..input.files.len() {
+            let deleted = input
+                .diff_file(&input.files[i].path)
+                .is_some_and(|f| f.new_path.is_none());
+            if !deleted {
+                input.files[i].content = blob_text(path, &head_sha, &input.files[i].path)?;
+            }
+        }
+        Ok(input)
+    }
+
+    /// Writes `format_review` output to stdout or the configured file.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        Self::local_range(target)?;
+        let report = format_review(target, result);
+        write_review(self.output.as_ref(), &report, "LocalGitProvider")
+    }
+
+    /// Local ranges are "posted" to the configured output, if any.
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.output.is_some() && matches!(target, ReviewTarget::LocalRange { .. })
+    }
+
+    /// `head` resolved to a commit SHA.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let (path, _, head) = Self::local_range(target)?;
+        commit_sha(path, head).map(Some)
+    }
+}
+
+/// Plain-text review report: target, reviewed head, verdict, summary, then `path:line` comments.
+pub fn format_review(target: &ReviewTarget, result: &ReviewResult) -> String {
+    let mut out = format!("Review of {}\n", target);
+    if let Some(sha) = &result.head_sha {
+        out.push_str(&format!("Head: {}\n", sha));
+    }
+    if let Some(verdict) = result.verdict {
+        out.push_str(&format!("Verdict: {:?}\n", verdict));
+    }
+    out.push('\n');
+    out.push_str(result.summary.trim_end());
+    out.push('\n');
+    if !result.line_comments.is_empty() {
+        out.push('\n');
+        for c in &result.line_comments {
+            out.push_str(&format!("{}:{}: {}\n", c.path, c.line, c.body));
+        }
+    }
+    out
+}
+
+/// Runs `git -C path args...` and returns stdout; non-zero exit becomes `McpError` with stderr.
+fn git(path: &Path, args: &[&str]) -> Result<String, McpError> {
+    let bytes = git_bytes(path, args)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn git_bytes(path: &Path, args: &[&str]) -> Result<Vec<u8>, McpError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .map_err(|e| McpError::new(format!("running git in {}: {}", path.display(), e)))?;
+    if !output.status.success() {
+        return Err(McpError::new(format!(
+            "git {} in {}: {}",
+            args.join(" "),
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// `rev` resolved to a commit SHA; errors if it does not name a commit.
+fn commit_sha(path: &Path, rev: &str) -> Result<String, McpError> {
+    let sha = git(path, &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?;
+    Ok(sha.trim().to_string())
+}
+
+/// Commits in `base..head`, oldest first, with `--numstat` stats (binary files count no lines).
+fn commits(path: &Path, base: &str, head: &str) -> Result<Vec<CommitInfo>, McpError> {
+    let log = git(
+        path,
+        &[
+            "log",
+            "--reverse",
+            "--no-renames",
+            "--numstat",
+            "--format=%x1e%H%x00%an <%ae>%x00%B%x00",
+            &format!("{}..{}", base, head),
+        ],
+    )?;
+    Ok(log
+        .split('\x1e')
+        .filter_map(|entry| {
+            let mut fields = entry.splitn(4, '\0');
+            let (sha, author, message) = (fields.next()?, fields.next()?, fields.next()?);
+            let mut stats = DiffStat::default();
+            let mut files = Vec::new();
+            for line in fields.next().unwrap_or_default().lines() {
+                let mut cols = line.splitn(3, '\t');
+                let (Some(added), Some(removed), Some(file)) = (cols.next(), cols.next(), cols.next())
+                else {
+                    continue;
+                };
+                stats.additions += added.parse::<u32>().unwrap_or_default();
+                stats.deletions += removed.parse::<u32>().unwrap_or_default();
+                files.push(file.to_string());
+            }
+            Some(CommitInfo {
+                sha: sha.to_string(),
+                author: author.to_string(),
+                message: message.trim().to_string(),
+                stats: Some(stats),
+                files,
+            })
+        })
+        .collect())
+}
+
+/// File text at `rev`; `None` for binary content (contains NUL).
+fn blob_text(path: &Path, rev: &str, file: &str) -> Result<Option<String>, McpError> {
+    let bytes = git_bytes(path, &["show", &format!("{}:{}", rev, file)])?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
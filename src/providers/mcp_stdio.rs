@@ -0,0 +1,361 @@
+//! MCP stdio provider: spawns a configured MCP server and uses its PR tools.
+//!
+//! The server is started lazily on first use (`McpClient::spawn` does the `initialize` /
+//! `tools/list` handshake) and kept for later calls. `McpToolSet` names the tools to call and how
+//! a `PrUrl` becomes their arguments; `github()` matches github-mcp-server, `gitlab()` matches the
+//! GitLab MCP servers. Tool results are JSON text (PR metadata, file list) or a raw diff.
+//! Neither tool set has a tool for existing review threads, so `ReviewInput::threads` stays empty,
+//! nor one for the PR's commits, which `ReviewInput::commits_unavailable` records.
+//! Configured from `QUICK_REVIEW_MCP_COMMAND` (see `from_env`).
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::{pull_request_revisions, require_pull_request, str_field};
+use super::gitlab::merge_request_revisions;
+use crate::diff_model::FileStatus;
+use crate::mcp_client::{McpClient, McpServerConfig};
+use crate::mcp_provider::{McpError, McpProvider};
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{FileContent, ReviewInput, Revisions};
+use crate::review_result::{ReviewResult, ReviewVerdict};
+use crate::review_target::ReviewTarget;
+
+/// Tool names and argument shape for one family of MCP servers.
+#[derive(Debug, Clone)]
+pub struct McpToolSet {
+    /// Platform whose PR/MR URLs these tools accept.
+    pub platform: Platform,
+    /// Returns PR/MR metadata as JSON (`title`, `body` or `description`). Required.
+    pub get_pull_request: String,
+    /// Returns the unified diff as text. Skipped if the server does not list it.
+    pub get_diff: Option<String>,
+    /// Returns changed files as a JSON array. Skipped if the server does not list it.
+    pub get_files: Option<String>,
+    /// Creates a review from `body`, `event` and `comments`. `None` disables posting.
+    pub create_review: Option<String>,
+    /// Arguments identifying the PR/MR, merged into every call.
+    pub pr_args: fn(&PrUrl) -> Value,
+}
+
+impl McpToolSet {
+    /// github-mcp-server tools (`owner`, `repo`, `pullNumber`).
+    pub fn github() -> Self {
+        Self {
+            platform: Platform::GitHub,
+            get_pull_request: "get_pull_request".into(),
+            get_diff: Some("get_pull_request_diff".into()),
+            get_files: Some("get_pull_request_files".into()),
+            create_review: Some("create_pull_request_review".into()),
+            pr_args: |pr| {
+                json!({
+                    "owner": pr.owner,
+                    "repo": pr.repo,
+                    "pullNumber": pr.id.parse::<u64>().unwrap_or_default()
+                })
+            },
+        }
+    }
+
+    /// GitLab MCP server tools (`project_id` as the project path, `merge_request_iid`).
+    pub fn gitlab() -> Self {
+        Self {
+            platform: Platform::GitLab,
+            get_pull_request: "get_merge_request".into(),
+            get_diff: None,
+            get_files: Some("get_merge_request_diffs".into()),
+            create_review: Some("create_merge_request_review".into()),
+            pr_args: |pr| {
+                json!({
+                    "project_id": format!("{}/{}", pr.owner, pr.repo),
+                    "merge_request_iid": pr.id
+                })
+            },
+        }
+    }
+
+    /// Tool set by name (`github` or `gitlab`).
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(Self::github()),
+            "gitlab" => Some(Self::gitlab()),
+            _ => None,
+        }
+    }
+
+    fn args(&self, pr: &PrUrl, extra: Value) -> Value {
+        let mut args = (self.pr_args)(pr);
+        if let (Some(args), Value::Object(extra)) = (args.as_object_mut(), extra) {
+            args.extend(extra);
+        }
+        args
+    }
+}
+
+/// `McpProvider` that delegates to an external MCP server over stdio.
+pub struct McpStdioProvider {
+    config: McpServerConfig,
+    tools: McpToolSet,
+    client: Mutex<Option<McpClient>>,
+}
+
+impl McpStdioProvider {
+    /// Creates a provider; the server is not started until the first fetch or post.
+    pub fn new(config: McpServerConfig, tools: McpToolSet) -> Self {
+        Self {
+            config,
+            tools,
+            client: Mutex::new(None),
+        }
+    }
+
+    /// Platform whose PRs/MRs the configured tools handle.
+    pub fn platform(&self) -> &Platform {
+        &self.tools.platform
+    }
+
+    /// Provider from `QUICK_REVIEW_MCP_COMMAND` (command line, split on whitespace), optional
+    /// `QUICK_REVIEW_MCP_TOOLS` (`github` or `gitlab`, default `github`) and
+    /// `QUICK_REVIEW_MCP_TIMEOUT_SECS`. `None` if no command is configured.
+    pub fn from_env() -> Result<Option<Self>, McpError> {
+        let Some(mut config) = std::env::var("QUICK_REVIEW_MCP_COMMAND")
+            .ok()
+            .and_then(|line| McpServerConfig::from_command_line(&line))
+        else {
+            return Ok(None);
+        };
+        let tools_name =
+            std::env::var("QUICK_REVIEW_MCP_TOOLS").unwrap_or_else(|_| "github".into());
+        let tools = McpToolSet::by_name(&tools_name).ok_or_else(|| {
+            McpError::new(format!(
+                "QUICK_REVIEW_MCP_TOOLS: unknown tool set '{}'",
+                tools_name
+            ))
+        })?;
+        if let Ok(secs) = std::env::var("QUICK_REVIEW_MCP_TIMEOUT_SECS") {
+            let secs = secs.parse().map_err(|_| {
+                McpError::new(format!(
+                    "QUICK_REVIEW_MCP_TIMEOUT_SECS: not a number: {}",
+                    secs
+                ))
+            })?;
+            config = config.with_timeout(Duration::from_secs(secs));
+        }
+        Ok(Some(Self::new(config, tools)))
+    }
+
+    /// Runs `f` with the connected client, spawning the server if needed. A server that has
+    /// exited is dropped so the next call starts a fresh one.
+    fn with_client<T>(
+        &self,
+        f: impl FnOnce(&mut McpClient) -> Result<T, McpError>,
+    ) -> Result<T, McpError> {
+        let mut guard = self.client.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.is_none() {
+            *guard = Some(McpClient::spawn(&self.config)?);
+        }
+        let client = guard.as_mut().expect("client was just set");
+        let result = f(client);
+        if result.is_err() && !client.is_running() {
+            *guard = None;
+        }
+        result
+    }
+}
+
+impl McpProvider for McpStdioProvider {
+    /// Metadata, files and diff from the PR tools; no threads or commits (see the module docs).
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let pr = require_pull_request(target, self.tools.platform.clone(), "McpStdioProvider")?;
+        let tools = &self.tools;
+        self.with_client(|client| {
+            if !client.has_tool(&tools.get_pull_request) {
+                return Err(missing_tool(client, &tools.get_pull_request));
+            }
+            let meta = parse_json(
+                &tools.get_pull_request,
+                &client.call_tool(&tools.get_pull_request, tools.args(pr, json!({})))?,
+            )?;
+            let description = match meta["body"].as_str() {
+                Some(body) => body.to_string(),
+                None => str_field(&meta, "description"),
+            };
+            let files = match tools.get_files.as_deref().filter(|t| client.has_tool(t)) {
+                Some(tool) => {
+                    let text = client.call_tool(tool, tools.args(pr, json!({})))?;
+                    files_from_json(&parse_json(tool, &text)?)
+                }
+                None => Vec::new(),
+            };
+            let diff = match tools.get_diff.as_deref().filter(|t| client.has_tool(t)) {
+                Some(tool) => client.call_tool(tool, tools.args(pr, json!({})))?,
+                None => diff_from_files(&files),
+            };
+            let revisions = revisions_from_json(&meta);
+            Ok(ReviewInput::new()
+                .with_title(str_field(&meta, "title"))
+                .with_description(description)
+                .with_diff(diff)
+                .with_files(files)
+                .with_revisions(revisions)
+                .with_commits_unavailable("the MCP server's tools do not list PR commits"))
+        })
+    }
+
+    /// Calls the review tool once with the summary, an event from the verdict and line comments.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        let pr = require_pull_request(target, self.tools.platform.clone(), "McpStdioProvider")?;
+        let Some(tool) = self.tools.create_review.as_deref() else {
+            return Err(McpError::new("McpStdioProvider: no review tool configured"));
+        };
+        let event = match result.verdict {
+            Some(ReviewVerdict::Approve) => "APPROVE",
+            Some(ReviewVerdict::RequestChanges) => "REQUEST_CHANGES",
+            Some(ReviewVerdict::Comment) | None => "COMMENT",
+        };
+        let comments: Vec<_> = result
+            .line_comments
+            .iter()
+            .map(|c| json!({ "path": c.path, "line": c.line, "side": "RIGHT", "body": c.body }))
+            .collect();
+        let args = self.tools.args(
+            pr,
+            json!({ "body": result.summary, "event": event, "comments": comments }),
+        );
+        self.with_client(|client| {
+            if !client.has_tool(tool) {
+                return Err(missing_tool(client, tool));
+            }
+            client.call_tool(tool, args).map(|_| ())
+        })
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        target.supports_posting() && self.tools.create_review.is_some()
+    }
+
+    /// Head SHA from the PR/MR metadata tool; `None` for targets of other platforms.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let pr = match target {
+            ReviewTarget::PullRequest(pr) if pr.platform.same_kind(&self.tools.platform) => pr,
+            _ => return Ok(None),
+        };
+        let tools = &self.tools;
+        self.with_client(|client| {
+            if !client.has_tool(&tools.get_pull_request) {
+                return Err(missing_tool(client, &tools.get_pull_request));
+            }
+            let meta = parse_json(
+                &tools.get_pull_request,
+                &client.call_tool(&tools.get_pull_request, tools.args(pr, json!({})))?,
+            )?;
+            Ok(revisions_from_json(&meta).head_sha)
+        })
+    }
+}
+
+/// Revisions from PR/MR metadata: GitLab servers return MR JSON (`diff_refs`), GitHub ones PR
+/// JSON (`base` / `head`).
+fn revisions_from_json(meta: &Value) -> Revisions {
+    if meta["diff_refs"].is_object() {
+        merge_request_revisions(meta)
+    } else {
+        pull_request_revisions(meta)
+    }
+}
+
+fn missing_tool(client: &McpClient, tool: &str) -> McpError {
+    McpError::new(format!(
+        "MCP server '{}' has no tool '{}' (available: {})",
+        client.server_name(),
+        tool,
+        client.tools().join(", ")
+    ))
+}
+
+fn parse_json(tool: &str, text: &str) -> Result<Value, McpError> {
+    serde_json::from_str(text)
+        .map_err(|e| McpError::new(format!("MCP tool '{}' returned invalid JSON: {}", tool, e)))
+}
+
+/// Files from a JSON array (or an object with `changes` / `files`): path from `filename`,
+/// `new_path` or `path`; per-file diff from `patch` or `diff`; status from `status` (GitHub) or
+/// `new_file` / `deleted_file` / `renamed_file` (GitLab).
+fn files_from_json(v: &Value) -> Vec<FileContent> {
+    let entries = v
+        .as_array()
+        .or_else(|| v["changes"].as_array())
+        .or_else(|| v["files"].as_array());
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = ["filename", "new_path", "path"]
+                .iter()
+                .find_map(|key| entry[key].as_str())?;
+            Some(FileContent {
+                path: path.to_string(),
+                diff: entry["patch"]
+                    .as_str()
+                    .or_else(|| entry["diff"].as_str())
+                    .map(String::from),
+                content: None,
+                status: file_status(entry),
+            })
+        })
+        .collect()
+}
+
+/// Change status of a file entry; `None` if the entry does not say.
+fn file_status(entry: &Value) -> Option<FileStatus> {
+    let from = entry["previous_filename"]
+        .as_str()
+        .or_else(|| entry["old_path"].as_str())
+        .map(String::from);
+    let flag = |key: &str| entry[key].as_bool().unwrap_or_default();
+    match entry["status"].as_str() {
+        Some("added") => Some(FileStatus::Added),
+        Some("removed") => Some(FileStatus::Deleted),
+        Some("modified" | "changed") => Some(FileStatus::Modified),
+        Some("renamed") => from.map(|from| FileStatus::Renamed { from }),
+        Some("copied") => from.map(|from| FileStatus::Copied { from }),
+        Some(_) => None,
+        None if flag("new_file") => Some(FileStatus::Added),
+        None if flag("deleted_file") => Some(FileStatus::Deleted),
+        None if flag("renamed_file") => from.map(|from| FileStatus::Renamed { from }),
+        None if entry["new_file"].is_boolean() => Some(FileStatus::Modified),
+        None => None,
+    }
+}
+
+/// Unified diff from per-file hunks when the server has no diff tool: added and deleted files get
+/// `/dev/null` on the missing side, renamed ones `rename from` / `rename to` lines.
+fn diff_from_files(files: &[FileContent]) -> String {
+    let mut diff = String::new();
+    for file in files {
+        let Some(hunks) = &file.diff else { continue };
+        let path = &file.path;
+        let (old, new) = match &file.status {
+            Some(FileStatus::Added) => ("/dev/null".to_string(), format!("b/{}", path)),
+            Some(FileStatus::Deleted) => (format!("a/{}", path), "/dev/null".to_string()),
+            Some(FileStatus::Renamed { from }) => {
+                diff.push_str(&format!(
+                    "diff --git a/{0} b/{1}\nrename from {0}\nrename to {1}\n",
+                    from, path
+                ));
+                (format!("a/{}", from), format!("b/{}", path))
+            }
+            _ => (format!("a/{}", path), format!("b/{}", path)),
+        };
+        if !matches!(file.status, Some(FileStatus::Renamed { .. })) {
+            diff.push_str(&format!("diff --git a/{0} b/{0}\n", path));
+        }
+        diff.push_str(&format!("--- {}\n+++ {}\n{}", old, new, hunks));
+        if !diff.ends_with('\n') {
+            diff.push('\n');
+        }
+    }
+    diff
+}
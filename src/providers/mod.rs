@@ -0,0 +1,225 @@
+//! Built-in providers: `McpProvider` implementations that talk to a forge's HTTP API directly,
+//! plus `LocalGitProvider` for local ranges, `PatchProvider` for patch files and mbox series,
+//! `FixtureProvider` for evaluation datasets, `McpStdioProvider` for external MCP servers,
+//! `ForgeProvider` to route each target to one of them, and decorators: record/replay (`RecordingProvider`, `ReplayProvider`) for offline runs and
+//! `CachingProvider` for a persistent fetch cache and `RetryingProvider` for transient failures.
+//!
+//! Each forge provider reads the API base from `PrUrl::api_base`, so self-hosted instances (and
+//! local HTTP stubs in tests) work without extra configuration. Tokens come from the environment.
+
+pub mod azure_devops;
+pub mod bitbucket;
+pub mod cache;
+pub mod cassette;
+pub mod fixture;
+pub mod forge;
+pub mod gerrit;
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+pub mod local_git;
+pub mod mcp_stdio;
+pub mod patch;
+pub mod retry;
+
+pub use azure_devops::AzureDevOpsProvider;
+pub use bitbucket::BitbucketProvider;
+pub use cache::CachingProvider;
+pub use cassette::{Cassette, RecordingProvider, ReplayProvider};
+pub use fixture::FixtureProvider;
+pub use forge::ForgeProvider;
+pub use gerrit::GerritProvider;
+pub use gitea::GiteaProvider;
+pub use github::GitHubProvider;
+pub use gitlab::GitLabProvider;
+pub use local_git::{LocalGitProvider, ReviewOutput};
+pub use mcp_stdio::{McpStdioProvider, McpToolSet};
+pub use patch::PatchProvider;
+pub use retry::{RetryPolicy, RetryingProvider};
+
+use std::io::Write;
+
+use serde_json::Value;
+
+use crate::diff_model::DiffStat;
+use crate::http::HttpClient;
+use crate::mcp_provider::McpError;
+use crate::pr_url::{Platform, PrUrl};
+use crate::review_input::{FileContent, Revisions};
+use crate::review_target::ReviewTarget;
+
+/// PR/MR commits (oldest first) whose stats and files are fetched with one request each; later
+/// commits go without them.
+pub(crate) const COMMIT_DETAILS_LIMIT: usize = 30;
+
+/// The PR/MR of `target`, erroring unless it is one on `platform` (compared with
+/// `Platform::same_kind`); `provider` names the caller.
+pub(crate) fn require_pull_request<'a>(
+    target: &'a ReviewTarget,
+    platform: Platform,
+    provider: &str,
+) -> Result<&'a PrUrl, McpError> {
+    let pr = target.as_pull_request().ok_or_else(|| {
+        McpError::new(format!(
+            "{} can only review PRs/MRs, not a {}: {}",
+            provider,
+            target.kind_name(),
+            target
+        ))
+    })?;
+    if pr.platform.same_kind(&platform) {
+        Ok(pr)
+    } else {
+        Err(McpError::new(format!(
+            "{} cannot handle {} URL {}",
+            provider,
+            pr.platform.name(),
+            pr
+        )))
+    }
+}
+
+/// `FileContent` with only the path set.
+pub(crate) fn file_entry(path: &str) -> FileContent {
+    FileContent {
+        path: path.to_string(),
+        diff: None,
+        content: None,
+        status: None,
+    }
+}
+
+/// String field of a JSON object, or empty if missing / null.
+pub(crate) fn str_field(v: &Value, key: &str) -> String {
+    v[key].as_str().unwrap_or_default().to_string()
+}
+
+/// Non-empty string value, or `None`.
+pub(crate) fn opt_str(v: &Value) -> Option<String> {
+    v.as_str().filter(|s| !s.is_empty()).map(String::from)
+}
+
+/// Revisions from GitHub-style PR JSON (`base` / `head` objects with `ref` and `sha`), as served
+/// by GitHub, Gitea and Forgejo; `merge_base` where the forge reports it (Gitea).
+pub(crate) fn pull_request_revisions(meta: &Value) -> Revisions {
+    Revisions {
+        base_ref: opt_str(&meta["base"]["ref"]),
+        base_sha: opt_str(&meta["base"]["sha"]),
+        head_ref: opt_str(&meta["head"]["ref"]),
+        head_sha: opt_str(&meta["head"]["sha"]),
+        merge_base: opt_str(&meta["merge_base"]),
+    }
+}
+
+/// Revisions of a single commit: its parent is both base and merge-base.
+pub(crate) fn commit_revisions(sha: Option<String>, parent: Option<String>) -> Revisions {
+    Revisions {
+        base_sha: parent.clone(),
+        head_sha: sha,
+        merge_base: parent,
+        ..Revisions::default()
+    }
+}
+
+/// `Name <email>`, or whichever of the two is present.
+pub(crate) fn author(name: &str, email: &str) -> String {
+    match (name.is_empty(), email.is_empty()) {
+        (false, false) => format!("{} <{}>", name, email),
+        (false, true) => name.to_string(),
+        _ => email.to_string(),
+    }
+}
+
+/// `DiffStat` from an object with `additions` / `deletions` counts (GitHub and GitLab commit
+/// `stats`); `None` if it has neither.
+pub(crate) fn diff_stat(v: &Value) -> Option<DiffStat> {
+    let count = |key: &str| v[key].as_u64().map(|n| n as u32);
+    match (count("additions"), count("deletions")) {
+        (None, None) => None,
+        (additions, deletions) => Some(DiffStat {
+            additions: additions.unwrap_or_default(),
+            deletions: deletions.unwrap_or_default(),
+        }),
+    }
+}
+
+/// Title and description for a commit range from its `(subject, body)` messages, oldest first:
+/// a single commit's own message, otherwise a `head (N commits since base)` title and a
+/// bulleted list of the commits.
+pub(crate) fn range_title_and_description(
+    commits: &[(String, String)],
+    base: &str,
+    head: &str,
+) -> (String, String) {
+    match commits {
+        [] => (format!("{}...{}", base, head), String::new()),
+        [(subject, body)] => (subject.clone(), body.clone()),
+        many => (
+            format!("{} ({} commits since {})", head, many.len(), base),
+            commit_list(many),
+        ),
+    }
+}
+
+/// Bulleted list of `(subject, body)` messages, bodies indented under their subject.
+pub(crate) fn commit_list(commits: &[(String, String)]) -> String {
+    commits
+        .iter()
+        .map(|(subject, body)| {
+            if body.is_empty() {
+                format!("- {}", subject)
+            } else {
+                format!("- {}\n\n  {}", subject, body.replace('\n', "\n  "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a commit message into `(subject, body)`: first line, then the rest trimmed.
+pub(crate) fn split_commit_message(message: &str) -> (String, String) {
+    match message.split_once('\n') {
+        Some((subject, body)) => (subject.trim().to_string(), body.trim().to_string()),
+        None => (message.trim().to_string(), String::new()),
+    }
+}
+
+/// Writes a plain-text review report to `output` (stdout or a file); errors without one
+/// (`provider` names the caller).
+pub(crate) fn write_review(
+    output: Option<&ReviewOutput>,
+    report: &str,
+    provider: &str,
+) -> Result<(), McpError> {
+    let Some(output) = output else {
+        return Err(McpError::new(format!("{}: no review output configured", provider)));
+    };
+    match output {
+        ReviewOutput::Stdout => std::io::stdout()
+            .write_all(report.as_bytes())
+            .map_err(|e| McpError::new(format!("writing review to stdout: {}", e))),
+        ReviewOutput::File(file) => std::fs::write(file, report)
+            .map_err(|e| McpError::new(format!("writing review to {}: {}", file.display(), e))),
+    }
+}
+
+/// Posts the requests of one review in order. A failure after a request went through is marked
+/// `McpError::partial`, so the review is not retried (and its earlier comments posted twice).
+pub(crate) struct ReviewPoster<'a> {
+    http: &'a HttpClient,
+    posted: bool,
+}
+
+impl<'a> ReviewPoster<'a> {
+    pub(crate) fn new(http: &'a HttpClient) -> Self {
+        Self { http, posted: false }
+    }
+
+    /// `HttpClient::post_json`, marking errors as partial once an earlier post succeeded.
+    pub(crate) fn post_json(&mut self, url: &str, body: &Value) -> Result<Value, McpError> {
+        let posted = self.posted;
+        let value = self.http.post_json(url, body).map_err(|e| e.with_partial(posted))?;
+        self.posted = true;
+        Ok(value)
+    }
+}
@@ -0,0 +1,409 @@
+//! Patch provider: builds `ReviewInput` from a unified diff, a `git format-patch` file or an mbox
+//! patch series (`ReviewTarget::Patch`), read from a file or stdin; no forge needed.
+//!
+//! Mail headers and commit messages give the title and description: a single patch uses its
+//! subject (without the `[PATCH n/m]` prefix) and message, a series its cover letter (`0/n`) or,
+//! without one, a list of its patches. A bare diff is titled after its file. The diff is the
+//! patches' diffs in order. The last patch's `From <sha>` line is the head revision, and a
+//! `base-commit:` line (`git format-patch --base`) the base; each patch becomes a commit. `post_review` writes the same
+//! plain-text report as `LocalGitProvider`, to the output configured with `with_output`.
+
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+
+use base64::Engine;
+
+use super::local_git::{format_review, ReviewOutput};
+use super::{commit_list, write_review};
+use crate::mcp_provider::{McpError, McpProvider};
+use crate::diff_model::{self, DiffStat};
+use crate::review_input::{CommitInfo, ReviewInput, Revisions};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// One mail of a patch series (or a single `format-patch` file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchMail {
+    /// Commit SHA from the mbox `From <sha> ...` line, if present.
+    pub sha: Option<String>,
+    /// `From:` header (decoded).
+    pub author: String,
+    /// `Subject:` header without `Re:` and `[PATCH ...]` prefixes (decoded).
+    pub subject: String,
+    /// Commit message body (or cover letter text), without the diffstat and signature.
+    pub message: String,
+    /// Diff part of the mail; empty for cover letters.
+    pub diff: String,
+    /// True for a `[PATCH 0/n]` cover letter.
+    pub cover_letter: bool,
+}
+
+impl PatchMail {
+    /// The patch as a commit: subject and message, stats and files from its diff (SHA empty if
+    /// the mail has none).
+    pub fn commit(&self) -> CommitInfo {
+        let files = diff_model::parse(&self.diff);
+        let message = if self.message.is_empty() {
+            self.subject.clone()
+        } else {
+            format!("{}\n\n{}", self.subject, self.message)
+        };
+        CommitInfo {
+            sha: self.sha.clone().unwrap_or_default(),
+            author: self.author.clone(),
+            message,
+            stats: Some(DiffStat::of(&files)),
+            files: files.iter().map(|f| f.path().to_string()).collect(),
+        }
+    }
+}
+
+/// `McpProvider` over patch files and stdin (`ReviewTarget::Patch` only).
+pub struct PatchProvider {
+    /// Where `post_review` writes; `None` disables posting.
+    output: Option<ReviewOutput>,
+    /// Standard input, read once on first use (or supplied via `with_stdin`).
+    stdin: Mutex<Option<String>>,
+}
+
+impl Default for PatchProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatchProvider {
+    /// Creates a provider that does not post: the CLI prints the review itself, and a report on
+    /// stdout as well would print it twice. Set an output with `with_output`.
+    pub fn new() -> Self {
+        Self {
+            output: None,
+            stdin: Mutex::new(None),
+        }
+    }
+
+    /// Builder-style: set where reviews are written (enables posting).
+    pub fn with_output(mut self, output: ReviewOutput) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Builder-style: use `text` for the `-` path instead of reading standard input.
+    pub fn with_stdin(self, text: impl Into<String>) -> Self {
+        *self.stdin.lock().unwrap_or_else(|e| e.into_inner()) = Some(text.into());
+        self
+    }
+
+    fn patch_path(target: &ReviewTarget) -> Result<&Path, McpError> {
+        match target {
+            ReviewTarget::Patch { path } => Ok(path),
+            other => Err(McpError::new(format!(
+                "PatchProvider can only review patches, not a {}: {}",
+                other.kind_name(),
+                other
+            ))),
+        }
+    }
+
+    fn read(&self, path: &Path) -> Result<String, McpError> {
+        if path != Path::new("-") {
+            let bytes = std::fs::read(path)
+                .map_err(|e| McpError::new(format!("reading {}: {}", path.display(), e)))?;
+            return Ok(String::from_utf8_lossy(&bytes).into_owned());
+        }
+        let mut stdin = self.stdin.lock().unwrap_or_else(|e| e.into_inner());
+        if stdin.is_none() {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| McpError::new(format!("reading stdin: {}", e)))?;
+            *stdin = Some(String::from_utf8_lossy(&bytes).into_owned());
+        }
+        Ok(stdin.clone().unwrap_or_default())
+    }
+}
+
+impl McpProvider for PatchProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let path = Self::patch_path(target)?;
+        let text = self.read(path)?;
+        let fallback_title = match path.file_name() {
+            Some(name) if path != Path::new("-") => name.to_string_lossy().into_owned(),
+            _ => "Diff from stdin".to_string(),
+        };
+        let input = parse_patch(&text, &fallback_title);
+        if input.diff.trim().is_empty() {
+            return Err(McpError::new(format!("no diff found in {}", target)));
+        }
+        Ok(input)
+    }
+
+    /// Writes `format_review` output to stdout or the configured file.
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        Self::patch_path(target)?;
+        let report = format_review(target, result);
+        write_review(self.output.as_ref(), &report, "PatchProvider")
+    }
+
+    /// Patches are "posted" to the configured output, if any.
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.output.is_some() && matches!(target, ReviewTarget::Patch { .. })
+    }
+
+    /// SHA of the last patch's mbox `From` line, if the input is `format-patch` output.
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        let text = self.read(Self::patch_path(target)?)?;
+        Ok(split_mails(&text).into_iter().rev().find_map(|m| m.sha))
+    }
+}
+
+/// Builds `ReviewInput` from patch text: mails via `split_mails`, otherwise a bare diff titled
+/// `fallback_title` (text before the first diff header is dropped).
+pub fn parse_patch(text: &str, fallback_title: &str) -> ReviewInput {
+    let mails = split_mails(text);
+    if mails.is_empty() {
+        let diff = diff_start(text).map_or("", |i| &text[i..]);
+        return ReviewInput::new().with_title(fallback_title).with_diff(diff);
+    }
+    let patches: Vec<&PatchMail> = mails.iter().filter(|m| !m.cover_letter).collect();
+    let (title, description) = match (mails.iter().find(|m| m.cover_letter), patches.as_slice()) {
+        (Some(cover), _) => (cover.subject.clone(), cover.message.clone()),
+        (None, [single]) => (single.subject.clone(), single.message.clone()),
+        (None, many) => {
+            let messages: Vec<(String, String)> = many
+                .iter()
+                .map(|m| (m.subject.clone(), m.message.clone()))
+                .collect();
+            let first = many.first().map(|m| m.subject.as_str()).unwrap_or(fallback_title);
+            (format!("{} ({} patches)", first, many.len()), commit_list(&messages))
+        }
+    };
+    let diff: String = patches.iter().map(|m| m.diff.as_str()).collect();
+    let base = text
+        .lines()
+        .find_map(|l| l.strip_prefix("base-commit: "))
+        .map(|sha| sha.trim().to_string());
+    let revisions = Revisions {
+        base_sha: base.clone(),
+        head_sha: patches.iter().rev().find_map(|m| m.sha.clone()),
+        merge_base: base,
+        ..Revisions::default()
+    };
+    let commits = patches.iter().map(|m| m.commit()).collect();
+    ReviewInput::new()
+        .with_title(title)
+        .with_description(description)
+        .with_diff(diff)
+        .with_revisions(revisions)
+        .with_commits(commits)
+}
+
+/// Splits mbox / `format-patch` text into mails, in order. Returns an empty list for a bare
+/// diff (no `From <sha> ...` separators and no leading mail headers).
+pub fn split_mails(text: &str) -> Vec<PatchMail> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let next_is_header = lines.get(i + 1).is_some_and(|l| header_name(l).is_some());
+        if line.starts_with("From ") && next_is_header {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    if starts.is_empty() {
+        let headers: Vec<&str> = lines.iter().take_while(|l| !l.trim().is_empty()).copied().collect();
+        let is_mail = headers
+            .iter()
+            .all(|l| header_name(l).is_some() || l.starts_with([' ', '\t']))
+            && headers.iter().any(|l| header_name(l) == Some("subject"));
+        if !is_mail {
+            return Vec::new();
+        }
+        starts.push(0);
+    }
+    starts.push(text.len());
+    starts.windows(2).map(|w| parse_mail(&text[w[0]..w[1]])).collect()
+}
+
+fn parse_mail(mail: &str) -> PatchMail {
+    let mut lines = mail.split_inclusive('\n').peekable();
+    let mut sha = None;
+    if let Some(from) = lines.next_if(|l| l.starts_with("From ")) {
+        sha = from
+            .split_whitespace()
+            .nth(1)
+            .filter(|s| (4..=64).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_hexdigit()))
+            .map(String::from);
+    }
+    // Unfolded headers up to the first blank line.
+    let mut headers: Vec<String> = Vec::new();
+    for line in lines.by_ref() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        match headers.last_mut() {
+            Some(last) if line.starts_with([' ', '\t']) => {
+                last.push(' ');
+                last.push_str(line.trim_start());
+            }
+            _ => headers.push(line.to_string()),
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|h| header_name(h) == Some(name))
+            .and_then(|h| h.split_once(':'))
+            .map(|(_, v)| decode_words(v.trim()))
+            .unwrap_or_default()
+    };
+    let (subject, cover_letter) = strip_subject_prefixes(&header("subject"));
+    let body: String = lines.collect();
+    let body = strip_signature(&body);
+    let (message, diff) = match diff_start(body) {
+        Some(i) => (&body[..i], &body[i..]),
+        None => (body, ""),
+    };
+    // `---` separates the commit message from the diffstat.
+    let message = message
+        .split_inclusive('\n')
+        .take_while(|l| l.trim_end() != "---")
+        .collect::<String>();
+    PatchMail {
+        sha,
+        author: header("from"),
+        subject,
+        message: message.trim().to_string(),
+        diff: diff.to_string(),
+        cover_letter,
+    }
+}
+
+/// Lower-cased name of a `Name: value` header line, if `line` is one.
+fn header_name(line: &str) -> Option<&'static str> {
+    let (name, _) = line.split_once(':')?;
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return None;
+    }
+    // Only the names we read need to be distinguished; any other valid header is "other".
+    Some(match name.to_ascii_lowercase().as_str() {
+        "from" => "from",
+        "subject" => "subject",
+        _ => "other",
+    })
+}
+
+/// Drops `Re:` and bracketed `[PATCH v2 1/3]` prefixes; true if a prefix numbers the mail `0/n`.
+fn strip_subject_prefixes(subject: &str) -> (String, bool) {
+    let mut rest = subject.trim();
+    let mut cover_letter = false;
+    loop {
+        if rest.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("re:")) {
+            rest = rest[3..].trim_start();
+        } else if let Some(end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+            let tag = &rest[1..end + 1];
+            let numbering = tag.split_whitespace().last().and_then(|t| t.split_once('/'));
+            if let Some((n, _)) = numbering {
+                cover_letter |= n.parse::<u32>() == Ok(0);
+            }
+            rest = rest[end + 2..].trim_start();
+        } else {
+            return (rest.to_string(), cover_letter);
+        }
+    }
+}
+
+/// Byte offset of the first diff header (`diff --git`, `Index:`, or a `---` / `+++` pair).
+fn diff_start(body: &str) -> Option<usize> {
+    let lines: Vec<&str> = body.split_inclusive('\n').collect();
+    let mut offset = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let plain_diff = line.starts_with("--- ")
+            && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ "));
+        if line.starts_with("diff --git ") || line.starts_with("Index: ") || plain_diff {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Cuts the `-- ` mail signature (e.g. `-- \n2.43.0\n`) that `format-patch` appends.
+fn strip_signature(body: &str) -> &str {
+    let Some(i) = body.rfind("\n-- \n").map(|i| i + 1) else {
+        return body;
+    };
+    let tail = &body[i + 4..];
+    if tail.lines().filter(|l| !l.trim().is_empty()).count() <= 2
+        && !tail.lines().any(|l| l.starts_with("@@") || l.starts_with("diff "))
+    {
+        &body[..i]
+    } else {
+        body
+    }
+}
+
+/// Decodes RFC 2047 encoded words (`=?UTF-8?q?...?=`, `=?UTF-8?b?...?=`) in a header value.
+/// Unknown charsets are left encoded; whitespace between adjacent encoded words is dropped.
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].split_once("?=").and_then(|(word, tail)| {
+            let mut parts = word.splitn(3, '?');
+            let (charset, encoding, text) = (parts.next()?, parts.next()?, parts.next()?);
+            let bytes = match encoding.to_ascii_lowercase().as_str() {
+                "q" => decode_q(text)?,
+                "b" => base64::engine::general_purpose::STANDARD.decode(text).ok()?,
+                _ => return None,
+            };
+            let text = match charset.to_ascii_lowercase().as_str() {
+                "utf-8" | "us-ascii" => String::from_utf8_lossy(&bytes).into_owned(),
+                "iso-8859-1" | "latin1" => bytes.iter().map(|&b| b as char).collect(),
+                _ => return None,
+            };
+            Some((text, tail))
+        });
+        let gap = &rest[..start];
+        match decoded {
+            Some((text, tail)) => {
+                if !(after_word && gap.trim().is_empty()) {
+                    out.push_str(gap);
+                }
+                out.push_str(&text);
+                rest = tail;
+                after_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Q encoding: `_` is a space, `=XX` a hex byte.
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,144 @@
+//! Retry decorator: retries retryable `McpError`s with exponential backoff and jitter.
+//!
+//! A server-requested wait (`McpError::retry_after`, from `Retry-After` or a rate-limit reset)
+//! is honoured as is, up to `RetryPolicy::max_wait`; otherwise the delay doubles from
+//! `base_delay` up to `max_delay` and is jittered into its upper half. Fetches and head lookups
+//! retry every retryable kind; `post_review` only retries `RateLimited`, where the forge
+//! rejected the request, so a timeout after the review was accepted cannot post it twice. Forges
+//! that post a review as several requests (one per comment or thread) mark a failure after the
+//! first accepted request as `McpError::partial`, which is not retried either: that would post
+//! the earlier comments again.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::mcp_provider::{McpError, McpErrorKind, McpProvider, RateLimit};
+use crate::review_input::ReviewInput;
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+
+/// How often and how long to retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry (doubled per attempt).
+    pub base_delay: Duration,
+    /// Cap for the computed backoff delay.
+    pub max_delay: Duration,
+    /// Longest server-requested wait to sit out; longer waits fail immediately.
+    pub max_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_wait: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `attempt` (0-based) after `err`; `None` to give up.
+    pub fn delay(&self, attempt: u32, err: &McpError) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        if let Some(wait) = err.retry_after {
+            return (wait <= self.max_wait).then_some(wait);
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        Some(jitter(backoff))
+    }
+}
+
+/// Provider decorator that retries transient failures of `inner`.
+pub struct RetryingProvider {
+    inner: Arc<dyn McpProvider>,
+    policy: RetryPolicy,
+    sleep: Arc<dyn Fn(Duration) + Send + Sync>,
+}
+
+impl RetryingProvider {
+    /// Retries `inner` with the default policy.
+    pub fn new(inner: Arc<dyn McpProvider>) -> Self {
+        Self {
+            inner,
+            policy: RetryPolicy::default(),
+            sleep: Arc::new(std::thread::sleep),
+        }
+    }
+
+    /// Builder-style: set the retry policy.
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Builder-style: replace `thread::sleep` (e.g. to record delays in tests).
+    pub fn with_sleep(mut self, sleep: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.sleep = Arc::new(sleep);
+        self
+    }
+
+    fn retry<T>(
+        &self,
+        retryable: impl Fn(&McpError) -> bool,
+        mut op: impl FnMut() -> Result<T, McpError>,
+    ) -> Result<T, McpError> {
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if retryable(&e) => match self.policy.delay(attempt, &e) {
+                    Some(delay) => {
+                        (self.sleep)(delay);
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                outcome => return outcome,
+            }
+        }
+    }
+}
+
+impl McpProvider for RetryingProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        self.retry(McpError::is_retryable, || self.inner.fetch(target))
+    }
+
+    fn post_review(&self, target: &ReviewTarget, result: &ReviewResult) -> Result<(), McpError> {
+        self.retry(
+            |e| e.kind == McpErrorKind::RateLimited && !e.partial,
+            || self.inner.post_review(target, result),
+        )
+    }
+
+    fn supports_posting(&self, target: &ReviewTarget) -> bool {
+        self.inner.supports_posting(target)
+    }
+
+    fn head_sha(&self, target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        self.retry(McpError::is_retryable, || self.inner.head_sha(target))
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.inner.rate_limit()
+    }
+}
+
+/// Random duration in `[d / 2, d]`.
+fn jitter(d: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let half = d / 2;
+    let spread = half.as_millis() as u64 + 1;
+    half + Duration::from_millis(random % spread)
+}
@@ -1,9 +1,11 @@
-//! LangGraphReviewAgent: builds ReAct graph per review, implements AgentReviewer.
+//! LangGraphReviewAgent: builds ReAct graph per review, implements AsyncAgentReviewer.
 //!
-//! Holds a tokio Runtime, an LLM, and an McpProvider. In `review(pr)` creates result slot
+//! Holds an LLM and an AsyncMcpProvider. In `review(target)` creates result slot
 //! and McpReviewToolSource (tools call MCP: get_pr_context → fetch, submit_review → post),
-//! builds StateGraph (think → act → observe), compiles, runs invoke via block_on, then
-//! reads the result slot. Which MCP to call is decided by the agent at runtime.
+//! builds StateGraph (think → act → observe), compiles, awaits invoke on the caller's runtime,
+//! then reads the result slot. Which MCP to call is decided by the agent at runtime.
+//! `with_commit_hygiene` adds `COMMIT_HYGIENE_PROMPT` so the agent also reviews the commit history.
+//! Wrap in `BlockingAgentReviewer` for the blocking `AgentReviewer` API.
 
 use std::sync::Arc;
 
@@ -12,12 +14,14 @@ use langgraph::{
 };
 use tokio::sync::RwLock;
 
-use crate::agent_reviewer::{AgentReviewer, ReviewError};
-use crate::mcp_provider::McpProvider;
-use crate::pr_url::PrUrl;
+use crate::agent_reviewer::{AsyncAgentReviewer, ReviewError};
+use crate::mcp_provider::{AsyncMcpProvider, BlockingMcpProvider, McpProvider};
 use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
 use crate::review_agent::mcp_review_tools::McpReviewToolSource;
-use crate::review_agent::prompts::{pr_url_to_user_message, REVIEW_SYSTEM_PROMPT};
+use crate::review_agent::prompts::{
+    review_target_to_user_message, COMMIT_HYGIENE_PROMPT, REVIEW_SYSTEM_PROMPT,
+};
 
 /// Wrapper so we can share an `Arc<dyn LlmClient>` with ThinkNode (which takes Box<dyn LlmClient>).
 /// Delegates invoke to the inner client.
@@ -35,32 +39,59 @@ impl langgraph::LlmClient for SharedLlm {
 
 /// Review agent that runs a langgraph ReAct graph (Think → Act → Observe) per review.
 ///
-/// Holds an McpProvider; tools (McpReviewToolSource) call MCP on the agent's behalf.
-/// Builds the graph on each `review(pr)` with a fresh result slot and McpReviewToolSource.
+/// Holds an AsyncMcpProvider; tools (McpReviewToolSource) call MCP on the agent's behalf.
+/// Builds the graph on each `review(target)` with a fresh result slot and McpReviewToolSource.
 /// After invoke, reads the result from the slot; if the agent never called `submit_review`,
 /// returns `ReviewError`.
 pub struct LangGraphReviewAgent {
-    runtime: tokio::runtime::Runtime,
     llm: Arc<dyn langgraph::LlmClient + Send + Sync>,
-    mcp: Arc<dyn McpProvider + Send + Sync>,
+    mcp: Arc<dyn AsyncMcpProvider>,
+    commit_hygiene: bool,
 }
 
 impl LangGraphReviewAgent {
     /// Creates an agent with the given LLM client and MCP provider. The agent decides when to call MCP.
     pub fn new(
         llm: Arc<dyn langgraph::LlmClient + Send + Sync>,
-        mcp: Arc<dyn McpProvider + Send + Sync>,
-    ) -> Result<Self, ReviewError> {
-        let runtime = tokio::runtime::Runtime::new()
-            .map_err(|e| ReviewError { message: e.to_string() })?;
-        Ok(Self { runtime, llm, mcp })
+        mcp: Arc<dyn AsyncMcpProvider>,
+    ) -> Self {
+        Self {
+            llm,
+            mcp,
+            commit_hygiene: false,
+        }
+    }
+
+    /// Creates an agent over a blocking provider (wrapped in `BlockingMcpProvider`).
+    pub fn from_blocking_provider(
+        llm: Arc<dyn langgraph::LlmClient + Send + Sync>,
+        mcp: Arc<dyn McpProvider>,
+    ) -> Self {
+        Self::new(llm, Arc::new(BlockingMcpProvider::new(mcp)))
+    }
+
+    /// Builder-style: also review the commit history (WIP / fixup commits, unclear messages,
+    /// mixed changes).
+    pub fn with_commit_hygiene(mut self, enabled: bool) -> Self {
+        self.commit_hygiene = enabled;
+        self
+    }
+
+    /// System prompt, with the commit-history section when enabled.
+    fn system_prompt(&self) -> String {
+        if self.commit_hygiene {
+            format!("{}\n\n{}", REVIEW_SYSTEM_PROMPT, COMMIT_HYGIENE_PROMPT)
+        } else {
+            REVIEW_SYSTEM_PROMPT.to_string()
+        }
     }
 
     /// Runs the ReAct graph for one review: think → act → observe (loop until END).
     /// Tools call MCP (get_pr_context → fetch, submit_review → post). Returns the result from the slot if submit_review was called; otherwise Err.
-    fn run_review(&self, pr: &PrUrl) -> Result<ReviewResult, ReviewError> {
+    async fn run_review(&self, target: &ReviewTarget) -> Result<ReviewResult, ReviewError> {
         let result_slot: Arc<RwLock<Option<ReviewResult>>> = Arc::new(RwLock::new(None));
-        let tool_source = McpReviewToolSource::new(self.mcp.clone(), pr.clone(), result_slot.clone());
+        let tool_source =
+            McpReviewToolSource::new(self.mcp.clone(), target.clone(), result_slot.clone());
 
         let think = ThinkNode::new(Box::new(SharedLlm(self.llm.clone())));
         let act = ActNode::new(Box::new(tool_source));
@@ -80,40 +111,35 @@ impl LangGraphReviewAgent {
             .compile()
             .map_err(|e| ReviewError { message: e.to_string() })?;
 
-        let user_text = pr_url_to_user_message(pr);
+        let user_text = review_target_to_user_message(target);
         let state = ReActState {
             messages: vec![
-                Message::system(REVIEW_SYSTEM_PROMPT.to_string()),
+                Message::system(self.system_prompt()),
                 Message::user(user_text),
             ],
             tool_calls: vec![],
             tool_results: vec![],
         };
 
-        let slot = result_slot.clone();
-        let run = async move {
-            compiled.invoke(state, None).await?;
-            let guard = slot.read().await;
-            Ok(guard.clone())
-        };
-
-        let outcome = self
-            .runtime
-            .block_on(run)
+        compiled
+            .invoke(state, None)
+            .await
             .map_err(|e: AgentError| ReviewError { message: e.to_string() })?;
 
+        let outcome = result_slot.read().await.clone();
         outcome.ok_or_else(|| ReviewError {
             message: "review agent did not call submit_review".to_string(),
         })
     }
 }
 
-impl AgentReviewer for LangGraphReviewAgent {
-    fn review(
+#[async_trait::async_trait]
+impl AsyncAgentReviewer for LangGraphReviewAgent {
+    async fn review(
         &self,
         _project_path: Option<&std::path::Path>,
-        pr: &PrUrl,
+        target: &ReviewTarget,
     ) -> Result<ReviewResult, ReviewError> {
-        self.run_review(pr)
+        self.run_review(target).await
     }
 }
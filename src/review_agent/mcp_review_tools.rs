@@ -1,8 +1,10 @@
-//! MCP-backed Review ToolSource: get_pr_context and submit_review call McpProvider.
+//! MCP-backed Review ToolSource: get_pr_context and submit_review call AsyncMcpProvider.
 //!
-//! The agent decides when to call which tool: get_pr_context triggers mcp.fetch(pr),
-//! submit_review builds the result and calls mcp.post_review(pr, result). Used by
-//! `LangGraphReviewAgent` when running with PrUrl; after invoke, the adapter reads from the slot.
+//! The agent decides when to call which tool: get_pr_context triggers mcp.fetch(target),
+//! submit_review builds the result and calls mcp.post_review(target, result) when the provider
+//! supports posting for the target (line comments outside the fetched diff are moved into the
+//! summary first). Used by `LangGraphReviewAgent`; after invoke, the adapter
+//! reads from the slot. Provider calls are awaited, so network I/O never blocks a runtime worker.
 
 use async_trait::async_trait;
 use langgraph::{ToolCallContent, ToolSource, ToolSourceError, ToolSpec};
@@ -10,64 +12,71 @@ use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::mcp_provider::{McpError, McpProvider};
-use crate::pr_url::PrUrl;
+use crate::mcp_provider::{AsyncMcpProvider, McpError};
 use crate::review_input::ReviewInput;
-use crate::review_result::{LineComment, ReviewResult};
-use super::review_tools::{TOOL_GET_PR_CONTEXT, TOOL_SUBMIT_REVIEW};
+use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
+use super::prompts::{commits_context, files_context, threads_context};
+use super::review_tools::{
+    move_unanchored_comments, parse_verdict, unanchored_note, LineCommentInput, ReviewToolSource,
+    TOOL_GET_COMMITS, TOOL_GET_PR_CONTEXT, TOOL_SUBMIT_REVIEW,
+};
 
-/// MCP-backed tool source: get_pr_context calls mcp.fetch(pr), submit_review calls mcp.post_review.
+/// MCP-backed tool source: get_pr_context calls mcp.fetch(target), submit_review calls mcp.post_review.
 ///
-/// Holds the PR URL and a result slot. On first get_pr_context, fetches via MCP and caches
+/// Holds the review target and a result slot. On first get_pr_context, fetches via MCP and caches
 /// ReviewInput; submit_review builds ReviewResult, writes to slot, and posts via MCP.
 pub struct McpReviewToolSource {
-    mcp: Arc<dyn McpProvider + Send + Sync>,
-    pr: PrUrl,
+    mcp: Arc<dyn AsyncMcpProvider>,
+    target: ReviewTarget,
     result_slot: Arc<RwLock<Option<ReviewResult>>>,
     cached: Arc<RwLock<Option<ReviewInput>>>,
 }
 
 impl McpReviewToolSource {
-    /// Creates a new McpReviewToolSource for the given PR. Fetch happens on first get_pr_context.
+    /// Creates a new McpReviewToolSource for the given target. Fetch happens on first get_pr_context.
     pub fn new(
-        mcp: Arc<dyn McpProvider + Send + Sync>,
-        pr: PrUrl,
+        mcp: Arc<dyn AsyncMcpProvider>,
+        target: ReviewTarget,
         result_slot: Arc<RwLock<Option<ReviewResult>>>,
     ) -> Self {
         Self {
             mcp,
-            pr,
+            target,
             result_slot,
             cached: Arc::new(RwLock::new(None)),
         }
     }
 
-    /// Returns the same tool specs as ReviewToolSource (get_pr_context, submit_review).
+    /// Returns the same tool specs as ReviewToolSource (get_pr_context, get_commits, submit_review).
     pub fn tool_specs() -> Vec<ToolSpec> {
-        super::review_tools::ReviewToolSource::tool_specs()
+        ReviewToolSource::tool_specs()
+    }
+
+    /// Runs `f` on the target's input, fetching it via MCP on first use.
+    async fn with_input<T>(&self, f: impl FnOnce(&ReviewInput) -> T) -> Result<T, ToolSourceError> {
+        let mut cached = self.cached.write().await;
+        if cached.is_none() {
+            let input = self.mcp.fetch(&self.target).await.map_err(|e: McpError| {
+                ToolSourceError::InvalidInput(format!("MCP fetch failed: {}", e))
+            })?;
+            *cached = Some(input);
+        }
+        Ok(f(cached.as_ref().expect("input was just fetched")))
     }
 
     fn get_part_from_input(input: &ReviewInput, part: &str) -> String {
         match part {
             "title" => input.title.clone(),
             "description" => input.description.clone(),
-            "diff" => input.diff.clone(),
-            "files" => {
-                let list: Vec<String> = input.files.iter().map(|f| f.path.clone()).collect();
-                list.join(", ")
-            }
+            "diff" => input.reviewable_diff(),
+            "files" => files_context(input),
+            "threads" => threads_context(input),
             _ => format!("Unknown part: {}", part),
         }
     }
 }
 
-#[derive(serde::Deserialize)]
-struct LineCommentInput {
-    path: String,
-    line: u32,
-    body: String,
-}
-
 #[async_trait]
 impl ToolSource for McpReviewToolSource {
     async fn list_tools(&self) -> Result<Vec<ToolSpec>, ToolSourceError> {
@@ -85,15 +94,13 @@ impl ToolSource for McpReviewToolSource {
                     .get("part")
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
-                let mut cached = self.cached.write().await;
-                if cached.is_none() {
-                    let input = self.mcp.fetch(&self.pr).map_err(|e: McpError| {
-                        ToolSourceError::InvalidInput(format!("MCP fetch failed: {}", e))
-                    })?;
-                    *cached = Some(input);
-                }
-                let input = cached.as_ref().unwrap();
-                let text = Self::get_part_from_input(input, part);
+                let text = self
+                    .with_input(|input| Self::get_part_from_input(input, part))
+                    .await?;
+                Ok(ToolCallContent { text })
+            }
+            TOOL_GET_COMMITS => {
+                let text = self.with_input(commits_context).await?;
                 Ok(ToolCallContent { text })
             }
             TOOL_SUBMIT_REVIEW => {
@@ -107,35 +114,33 @@ impl ToolSource for McpReviewToolSource {
                 let line_comments: Option<Vec<LineCommentInput>> =
                     serde_json::from_value(arguments.get("line_comments").cloned().unwrap_or(json!([])))
                         .ok();
-                let comments = line_comments
-                    .unwrap_or_default()
-                    .into_iter()
-                    .filter_map(|c| {
-                        if c.line >= 1 && !c.path.is_empty() && !c.body.is_empty() {
-                            Some(LineComment {
-                                path: c.path,
-                                line: c.line,
-                                body: c.body,
-                            })
-                        } else {
-                            None
-                        }
+                let verdict = parse_verdict(&arguments);
+                let mut result = ReviewToolSource::build_review_result(summary, line_comments, verdict);
+                let moved = self
+                    .with_input(|input| {
+                        result.head_sha = input.revisions.head_sha.clone();
+                        move_unanchored_comments(input, &mut result)
                     })
-                    .collect();
-                let result = ReviewResult {
-                    summary,
-                    line_comments: comments,
-                };
-                self.mcp.post_review(&self.pr, &result).map_err(|e: McpError| {
-                    ToolSourceError::InvalidInput(format!("MCP post_review failed: {}", e))
-                })?;
+                    .await?;
+                let posted = self.mcp.supports_posting(&self.target);
+                if posted {
+                    self.mcp.post_review(&self.target, &result).await.map_err(|e: McpError| {
+                        ToolSourceError::InvalidInput(format!("MCP post_review failed: {}", e))
+                    })?;
+                }
                 let mut slot = self.result_slot.write().await;
                 if slot.is_none() {
                     *slot = Some(result);
                 }
-                Ok(ToolCallContent {
-                    text: "Review submitted and posted via MCP.".to_string(),
-                })
+                let text = if posted {
+                    "Review submitted and posted via MCP.".to_string()
+                } else {
+                    format!(
+                        "Review submitted (not posted: posting is not supported for a {}).",
+                        self.target.kind_name()
+                    )
+                } + &unanchored_note(&moved);
+                Ok(ToolCallContent { text })
             }
             _ => Err(ToolSourceError::NotFound(name.to_string())),
         }
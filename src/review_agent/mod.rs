@@ -1,6 +1,6 @@
-//! Review agent: LangGraph ReAct agent that implements `AgentReviewer`.
+//! Review agent: LangGraph ReAct agent that implements `AsyncAgentReviewer`.
 //!
-//! Uses tools `get_pr_context` and `submit_review`; result is read from a slot
+//! Uses tools `get_pr_context`, `get_commits` and `submit_review`; result is read from a slot
 //! after invoke. See `idea/langgraph-review-agent.md`.
 
 mod agent;
@@ -9,5 +9,8 @@ mod prompts;
 mod review_tools;
 
 pub use agent::LangGraphReviewAgent;
-pub use prompts::{review_input_to_user_message, REVIEW_SYSTEM_PROMPT};
-pub use review_tools::{ReviewToolSource, TOOL_GET_PR_CONTEXT, TOOL_SUBMIT_REVIEW};
+pub use prompts::{
+    review_input_to_user_message, review_target_to_user_message, COMMIT_HYGIENE_PROMPT,
+    REVIEW_SYSTEM_PROMPT,
+};
+pub use review_tools::{ReviewToolSource, TOOL_GET_COMMITS, TOOL_GET_PR_CONTEXT, TOOL_SUBMIT_REVIEW};
@@ -3,66 +3,229 @@
 //! Used by `LangGraphReviewAgent` to build initial `ReActState` messages.
 //! See `idea/langgraph-review-agent.md` for the prompt design.
 
-use crate::pr_url::{Platform, PrUrl};
+use crate::commit_hygiene;
+use crate::pr_url::PrUrl;
 use crate::review_input::ReviewInput;
+use crate::review_target::ReviewTarget;
 
 /// System prompt for the code review ReAct agent.
 ///
-/// Tells the agent it is a code reviewer, describes the input (PR title, description, diff, files),
-/// the tools (`get_pr_context` to read a part, `submit_review` to submit the final result),
-/// and that it **must** call `submit_review` exactly once when done.
+/// Tells the agent it is a code reviewer, describes the input (PR title, description, diff, files,
+/// existing review threads), the tools (`get_pr_context` to read a part, `get_commits` for the
+/// commit list, `submit_review` to submit the final result), that it should not repeat points
+/// already raised in existing threads, and that it **must** call `submit_review` exactly once
+/// when done.
 pub const REVIEW_SYSTEM_PROMPT: &str = r#"You are a code review agent. Your input is the current PR's title, description, diff, and file list.
 
 RULES:
-1. Use get_pr_context(part: "title" | "description" | "diff" | "files") to load PR content (call at least once).
+1. Use get_pr_context(part: "title" | "description" | "diff" | "files" | "threads") to load PR content (call at least once). Use get_commits when the individual commits matter.
 2. When your review is complete, you MUST call submit_review once with:
    - summary: string (overall review summary, required)
    - line_comments: optional array of { path, line, body } for per-line comments (line >= 1).
+   - verdict: optional "approve" | "comment" | "request_changes".
 3. If you do not call submit_review, the review will fail.
-4. Be concise and focused; for line comments, cite file path and line number clearly."#;
+4. Be concise and focused; for line comments, cite file path and line number clearly.
+5. Check get_pr_context(part: "threads") for review comments already on the PR (by humans, bots or earlier reviews). Do not repeat points raised there, open or resolved; you may refer to a thread (e.g. "as already noted on src/lib.rs:12") when it bears on your review, or say in the summary whether an open point still applies."#;
+
+/// Appended to `REVIEW_SYSTEM_PROMPT` for the optional commit-hygiene review
+/// (`LangGraphReviewAgent::with_commit_hygiene`).
+pub const COMMIT_HYGIENE_PROMPT: &str = r#"COMMIT HISTORY:
+Also review the commit history: call get_commits and check each commit. Flag WIP and fixup/squash commits that should be folded before merging, messages that do not explain what changed and why, and commits that mix unrelated changes (e.g. a refactor together with a feature, or edits to unrelated areas). get_commits lists heuristic flags; confirm or dismiss them. Report findings in a "Commit history" section of the summary, naming each commit by short SHA and subject; do not use line comments for them. Say so briefly if the history is clean; if get_commits says the commits could not be listed, say the history was not checked instead."#;
 
 /// Builds the initial user message from `PrUrl` when the agent fetches via MCP.
 pub fn pr_url_to_user_message(pr: &PrUrl) -> String {
-    let platform = match pr.platform {
-        Platform::GitHub => "GitHub",
-        Platform::GitLab => "GitLab",
+    format!(
+        "Review the {} PR on {}: {} / {} #{}.\nUse get_pr_context(part) to load title, description, diff, files, or threads. When done, call submit_review.",
+        pr.platform.name(),
+        pr.host,
+        pr.owner,
+        pr.repo,
+        pr.id
+    )
+}
+
+/// Builds the initial user message for any `ReviewTarget` (PR/MR via `pr_url_to_user_message`).
+pub fn review_target_to_user_message(target: &ReviewTarget) -> String {
+    let subject = match target {
+        ReviewTarget::PullRequest(pr) => return pr_url_to_user_message(pr),
+        ReviewTarget::Commit { repo, sha } => format!(
+            "commit {} in the {} repository {} / {} on {}",
+            sha,
+            repo.platform.name(),
+            repo.owner,
+            repo.repo,
+            repo.host
+        ),
+        ReviewTarget::Compare { repo, base, head } => format!(
+            "the changes of {} compared with {} in the {} repository {} / {} on {}",
+            head,
+            base,
+            repo.platform.name(),
+            repo.owner,
+            repo.repo,
+            repo.host
+        ),
+        ReviewTarget::LocalRange { path, base, head } => format!(
+            "the local changes {}...{} in {}",
+            base,
+            head,
+            path.display()
+        ),
+        ReviewTarget::Patch { .. } | ReviewTarget::Fixture { .. } => format!("the {}", target),
+        ReviewTarget::Diff { title } => format!("the supplied diff \"{}\"", title),
     };
     format!(
-        "Review the {} PR: {} / {} #{}.\nUse get_pr_context(part) to load title, description, diff, or files. When done, call submit_review.",
-        platform, pr.owner, pr.repo, pr.id
+        "Review {}.\nUse get_pr_context(part) to load title, description, diff, files, or threads. When done, call submit_review.",
+        subject
     )
 }
 
 /// Builds the user message text from `ReviewInput` for the ReAct agent.
 ///
-/// Format: Title, Description, Diff, then Files list. Matches the parts returned by
-/// `get_pr_context` (title, description, diff, files). Used by `ReviewToolSource::get_pr_context`.
+/// Format: Title, Description, Diff, then Files list, then existing review threads if there are
+/// any. Matches the parts returned by `get_pr_context` (title, description, diff, files,
+/// threads). The diff is `ReviewInput::reviewable_diff`; each file shows its change status, if
+/// known.
 pub fn review_input_to_user_message(input: &ReviewInput) -> String {
     let files_list = input
         .files
         .iter()
         .map(|f| {
             let extra = match (&f.diff, &f.content) {
-                (Some(_), Some(_)) => " (diff+content)",
-                (Some(_), None) => " (diff)",
-                (None, Some(_)) => " (content)",
+                (Some(_), Some(_)) => "diff+content",
+                (Some(_), None) => "diff",
+                (None, Some(_)) => "content",
                 (None, None) => "",
             };
-            format!("{}{}", f.path, extra)
+            let notes: Vec<String> = f
+                .status
+                .iter()
+                .map(|s| s.to_string())
+                .chain((!extra.is_empty()).then(|| extra.to_string()))
+                .collect();
+            if notes.is_empty() {
+                f.path.clone()
+            } else {
+                format!("{} ({})", f.path, notes.join(", "))
+            }
         })
         .collect::<Vec<_>>()
         .join(", ");
     let n = input.files.len();
+    let threads = if input.threads.is_empty() && input.threads_unavailable.is_none() {
+        String::new()
+    } else {
+        format!("\n\n{}", threads_context(input))
+    };
     format!(
-        "Title: {}\n\nDescription: {}\n\nDiff:\n{}\n\nFiles ({}): {}",
+        "Title: {}\n\nDescription: {}\n\nDiff:\n{}\n\nFiles ({}): {}{}",
         input.title,
         input.description,
-        input.diff,
+        input.reviewable_diff(),
         n,
         if files_list.is_empty() {
             "(none)".to_string()
         } else {
             files_list
-        }
+        },
+        threads
     )
 }
+
+/// The `files` part of `get_pr_context`: paths with their change status, e.g.
+/// `src/lib.rs (modified), logo.png (binary)`.
+pub(crate) fn files_context(input: &ReviewInput) -> String {
+    input
+        .files
+        .iter()
+        .map(|f| match &f.status {
+            Some(status) => format!("{} ({})", f.path, status),
+            None => f.path.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The `get_commits` tool output: one block per commit (short SHA, subject, author, stats,
+/// files, `commit_hygiene` flags, indented body), oldest first, plus a note when the source
+/// could not list them.
+pub(crate) fn commits_context(input: &ReviewInput) -> String {
+    let unavailable = input.commits_unavailable.as_ref().map(|reason| {
+        format!(
+            "The commits could not be listed ({}); the commit history was not checked.",
+            reason
+        )
+    });
+    if input.commits.is_empty() {
+        return unavailable.unwrap_or_else(|| "No commits listed for this change.".to_string());
+    }
+    let mut out = format!("{} commit(s), oldest first:", input.commits.len());
+    for commit in &input.commits {
+        let short = commit.sha.get(..12).unwrap_or(&commit.sha);
+        out.push_str(&format!(
+            "\n\n{} {}\n  Author: {}",
+            short,
+            commit.subject(),
+            commit.author
+        ));
+        if let Some(stats) = commit.stats {
+            out.push_str(&format!("\n  Stats: +{} -{}", stats.additions, stats.deletions));
+        }
+        if !commit.files.is_empty() {
+            out.push_str(&format!("\n  Files: {}", commit.files.join(", ")));
+        }
+        let issues = commit_hygiene::check(commit);
+        if !issues.is_empty() {
+            let flags: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            out.push_str(&format!("\n  Flags: {}", flags.join(", ")));
+        }
+        if !commit.body().is_empty() {
+            out.push_str(&format!("\n\n  {}", commit.body().replace('\n', "\n  ")));
+        }
+    }
+    if let Some(note) = unavailable {
+        out.push_str(&format!("\n\n{}", note));
+    }
+    out
+}
+
+/// The `threads` part of `get_pr_context`: each existing review thread with its location, state
+/// and comments, e.g. `1. src/lib.rs:12 (open)` followed by `   alice: ...` lines.
+pub(crate) fn threads_context(input: &ReviewInput) -> String {
+    let unavailable = input.threads_unavailable.as_ref().map(|reason| {
+        format!(
+            "Existing review threads could not be loaded ({}); \
+             there may be discussion not shown here.",
+            reason
+        )
+    });
+    if input.threads.is_empty() {
+        return unavailable.unwrap_or_else(|| "No existing review threads.".to_string());
+    }
+    let mut out = format!("{} existing review thread(s):", input.threads.len());
+    for (i, thread) in input.threads.iter().enumerate() {
+        let place = match (&thread.path, thread.line) {
+            (Some(path), Some(line)) => format!("{}:{}", path, line),
+            (Some(path), None) => path.clone(),
+            (None, _) => "general discussion".to_string(),
+        };
+        let state = match (thread.resolved, thread.outdated) {
+            (true, false) => "resolved",
+            (true, true) => "resolved, outdated",
+            (false, false) => "open",
+            (false, true) => "open, outdated",
+        };
+        out.push_str(&format!("\n\n{}. {} ({})", i + 1, place, state));
+        for comment in &thread.comments {
+            out.push_str(&format!(
+                "\n   {}: {}",
+                comment.author,
+                comment.body.trim().replace('\n', "\n     ")
+            ));
+        }
+    }
+    if let Some(note) = unavailable {
+        out.push_str(&format!("\n\n{}", note));
+    }
+    out
+}
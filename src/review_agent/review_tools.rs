@@ -1,7 +1,10 @@
-//! Review ToolSource: get_pr_context and submit_review tools, with result slot.
+//! Review ToolSource: get_pr_context, get_commits and submit_review tools, with result slot.
+//! `get_pr_context` also serves the review threads already on the change (part `threads`).
 //!
 //! Implements langgraph `ToolSource`. Holds `ReviewInput` and an
-//! `Arc<RwLock<Option<ReviewResult>>>`; `submit_review` writes the result there.
+//! `Arc<RwLock<Option<ReviewResult>>>`; `submit_review` writes the result there, with line
+//! comments outside the diff moved into the summary (forges reject them) and `head_sha` taken
+//! from the input's revisions.
 //! Used by `LangGraphReviewAgent`; after invoke, the adapter reads from the slot.
 
 use async_trait::async_trait;
@@ -10,13 +13,16 @@ use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use super::prompts::{commits_context, files_context, threads_context};
 use crate::review_input::ReviewInput;
-use crate::review_result::{LineComment, ReviewResult};
+use crate::review_result::{LineComment, ReviewResult, ReviewVerdict};
 
 /// Tool name for retrieving a part of the PR context.
 pub const TOOL_GET_PR_CONTEXT: &str = "get_pr_context";
 /// Tool name for submitting the final review result.
 pub const TOOL_SUBMIT_REVIEW: &str = "submit_review";
+/// Tool name: list the change's commits.
+pub const TOOL_GET_COMMITS: &str = "get_commits";
 
 /// Review-specific ToolSource: get_pr_context and submit_review.
 ///
@@ -37,31 +43,41 @@ impl ReviewToolSource {
         Self { input, result_slot }
     }
 
-    /// Returns the list of tools (get_pr_context, submit_review) with JSON schemas.
+    /// Returns the list of tools (get_pr_context, get_commits, submit_review) with JSON schemas.
     pub fn tool_specs() -> Vec<ToolSpec> {
         vec![
             ToolSpec {
                 name: TOOL_GET_PR_CONTEXT.to_string(),
-                description: Some("Retrieve a part of the PR: title, description, diff, or files.".to_string()),
+                description: Some("Retrieve a part of the PR: title, description, diff, files, or threads (existing review comments).".to_string()),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "part": {
                             "type": "string",
-                            "enum": ["title", "description", "diff", "files"],
+                            "enum": ["title", "description", "diff", "files", "threads"],
                             "description": "Which part of the PR to retrieve."
                         }
                     },
                     "required": ["part"]
                 }),
             },
+            ToolSpec {
+                name: TOOL_GET_COMMITS.to_string(),
+                description: Some("List the PR's commits, oldest first: SHA, author, message, diff stats, files and hygiene flags.".to_string()),
+                input_schema: json!({ "type": "object", "properties": {} }),
+            },
             ToolSpec {
                 name: TOOL_SUBMIT_REVIEW.to_string(),
-                description: Some("Submit the final code review. Call exactly once when done. Required: summary; optional: line_comments.".to_string()),
+                description: Some("Submit the final code review. Call exactly once when done. Required: summary; optional: line_comments, verdict.".to_string()),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "summary": { "type": "string", "description": "Overall review summary." },
+                        "verdict": {
+                            "type": "string",
+                            "enum": ["approve", "comment", "request_changes"],
+                            "description": "Optional overall verdict."
+                        },
                         "line_comments": {
                             "type": "array",
                             "items": {
@@ -86,23 +102,17 @@ impl ReviewToolSource {
         match part {
             "title" => self.input.title.clone(),
             "description" => self.input.description.clone(),
-            "diff" => self.input.diff.clone(),
-            "files" => {
-                let list: Vec<String> = self
-                    .input
-                    .files
-                    .iter()
-                    .map(|f| format!("{}", f.path))
-                    .collect();
-                list.join(", ")
-            }
+            "diff" => self.input.reviewable_diff(),
+            "files" => files_context(&self.input),
+            "threads" => threads_context(&self.input),
             _ => format!("Unknown part: {}", part),
         }
     }
 
-    fn build_review_result(
+    pub(crate) fn build_review_result(
         summary: String,
         line_comments: Option<Vec<LineCommentInput>>,
+        verdict: Option<ReviewVerdict>,
     ) -> ReviewResult {
         let comments = line_comments
             .unwrap_or_default()
@@ -122,12 +132,14 @@ impl ReviewToolSource {
         ReviewResult {
             summary,
             line_comments: comments,
+            verdict,
+            head_sha: None,
         }
     }
 }
 
 #[derive(serde::Deserialize)]
-struct LineCommentInput {
+pub(crate) struct LineCommentInput {
     path: String,
     line: u32,
     body: String,
@@ -153,6 +165,9 @@ impl ToolSource for ReviewToolSource {
                 let text = self.get_pr_context(part);
                 Ok(ToolCallContent { text })
             }
+            TOOL_GET_COMMITS => Ok(ToolCallContent {
+                text: commits_context(&self.input),
+            }),
             TOOL_SUBMIT_REVIEW => {
                 let summary = arguments
                     .get("summary")
@@ -162,16 +177,57 @@ impl ToolSource for ReviewToolSource {
                 let line_comments: Option<Vec<LineCommentInput>> =
                     serde_json::from_value(arguments.get("line_comments").cloned().unwrap_or(json!([])))
                         .ok();
-                let result = Self::build_review_result(summary, line_comments);
+                let verdict = parse_verdict(&arguments);
+                let mut result = Self::build_review_result(summary, line_comments, verdict);
+                result.head_sha = self.input.revisions.head_sha.clone();
+                let moved = move_unanchored_comments(&self.input, &mut result);
                 let mut slot = self.result_slot.write().await;
                 if slot.is_none() {
                     *slot = Some(result);
                 }
                 Ok(ToolCallContent {
-                    text: "Review submitted.".to_string(),
+                    text: format!("Review submitted.{}", unanchored_note(&moved)),
                 })
             }
             _ => Err(ToolSourceError::NotFound(name.to_string())),
         }
     }
 }
+
+/// Moves line comments whose path and line are not in the parsed diff (see
+/// `ReviewInput::is_commentable`) from `result.line_comments` to the end of its summary, and
+/// returns them.
+pub(crate) fn move_unanchored_comments(input: &ReviewInput, result: &mut ReviewResult) -> Vec<LineComment> {
+    let (kept, moved): (Vec<LineComment>, Vec<LineComment>) = std::mem::take(&mut result.line_comments)
+        .into_iter()
+        .partition(|c| input.is_commentable(&c.path, c.line));
+    result.line_comments = kept;
+    if !moved.is_empty() {
+        result.summary.push_str("\n\nComments on lines outside the diff:");
+        for c in &moved {
+            result.summary.push_str(&format!("\n- {}:{}: {}", c.path, c.line, c.body));
+        }
+    }
+    moved
+}
+
+/// Tool-response note listing comments moved by `move_unanchored_comments` (empty if none).
+pub(crate) fn unanchored_note(moved: &[LineComment]) -> String {
+    if moved.is_empty() {
+        return String::new();
+    }
+    let places: Vec<String> = moved.iter().map(|c| format!("{}:{}", c.path, c.line)).collect();
+    format!(
+        " {} line comment(s) were not on lines of the diff and were moved to the summary: {}.",
+        moved.len(),
+        places.join(", ")
+    )
+}
+
+/// Reads the optional `verdict` argument of `submit_review`; unknown values are ignored.
+pub(crate) fn parse_verdict(arguments: &serde_json::Value) -> Option<ReviewVerdict> {
+    arguments
+        .get("verdict")
+        .and_then(|v| v.as_str())
+        .and_then(ReviewVerdict::from_name)
+}
@@ -1,22 +1,126 @@
 //! Input for the agent reviewer: diff, description, and file list fetched via MCP.
 //!
-//! Produced by `McpProvider::fetch`; consumed by `AgentReviewer::review`.
+//! Produced by `McpProvider::fetch`; consumed by `AgentReviewer::review`. Setting the diff
+//! (`with_diff`, or deserializing) also parses it into `diff_files`; `files` entries get their
+//! per-file `diff` and `status` from it, and an empty file list is filled from it.
+//! `revisions` identifies the reviewed commits (base, head, merge-base); `commits` lists them
+//! (`commits_unavailable` says why when the source could not).
+//! `threads` holds the review discussion that already exists on the change.
+
+use crate::diff_model::{self, DiffFile, DiffStat, FileStatus};
 
 /// One file's metadata and content (or diff) for review.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct FileContent {
     pub path: String,
     pub diff: Option<String>,
     pub content: Option<String>,
+    /// Change status from the parsed diff; `None` if the file is not in it.
+    pub status: Option<FileStatus>,
+}
+
+/// Commit identity of the reviewed change. Providers fill what their source exposes; the rest
+/// stays `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Revisions {
+    /// Target branch (or the base of a compare / local range) as named by the source.
+    pub base_ref: Option<String>,
+    /// Commit the target branch pointed at when the change was fetched.
+    pub base_sha: Option<String>,
+    /// Source branch (or the head of a compare / local range).
+    pub head_ref: Option<String>,
+    /// Reviewed head commit.
+    pub head_sha: Option<String>,
+    /// Common ancestor of base and head; PR diffs are taken against it.
+    pub merge_base: Option<String>,
+}
+
+/// One commit of the change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CommitInfo {
+    pub sha: String,
+    /// `Name <email>`, or just the name when the source has no email.
+    pub author: String,
+    /// Full message: subject line, then the body.
+    pub message: String,
+    /// `None` when the source does not report per-commit stats.
+    pub stats: Option<DiffStat>,
+    /// Paths the commit touches; empty when unknown.
+    pub files: Vec<String>,
+}
+
+impl CommitInfo {
+    /// First line of the message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default().trim()
+    }
+
+    /// Message after the subject line, trimmed.
+    pub fn body(&self) -> &str {
+        self.message
+            .split_once('\n')
+            .map_or("", |(_, body)| body.trim())
+    }
+}
+
+/// One comment of a review thread.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ThreadComment {
+    /// Login or display name as the source reports it.
+    pub author: String,
+    pub body: String,
+}
+
+/// An existing review thread on the change: a line comment with its replies, or a general
+/// discussion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ReviewThread {
+    /// File the thread is on; `None` for general discussion.
+    pub path: Option<String>,
+    /// Line the thread is on (new side, or old side for removed lines); `None` for file-level
+    /// threads.
+    pub line: Option<u32>,
+    pub resolved: bool,
+    /// The commented code has changed since (the thread is on an older revision).
+    pub outdated: bool,
+    /// Oldest first; the first comment opened the thread.
+    pub comments: Vec<ThreadComment>,
+}
+
+impl ReviewThread {
+    /// Author of the comment that opened the thread.
+    pub fn author(&self) -> &str {
+        self.comments.first().map_or("", |c| c.author.as_str())
+    }
 }
 
 /// Aggregated input for a single PR/MR review.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, remote = "Self")]
 pub struct ReviewInput {
     pub title: String,
     pub description: String,
     pub diff: String,
     pub files: Vec<FileContent>,
+    pub revisions: Revisions,
+    /// Commits of the change, oldest first; empty when the source does not list them.
+    pub commits: Vec<CommitInfo>,
+    /// Why the commits could not be listed (e.g. the source has no commit list); `None` when
+    /// `commits` is every commit of the change.
+    pub commits_unavailable: Option<String>,
+    /// Review threads already on the change; empty when there are none or the source has none.
+    pub threads: Vec<ReviewThread>,
+    /// Why existing threads could not be loaded (e.g. the source refused access); `None` when
+    /// `threads` is everything the source has.
+    pub threads_unavailable: Option<String>,
+    /// `diff` parsed into files, hunks and lines. Not serialized; rebuilt from `diff`.
+    #[serde(skip)]
+    pub diff_files: Vec<DiffFile>,
 }
 
 impl ReviewInput {
@@ -37,15 +141,122 @@ impl ReviewInput {
         self
     }
 
-    /// Builder-style: set full diff.
+    /// Builder-style: set full diff (parsed into `diff_files`).
     pub fn with_diff(mut self, diff: impl Into<String>) -> Self {
         self.diff = diff.into();
+        self.diff_files = diff_model::parse(&self.diff);
+        self.link_files();
         self
     }
 
-    /// Builder-style: set file list.
+    /// Builder-style: set file list (completed from `diff_files`).
     pub fn with_files(mut self, files: Vec<FileContent>) -> Self {
         self.files = files;
+        self.link_files();
+        self
+    }
+
+    /// Builder-style: set base / head refs and SHAs.
+    pub fn with_revisions(mut self, revisions: Revisions) -> Self {
+        self.revisions = revisions;
+        self
+    }
+
+    /// Builder-style: set commits (oldest first).
+    pub fn with_commits(mut self, commits: Vec<CommitInfo>) -> Self {
+        self.commits = commits;
         self
     }
+
+    /// Builder-style: record why the commits could not be listed.
+    pub fn with_commits_unavailable(mut self, reason: impl Into<String>) -> Self {
+        self.commits_unavailable = Some(reason.into());
+        self
+    }
+
+    /// Builder-style: set existing review threads.
+    pub fn with_threads(mut self, threads: Vec<ReviewThread>) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Builder-style: record why existing threads could not be loaded.
+    pub fn with_threads_unavailable(mut self, reason: impl Into<String>) -> Self {
+        self.threads_unavailable = Some(reason.into());
+        self
+    }
+
+    /// `diff` for the model: binary files and pure renames / copies are reduced to a one-line
+    /// note (see `diff_model::reviewable_diff`).
+    pub fn reviewable_diff(&self) -> String {
+        diff_model::reviewable_diff(&self.diff, &self.diff_files)
+    }
+
+    /// Parsed diff of `path` (new path, or old path of a deleted or renamed file).
+    pub fn diff_file(&self, path: &str) -> Option<&DiffFile> {
+        self.diff_files
+            .iter()
+            .find(|f| f.path() == path)
+            .or_else(|| self.diff_files.iter().find(|f| f.old_path.as_deref() == Some(path)))
+    }
+
+    /// True if a comment on new-side `line` of `path` can be anchored to the diff. Always true
+    /// when there is no parsed diff to check against.
+    pub fn is_commentable(&self, path: &str, line: u32) -> bool {
+        self.diff_files.is_empty() || self.diff_file(path).is_some_and(|f| f.has_new_line(line))
+    }
+
+    /// Sets statuses and missing per-file diffs from `diff_files` (sections of the same path
+    /// are concatenated; files that are not reviewable get no diff); an empty `files` list gets
+    /// one entry per file in the diff.
+    fn link_files(&mut self) {
+        let fill = self.files.is_empty();
+        let mut sections: Vec<(&DiffFile, Option<String>)> = Vec::new();
+        for file in &self.diff_files {
+            let section = file.is_reviewable().then(|| file.section(&self.diff));
+            match sections.iter_mut().find(|(f, _)| f.path() == file.path()) {
+                Some((_, text)) => {
+                    if let Some(section) = section {
+                        text.get_or_insert_with(String::new).push_str(section);
+                    }
+                }
+                None => sections.push((file, section.map(String::from))),
+            }
+        }
+        for (diff_file, section) in sections {
+            let status = Some(diff_file.status());
+            match self.files.iter_mut().find(|f| f.path == diff_file.path()) {
+                Some(file) => {
+                    file.status = status;
+                    match section {
+                        Some(section) => {
+                            file.diff.get_or_insert(section);
+                        }
+                        None => file.diff = None,
+                    }
+                }
+                None if fill => self.files.push(FileContent {
+                    path: diff_file.path().to_string(),
+                    diff: section,
+                    content: None,
+                    status,
+                }),
+                None => {}
+            }
+        }
+    }
+}
+
+impl serde::Serialize for ReviewInput {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ReviewInput::serialize(self, serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ReviewInput {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut input = ReviewInput::deserialize(deserializer)?;
+        input.diff_files = diff_model::parse(&input.diff);
+        Ok(input)
+    }
 }
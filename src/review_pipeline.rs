@@ -1,18 +1,18 @@
-//! Pipeline that runs the agent for one PR/MR. The agent decides which MCP to call (fetch/post).
+//! Pipeline that runs the agent for one review target (PR/MR, commit, compare or local range). The agent decides which MCP to call (fetch/post).
 //!
-//! Depends only on `AgentReviewer`. MCP is used by the agent via its tools.
+//! Depends only on `AgentReviewer` (or `AsyncAgentReviewer` for `run_async`). MCP is used by the agent via its tools.
 
-use crate::agent_reviewer::AgentReviewer;
-use crate::pr_url::PrUrl;
+use crate::agent_reviewer::{AgentReviewer, AsyncAgentReviewer};
 use crate::review_result::ReviewResult;
+use crate::review_target::ReviewTarget;
 
-/// Runs the full review flow for one PR/MR. The agent holds MCP and calls it via tools.
+/// Runs the full review flow for one review target. The agent holds MCP and calls it via tools.
 pub struct ReviewPipeline<A> {
     pub agent: A,
     pub project_path: Option<std::path::PathBuf>,
 }
 
-impl<A: AgentReviewer> ReviewPipeline<A> {
+impl<A> ReviewPipeline<A> {
     /// Creates a pipeline with the given agent reviewer (agent decides MCP calls).
     pub fn new(agent: A) -> Self {
         Self {
@@ -26,11 +26,23 @@ impl<A: AgentReviewer> ReviewPipeline<A> {
         self.project_path = Some(path);
         self
     }
+}
+
+impl<A: AgentReviewer> ReviewPipeline<A> {
+    /// Runs agent review for the target. The agent fetches and posts via MCP tools. Returns the `ReviewResult` on success.
+    pub fn run(&self, target: &ReviewTarget) -> Result<ReviewResult, PipelineError> {
+        self.agent
+            .review(self.project_path.as_deref(), target)
+            .map_err(PipelineError::Review)
+    }
+}
 
-    /// Runs agent review for the PR/MR. The agent fetches and posts via MCP tools. Returns the `ReviewResult` on success.
-    pub fn run(&self, pr: &PrUrl) -> Result<ReviewResult, PipelineError> {
+impl<A: AsyncAgentReviewer> ReviewPipeline<A> {
+    /// Async `run`, for async reviewers (e.g. `LangGraphReviewAgent` inside a tokio service).
+    pub async fn run_async(&self, target: &ReviewTarget) -> Result<ReviewResult, PipelineError> {
         self.agent
-            .review(self.project_path.as_deref(), pr)
+            .review(self.project_path.as_deref(), target)
+            .await
             .map_err(PipelineError::Review)
     }
 }
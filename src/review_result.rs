@@ -1,20 +1,48 @@
-//! Result of agent review: summary and optional line-level comments.
+//! Result of agent review: summary, optional line-level comments and optional verdict, plus the
+//! head commit the review saw.
 //!
 //! Produced by `AgentReviewer::review`; consumed by the publish step (e.g. `McpProvider::post_review`).
 
 /// A single comment attached to a line (file path + line number).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct LineComment {
     pub path: String,
     pub line: u32,
     pub body: String,
 }
 
-/// Full review result: summary text and optional per-line comments.
-#[derive(Debug, Clone, Default)]
+/// Overall verdict of a review (maps to e.g. a Gerrit `Code-Review` vote).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    Comment,
+    RequestChanges,
+}
+
+impl ReviewVerdict {
+    /// Parses the `submit_review` tool value (`approve`, `comment`, `request_changes`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "approve" => Some(ReviewVerdict::Approve),
+            "comment" => Some(ReviewVerdict::Comment),
+            "request_changes" => Some(ReviewVerdict::RequestChanges),
+            _ => None,
+        }
+    }
+}
+
+/// Full review result: summary text, optional per-line comments and optional verdict.
+/// Serializes as `{"summary", "line_comments", "verdict"}` (verdict as in `from_name`), plus
+/// `head_sha` when known.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ReviewResult {
     pub summary: String,
     pub line_comments: Vec<LineComment>,
+    pub verdict: Option<ReviewVerdict>,
+    /// `ReviewInput::revisions.head_sha` of the reviewed input; providers post against it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_sha: Option<String>,
 }
 
 impl ReviewResult {
@@ -34,4 +62,16 @@ impl ReviewResult {
         self.line_comments = line_comments;
         self
     }
+
+    /// Builder-style: set verdict.
+    pub fn with_verdict(mut self, verdict: ReviewVerdict) -> Self {
+        self.verdict = Some(verdict);
+        self
+    }
+
+    /// Builder-style: set the reviewed head commit.
+    pub fn with_head_sha(mut self, head_sha: impl Into<String>) -> Self {
+        self.head_sha = Some(head_sha.into());
+        self
+    }
 }
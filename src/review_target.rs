@@ -0,0 +1,364 @@
+//! What to review: a PR/MR, a single commit, a compare range, a local git range, a patch file
+//! (or stdin), a fixture directory, or a supplied diff.
+//!
+//! `ReviewTarget` is what `McpProvider::fetch`, `AgentReviewer::review` and `ReviewPipeline::run`
+//! take. Commit and compare targets name a hosted repository (`RepoRef`); local ranges name a
+//! checkout on disk; patches and fixtures name a file or directory; a supplied diff is only named, its content comes from the provider. Only PR/MR targets can be posted back by default (`supports_posting`).
+
+use std::path::{Path, PathBuf};
+
+use crate::known_hosts::KnownHosts;
+use crate::pr_url::{check_trailing, encode_path_segment, split_url, Platform, PrUrl, PrUrlParseError};
+
+/// Hosted repository on a forge: the repository part of a `PrUrl`, without the PR/MR id.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RepoRef {
+    pub platform: Platform,
+    /// Web host, e.g. `github.com` (may include a port).
+    pub host: String,
+    /// REST API base URL for `host`, without trailing slash.
+    pub api_base: String,
+    /// Owner or namespace path; same meaning as `PrUrl::owner`.
+    pub owner: String,
+    pub repo: String,
+}
+
+impl RepoRef {
+    /// Full project path: `owner/repo`, or just `repo` if there is no owner.
+    pub fn project_path(&self) -> String {
+        if self.owner.is_empty() {
+            self.repo.clone()
+        } else {
+            format!("{}/{}", self.owner, self.repo)
+        }
+    }
+
+    /// URL-encoded project path, as the GitLab API expects in `/projects/:id`.
+    pub fn encoded_project_path(&self) -> String {
+        encode_path_segment(&self.project_path())
+    }
+
+    /// Web URL of one commit (e.g. `https://github.com/owner/repo/commit/abc123`).
+    pub fn commit_web_url(&self, sha: &str) -> String {
+        let root = self.web_root();
+        match &self.platform {
+            Platform::GitHub | Platform::Gitea => format!("{}/commit/{}", root, sha),
+            Platform::GitLab => format!("{}/-/commit/{}", root, sha),
+            Platform::Bitbucket => format!("{}/commits/{}", root, sha),
+            Platform::Gerrit => format!("{}/+/{}", root, sha),
+            Platform::AzureDevOps { .. } => format!("{}/commit/{}", root, sha),
+        }
+    }
+
+    /// Web URL comparing `base` with `head` (e.g. `https://github.com/owner/repo/compare/main...topic`).
+    pub fn compare_web_url(&self, base: &str, head: &str) -> String {
+        let root = self.web_root();
+        match &self.platform {
+            Platform::GitHub | Platform::Gitea => format!("{}/compare/{}...{}", root, base, head),
+            Platform::GitLab => format!("{}/-/compare/{}...{}", root, base, head),
+            Platform::Bitbucket if self.is_bitbucket_data_center() => format!(
+                "{}/compare/diff?sourceBranch={}&targetBranch={}",
+                root, head, base
+            ),
+            Platform::Bitbucket => format!("{}/branches/compare/{}%0D{}", root, head, base),
+            Platform::Gerrit => format!("{}/+log/{}..{}", root, base, head),
+            Platform::AzureDevOps { .. } => format!(
+                "{}/branchCompare?baseVersion=GB{}&targetVersion=GB{}",
+                root, base, head
+            ),
+        }
+    }
+
+    /// True for Bitbucket Data Center / Server (see `PrUrl::is_bitbucket_data_center`).
+    pub fn is_bitbucket_data_center(&self) -> bool {
+        self.platform == Platform::Bitbucket && self.api_base.contains("/rest/api/")
+    }
+
+    /// Repository home page; commit and compare pages hang off it.
+    fn web_root(&self) -> String {
+        let scheme = if self.api_base.starts_with("http://") { "http" } else { "https" };
+        match &self.platform {
+            Platform::Bitbucket if self.is_bitbucket_data_center() => {
+                let container = match self.owner.strip_prefix('~') {
+                    Some(user) => format!("users/{}", user),
+                    None => format!("projects/{}", self.owner),
+                };
+                format!("{}://{}/{}/repos/{}", scheme, self.host, container, self.repo)
+            }
+            Platform::Gerrit => format!(
+                "{}://{}/plugins/gitiles/{}",
+                scheme,
+                self.host,
+                self.project_path()
+            ),
+            Platform::AzureDevOps {
+                organization,
+                project,
+                repository,
+            } => format!(
+                "{}://{}/{}/{}/_git/{}",
+                scheme, self.host, organization, project, repository
+            ),
+            _ => format!("{}://{}/{}", scheme, self.host, self.project_path()),
+        }
+    }
+}
+
+impl From<&PrUrl> for RepoRef {
+    fn from(pr: &PrUrl) -> Self {
+        Self {
+            platform: pr.platform.clone(),
+            host: pr.host.clone(),
+            api_base: pr.api_base.clone(),
+            owner: pr.owner.clone(),
+            repo: pr.repo.clone(),
+        }
+    }
+}
+
+/// One thing to review.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReviewTarget {
+    /// A pull/merge request (or Gerrit change).
+    PullRequest(PrUrl),
+    /// A single commit in a hosted repository, reviewed against its first parent.
+    Commit { repo: RepoRef, sha: String },
+    /// Changes on `head` since it diverged from `base` (`base...head`) in a hosted repository.
+    Compare {
+        repo: RepoRef,
+        base: String,
+        head: String,
+    },
+    /// Changes on `head` since it diverged from `base` in the checkout at `path`.
+    /// Both `base..head` and `base...head` input mean the merge-base diff, like a PR.
+    LocalRange {
+        path: PathBuf,
+        base: String,
+        head: String,
+    },
+    /// A unified diff, `git format-patch` output or mbox patch series in a file; `-` is stdin.
+    Patch { path: PathBuf },
+    /// An evaluation fixture directory (`title.txt`, `description.md`, `diff.patch`, `files/`).
+    Fixture { dir: PathBuf },
+    /// A diff handed over directly (e.g. by an `mcp-serve` client), named by its title.
+    Diff { title: String },
+}
+
+impl ReviewTarget {
+    /// The PR/MR, if this target is one.
+    pub fn as_pull_request(&self) -> Option<&PrUrl> {
+        match self {
+            ReviewTarget::PullRequest(pr) => Some(pr),
+            _ => None,
+        }
+    }
+
+    /// Hosted repository of the target; `None` for local ranges.
+    pub fn repo(&self) -> Option<RepoRef> {
+        match self {
+            ReviewTarget::PullRequest(pr) => Some(RepoRef::from(pr)),
+            ReviewTarget::Commit { repo, .. } | ReviewTarget::Compare { repo, .. } => Some(repo.clone()),
+            ReviewTarget::LocalRange { .. }
+            | ReviewTarget::Patch { .. }
+            | ReviewTarget::Fixture { .. }
+            | ReviewTarget::Diff { .. } => None,
+        }
+    }
+
+    /// True if a review of this target can be posted back to a forge (PR/MR only).
+    /// Providers may widen this (see `McpProvider::supports_posting`).
+    pub fn supports_posting(&self) -> bool {
+        matches!(self, ReviewTarget::PullRequest(_))
+    }
+
+    /// Short noun for messages: `PR/MR`, `commit`, `compare range`, `local range`, `patch`,
+    /// `fixture` or `diff`.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            ReviewTarget::PullRequest(_) => "PR/MR",
+            ReviewTarget::Commit { .. } => "commit",
+            ReviewTarget::Compare { .. } => "compare range",
+            ReviewTarget::LocalRange { .. } => "local range",
+            ReviewTarget::Patch { .. } => "patch",
+            ReviewTarget::Fixture { .. } => "fixture",
+            ReviewTarget::Diff { .. } => "diff",
+        }
+    }
+
+    /// Parses a PR/MR, commit or compare URL on any host in `hosts`.
+    ///
+    /// Commit URLs: `/{owner}/{repo}/commit/{sha}` (GitHub, Gitea), `/-/commit/{sha}` (GitLab),
+    /// `/commits/{sha}` (Bitbucket), `/_git/{repo}/commit/{sha}` (Azure DevOps).
+    /// Compare URLs: `/{owner}/{repo}/compare/{base}...{head}` (GitHub, Gitea), `/-/compare/...` (GitLab).
+    pub fn parse_with_hosts(url: &str, hosts: &KnownHosts) -> Result<Self, PrUrlParseError> {
+        let pr_err = match PrUrl::parse_with_hosts(url, hosts) {
+            Ok(pr) => return Ok(ReviewTarget::PullRequest(pr)),
+            Err(e @ PrUrlParseError::NotAPullRequest { .. }) => e,
+            Err(e) => return Err(e),
+        };
+        let (known, parts) = split_url(url, hosts)?;
+        let Some((owner, repo, platform, revision)) = parse_repo_revision(&known.platform, &parts)? else {
+            return Err(pr_err);
+        };
+        let repo = RepoRef {
+            platform,
+            host: known.host.clone(),
+            api_base: known.api_base.clone(),
+            owner,
+            repo,
+        };
+        Ok(match revision {
+            Revision::Commit(sha) => ReviewTarget::Commit { repo, sha },
+            Revision::Compare(base, head) => ReviewTarget::Compare { repo, base, head },
+        })
+    }
+
+    /// Resolves CLI input: a URL (see `parse_with_hosts`), a patch file (`.patch`, `.diff`,
+    /// `.mbox`, `.eml`) or `-` for stdin, a local range `base..head` / `base...head` in
+    /// `project_path`, or a PR shorthand (`#123`, `!45`, `owner/repo#123`).
+    pub fn resolve(input: &str, project_path: &Path, hosts: &KnownHosts) -> Result<Self, PrUrlParseError> {
+        let input = input.trim();
+        if input.contains("://") {
+            return Self::parse_with_hosts(input, hosts);
+        }
+        if input == "-" || is_patch_file(input) {
+            return Ok(ReviewTarget::Patch {
+                path: PathBuf::from(input),
+            });
+        }
+        if input.contains("..") && !input.contains(['#', '!']) {
+            let (base, head) = split_range(input)?;
+            return Ok(ReviewTarget::LocalRange {
+                path: project_path.to_path_buf(),
+                base,
+                head,
+            });
+        }
+        PrUrl::resolve(input, project_path, hosts).map(ReviewTarget::PullRequest)
+    }
+}
+
+impl From<PrUrl> for ReviewTarget {
+    fn from(pr: PrUrl) -> Self {
+        ReviewTarget::PullRequest(pr)
+    }
+}
+
+impl std::fmt::Display for ReviewTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewTarget::PullRequest(pr) => write!(f, "{}", pr),
+            ReviewTarget::Commit { repo, sha } => write!(f, "{}", repo.commit_web_url(sha)),
+            ReviewTarget::Compare { repo, base, head } => {
+                write!(f, "{}", repo.compare_web_url(base, head))
+            }
+            ReviewTarget::LocalRange { path, base, head } => {
+                write!(f, "{}...{} in {}", base, head, path.display())
+            }
+            ReviewTarget::Patch { path } if path == Path::new("-") => write!(f, "patch from stdin"),
+            ReviewTarget::Patch { path } => write!(f, "patch {}", path.display()),
+            ReviewTarget::Fixture { dir } => write!(f, "fixture {}", dir.display()),
+            ReviewTarget::Diff { title } => write!(f, "diff \"{}\"", title),
+        }
+    }
+}
+
+/// True for paths with a patch / mail extension (`.patch`, `.diff`, `.mbox`, `.eml`).
+fn is_patch_file(input: &str) -> bool {
+    Path::new(input)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ["patch", "diff", "mbox", "eml"].contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Commit or compare part of a repository URL.
+enum Revision {
+    Commit(String),
+    Compare(String, String),
+}
+
+/// Owner, repo, platform (Azure DevOps scoping filled in) and revision of a commit / compare
+/// URL path; `None` if the path is neither on this platform.
+fn parse_repo_revision(
+    platform: &Platform,
+    parts: &[&str],
+) -> Result<Option<(String, String, Platform, Revision)>, PrUrlParseError> {
+    let (owner, repo, platform, rest) = match platform {
+        Platform::GitHub | Platform::Gitea => match parts {
+            [owner, repo, rest @ ..] => (owner.to_string(), repo.to_string(), platform.clone(), rest),
+            _ => return Ok(None),
+        },
+        Platform::GitLab => match parts.iter().position(|&p| p == "-") {
+            Some(pos) if pos >= 2 => (
+                parts[..pos - 1].join("/"),
+                parts[pos - 1].to_string(),
+                platform.clone(),
+                &parts[pos + 1..],
+            ),
+            _ => return Ok(None),
+        },
+        Platform::Bitbucket => match parts {
+            ["projects", key, "repos", slug, rest @ ..] => (key.to_string(), slug.to_string(), platform.clone(), rest),
+            ["users", user, "repos", slug, rest @ ..] => {
+                (format!("~{}", user), slug.to_string(), platform.clone(), rest)
+            }
+            [workspace, repo, rest @ ..] => (workspace.to_string(), repo.to_string(), platform.clone(), rest),
+            _ => return Ok(None),
+        },
+        Platform::AzureDevOps { .. } => {
+            let (org, project, repo, rest) = match parts {
+                [org, project, "_git", repo, rest @ ..] => (*org, *project, *repo, rest),
+                [org, "_git", repo, rest @ ..] => (*org, *repo, *repo, rest),
+                _ => return Ok(None),
+            };
+            let scoped = Platform::AzureDevOps {
+                organization: org.to_string(),
+                project: project.to_string(),
+                repository: repo.to_string(),
+            };
+            (format!("{}/{}", org, project), repo.to_string(), scoped, rest)
+        }
+        Platform::Gerrit => return Ok(None),
+    };
+    let commit_segment = match &platform {
+        Platform::Bitbucket => "commits",
+        _ => "commit",
+    };
+    let compare_allowed = matches!(platform, Platform::GitHub | Platform::GitLab | Platform::Gitea);
+    let revision = match rest {
+        [seg, sha, trailing @ ..] if *seg == commit_segment => {
+            check_trailing(trailing)?;
+            Revision::Commit(parse_sha(sha)?)
+        }
+        ["compare", range @ ..] if compare_allowed && !range.is_empty() => {
+            let (base, head) = split_range(&range.join("/"))?;
+            Revision::Compare(base, head)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((owner, repo, platform, revision)))
+}
+
+/// Accepts 4–64 hex digits (abbreviated or full SHA-1 / SHA-256).
+fn parse_sha(s: &str) -> Result<String, PrUrlParseError> {
+    if (4..=64).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(s.to_string())
+    } else {
+        Err(PrUrlParseError::InvalidRevision(s.to_string()))
+    }
+}
+
+/// Splits `base...head` (or `base..head`) into non-empty refs without whitespace.
+fn split_range(range: &str) -> Result<(String, String), PrUrlParseError> {
+    let (base, head) = range
+        .split_once("...")
+        .or_else(|| range.split_once(".."))
+        .ok_or_else(|| PrUrlParseError::InvalidRevision(range.to_string()))?;
+    let valid = |r: &str| !r.is_empty() && !r.contains(char::is_whitespace) && !r.starts_with('.');
+    if valid(base) && valid(head) {
+        Ok((base.to_string(), head.to_string()))
+    } else {
+        Err(PrUrlParseError::InvalidRevision(range.to_string()))
+    }
+}
@@ -0,0 +1,137 @@
+//! Line-based unified diff of two texts.
+//!
+//! Used by providers whose API returns file versions but no diff (e.g. Azure DevOps), so the
+//! agent still gets a `git diff`-style `ReviewInput::diff`. LCS over lines with 3 lines of context.
+
+/// Lines of context around each change.
+const CONTEXT: usize = 3;
+/// Above this many LCS cells (old lines × new lines), fall back to replacing the whole file.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Builds a unified diff with a `diff --git` header. `None` paths mean the file was added
+/// (`old_path`) or deleted (`new_path`); differing paths add `rename from` / `rename to` lines.
+/// Equal texts give the header alone, so pure renames and changes the texts do not show (mode
+/// changes) are still listed; empty added / deleted files get a `new file mode` /
+/// `deleted file mode` line (`100644`, as the callers' APIs report no modes).
+pub fn unified_diff(old_path: Option<&str>, new_path: Option<&str>, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+    let a = old_path.or(new_path).unwrap_or_default();
+    let b = new_path.or(old_path).unwrap_or_default();
+    let mut out = format!("diff --git a/{} b/{}\n", a, b);
+    if let (Some(from), Some(to)) = (old_path, new_path) {
+        if from != to {
+            out.push_str(&format!("rename from {}\nrename to {}\n", from, to));
+        }
+    }
+    if ops.iter().all(|(op, _)| *op == Op::Equal) {
+        match (old_path, new_path) {
+            (None, Some(_)) => out.push_str("new file mode 100644\n"),
+            (Some(_), None) => out.push_str("deleted file mode 100644\n"),
+            _ => {}
+        }
+        return out;
+    }
+    out.push_str(&match old_path {
+        Some(p) => format!("--- a/{}\n", p),
+        None => "--- /dev/null\n".to_string(),
+    });
+    out.push_str(&match new_path {
+        Some(p) => format!("+++ b/{}\n", p),
+        None => "+++ /dev/null\n".to_string(),
+    });
+    for (start, end) in hunk_ranges(&ops) {
+        let old_before = ops[..start].iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_before = ops[..start].iter().filter(|(op, _)| *op != Op::Delete).count();
+        let old_count = ops[start..end].iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_count = ops[start..end].iter().filter(|(op, _)| *op != Op::Delete).count();
+        // By convention an empty side reports the line *before* the hunk.
+        let old_start = if old_count == 0 { old_before } else { old_before + 1 };
+        let new_start = if new_count == 0 { new_before } else { new_before + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for (op, line) in &ops[start..end] {
+            let sigil = match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            };
+            out.push(sigil);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Edit script from `old` to `new` (common prefix/suffix trimmed, LCS in between).
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (o, n) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|l| (Op::Equal, *l)).collect();
+    if o.len().saturating_mul(n.len()) > MAX_LCS_CELLS {
+        ops.extend(o.iter().map(|l| (Op::Delete, *l)));
+        ops.extend(n.iter().map(|l| (Op::Insert, *l)));
+    } else {
+        // lcs[i][j] = LCS length of o[i..] and n[j..].
+        let width = n.len() + 1;
+        let mut lcs = vec![0u32; (o.len() + 1) * width];
+        for i in (0..o.len()).rev() {
+            for j in (0..n.len()).rev() {
+                lcs[i * width + j] = if o[i] == n[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < o.len() && j < n.len() {
+            if o[i] == n[j] {
+                ops.push((Op::Equal, o[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                ops.push((Op::Delete, o[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Insert, n[j]));
+                j += 1;
+            }
+        }
+        ops.extend(o[i..].iter().map(|l| (Op::Delete, *l)));
+        ops.extend(n[j..].iter().map(|l| (Op::Insert, *l)));
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (Op::Equal, *l)));
+    ops
+}
+
+/// Half-open op ranges for each hunk: changes plus `CONTEXT` lines, merged when close.
+fn hunk_ranges(ops: &[(Op, &str)]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (idx, _) in ops.iter().enumerate().filter(|(_, (op, _))| *op != Op::Equal) {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + CONTEXT + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+}
@@ -0,0 +1,121 @@
+//! Integration tests for the async provider/reviewer traits and their blocking adapters.
+//!
+//! BDD-style: given a blocking provider that waits on I/O, when it is awaited through
+//! BlockingMcpProvider on a single-threaded runtime, then other tasks keep running; given an async
+//! reviewer, when wrapped in BlockingAgentReviewer, then it runs from synchronous code and
+//! reports an error (instead of panicking) when called inside a runtime.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use quick_review::agent_reviewer::{AgentReviewer, BlockingAgentReviewer, ReviewError};
+use quick_review::mcp_provider::{BlockingMcpProvider, McpError};
+use quick_review::review_input::ReviewInput;
+use quick_review::review_result::ReviewResult;
+use quick_review::review_target::ReviewTarget;
+use quick_review::{AsyncAgentReviewer, AsyncMcpProvider, McpProvider, PrUrl, ReviewPipeline};
+
+/// Blocking provider whose fetch waits for a signal from another task (stands in for network I/O).
+struct WaitingProvider {
+    signal: Mutex<mpsc::Receiver<()>>,
+}
+
+impl McpProvider for WaitingProvider {
+    fn fetch(&self, _target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        self.signal
+            .lock()
+            .unwrap()
+            .recv_timeout(Duration::from_secs(5))
+            .map_err(|_| McpError::new("fetch was never signalled"))?;
+        Ok(ReviewInput::new().with_title("signalled"))
+    }
+
+    fn post_review(&self, _target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        Ok(())
+    }
+}
+
+/// Async reviewer that fetches through an AsyncMcpProvider and summarizes the title.
+struct TitleReviewer {
+    mcp: Arc<dyn AsyncMcpProvider>,
+}
+
+#[async_trait]
+impl AsyncAgentReviewer for TitleReviewer {
+    async fn review(
+        &self,
+        _project_path: Option<&std::path::Path>,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError> {
+        let input = self
+            .mcp
+            .fetch(target)
+            .await
+            .map_err(|e| ReviewError { message: e.message })?;
+        Ok(ReviewResult::new().with_summary(format!("Reviewed: {}", input.title)))
+    }
+}
+
+/// Provider that answers immediately.
+struct FixedProvider;
+
+impl McpProvider for FixedProvider {
+    fn fetch(&self, _target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        Ok(ReviewInput::new().with_title("Fix parser"))
+    }
+
+    fn post_review(&self, _target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        Ok(())
+    }
+}
+
+fn pr() -> ReviewTarget {
+    PrUrl::parse("https://github.com/owner/repo/pull/1").unwrap().into()
+}
+
+fn reviewer(inner: Arc<dyn McpProvider>) -> TitleReviewer {
+    TitleReviewer {
+        mcp: Arc::new(BlockingMcpProvider::new(inner)),
+    }
+}
+
+/// Scenario: A blocking fetch awaited through BlockingMcpProvider does not stall the only runtime
+/// worker: the task that unblocks it still runs, and the async pipeline completes.
+#[test]
+fn blocking_provider_does_not_stall_the_runtime() {
+    let (tx, rx) = mpsc::channel();
+    let provider = Arc::new(WaitingProvider {
+        signal: Mutex::new(rx),
+    });
+    let pipeline = ReviewPipeline::new(reviewer(provider));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let result = runtime.block_on(async {
+        let signaller = tokio::spawn(async move {
+            tokio::task::yield_now().await;
+            tx.send(()).unwrap();
+        });
+        let result = pipeline.run_async(&pr()).await;
+        signaller.await.unwrap();
+        result
+    });
+    assert_eq!(result.unwrap().summary, "Reviewed: signalled");
+}
+
+/// Scenario: BlockingAgentReviewer runs an async reviewer from sync code, and returns an error
+/// when called from inside a tokio runtime.
+#[test]
+fn blocking_reviewer_runs_outside_but_not_inside_a_runtime() {
+    let agent = BlockingAgentReviewer::new(reviewer(Arc::new(FixedProvider))).unwrap();
+    let result = ReviewPipeline::new(agent).run(&pr()).unwrap();
+    assert_eq!(result.summary, "Reviewed: Fix parser");
+
+    let agent = BlockingAgentReviewer::new(reviewer(Arc::new(FixedProvider))).unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let err = runtime.block_on(async { agent.review(None, &pr()) }).unwrap_err();
+    assert!(err.message.contains("inside a tokio runtime"), "{}", err.message);
+}
@@ -0,0 +1,193 @@
+//! Integration tests for Azure DevOps URL parsing and AzureDevOpsProvider against a local HTTP stub.
+//!
+//! BDD-style: given a stub serving iterations, changes, blobs, commits and threads, when we
+//! fetch, then the diff is rebuilt from blob contents (renames and empty files keep their
+//! headers) and commits and threads are read; when we post, then threads carry `threadContext`
+//! positions, unless the source branch moved since the review.
+
+mod common;
+
+use common::{Route, StubServer};
+use quick_review::diff_model::{parse, FileStatus};
+use quick_review::http::HttpClient;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::AzureDevOpsProvider;
+use quick_review::review_result::{LineComment, ReviewResult};
+use quick_review::text_diff::unified_diff;
+use quick_review::McpProvider;
+
+const PR_PATH: &str = "/org/proj/_apis/git/repositories/repo/pullRequests/42";
+const REPO_PATH: &str = "/org/proj/_apis/git/repositories/repo";
+
+fn stub_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::parse("https://dev.azure.com/org/proj/_git/repo/pullrequest/42")
+        .expect("should parse")
+        .with_host(stub.host.clone(), stub.base_url.clone())
+}
+
+/// Scenario: dev.azure.com PR URLs carry organization, project and repository in the platform.
+#[test]
+fn parse_azure_devops_pull_request_url() {
+    let pr = PrUrl::parse("https://dev.azure.com/org/proj/_git/repo/pullrequest/42").expect("should parse");
+    assert_eq!(
+        pr.platform,
+        Platform::AzureDevOps {
+            organization: "org".to_string(),
+            project: "proj".to_string(),
+            repository: "repo".to_string(),
+        }
+    );
+    assert_eq!(pr.owner, "org/proj");
+    assert_eq!(pr.repo, "repo");
+    assert_eq!(pr.id, "42");
+    assert_eq!(pr.to_web_url(), "https://dev.azure.com/org/proj/_git/repo/pullrequest/42");
+    assert_eq!(
+        pr.to_api_url(),
+        "https://dev.azure.com/org/proj/_apis/git/repositories/repo/pullRequests/42"
+    );
+}
+
+/// Scenario: unified_diff produces a hunk with context around a changed line.
+#[test]
+fn unified_diff_marks_changed_line() {
+    let diff = unified_diff(Some("a.txt"), Some("a.txt"), "1\n2\n3\n", "1\ntwo\n3\n");
+    assert!(diff.starts_with("diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n"));
+    assert!(diff.contains("@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n"));
+    assert_eq!(unified_diff(Some("a"), Some("a"), "same\n", "same\n"), "diff --git a/a b/a\n");
+}
+
+/// Scenario: unified_diff keeps renames and empty added files whose texts show no change.
+#[test]
+fn unified_diff_lists_unchanged_renames_and_empty_files() {
+    let diff = unified_diff(Some("old.txt"), Some("new.txt"), "x\n", "x\n");
+    assert_eq!(diff, "diff --git a/old.txt b/new.txt\nrename from old.txt\nrename to new.txt\n");
+    let files = parse(&diff);
+    assert_eq!(files[0].status(), FileStatus::Renamed { from: "old.txt".to_string() });
+
+    let diff = unified_diff(None, Some("empty.txt"), "", "");
+    assert_eq!(diff, "diff --git a/empty.txt b/empty.txt\nnew file mode 100644\n");
+    assert_eq!(parse(&diff)[0].status(), FileStatus::Added);
+}
+
+/// Scenario: Fetch reads the latest iteration's changes and diffs old/new blobs, plus the PR's
+/// commits and threads.
+#[test]
+fn fetch_builds_diff_from_iteration_changes() {
+    let stub = StubServer::start(vec![
+        Route::get(PR_PATH, r#"{"title":"Tune","description":"Tunes things"}"#),
+        Route::get(&format!("{}/iterations", PR_PATH), r#"{"value":[{"id":1},{"id":2}]}"#),
+        Route::get(
+            &format!("{}/iterations/2/changes", PR_PATH),
+            r#"{"changeEntries":[
+                {"changeType":"edit","item":{"path":"/src/app.cs","objectId":"new1","originalObjectId":"old1"}},
+                {"changeType":"add","item":{"path":"/README.md","objectId":"new2"}},
+                {"changeType":"add","item":{"path":"/src","isFolder":true}}
+            ]}"#,
+        ),
+        Route::get(&format!("{}/blobs/old1", REPO_PATH), "a\nb\n"),
+        Route::get(&format!("{}/blobs/new1", REPO_PATH), "a\nB\n"),
+        Route::get(&format!("{}/blobs/new2", REPO_PATH), "hello\n"),
+        Route::get(
+            &format!("{}/commits", PR_PATH),
+            r#"{"value":[
+                {"commitId":"c2","comment":"Add README","author":{"name":"Ann","email":"ann@example.com"}},
+                {"commitId":"c1","comment":"Tune app","author":{"name":"Ann"}}
+            ]}"#,
+        ),
+        Route::get(
+            &format!("{}/threads", PR_PATH),
+            r#"{"value":[
+                {"status":"fixed","threadContext":{"filePath":"/src/app.cs","rightFileStart":{"line":2}},
+                 "pullRequestThreadContext":{"iterationContext":{"secondComparingIteration":1}},
+                 "comments":[{"author":{"displayName":"Ann"},"content":"Why B?","commentType":"text"},
+                             {"author":{"displayName":"Bo"},"content":"Style guide.","commentType":"text"}]},
+                {"status":"active","comments":[{"author":{"displayName":"Cy"},"content":"Ship it?"}]},
+                {"comments":[{"author":{"displayName":"Bot"},"content":"Bo voted 10","commentType":"system"}]},
+                {"isDeleted":true,"comments":[{"content":"gone"}]}
+            ]}"#,
+        ),
+    ]);
+    let provider = AzureDevOpsProvider::new(HttpClient::new());
+    let input = provider.fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Tune");
+    assert_eq!(input.description, "Tunes things");
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["src/app.cs", "README.md"]);
+    assert!(input.diff.contains("-b\n+B\n"));
+    assert!(input.diff.contains("--- /dev/null\n+++ b/README.md\n@@ -0,0 +1,1 @@\n+hello\n"));
+    assert_eq!(input.files[0].content.as_deref(), Some("a\nB\n"));
+    // Commits are listed newest first.
+    let shas: Vec<&str> = input.commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, ["c1", "c2"]);
+    assert_eq!(input.commits[1].author, "Ann <ann@example.com>");
+    assert_eq!(input.commits[1].subject(), "Add README");
+    // System-only and deleted threads are dropped; a thread from iteration 1 is outdated.
+    assert_eq!(input.threads.len(), 2);
+    let thread = &input.threads[0];
+    assert_eq!((thread.path.as_deref(), thread.line), (Some("src/app.cs"), Some(2)));
+    assert!(thread.resolved && thread.outdated);
+    assert_eq!(thread.comments.len(), 2);
+    assert!(!input.threads[1].resolved && input.threads[1].path.is_none());
+    assert!(stub.requests().iter().all(|r| r.path.contains("api-version=7.1")));
+}
+
+/// Scenario: post_review creates a thread per line comment with right-side positions, then a summary thread.
+#[test]
+fn post_review_creates_threads_with_context() {
+    let stub = StubServer::start(vec![
+        Route::get(PR_PATH, r#"{"lastMergeSourceCommit":{"commitId":"feedbeef"}}"#),
+        Route::post(&format!("{}/threads", PR_PATH), "{}"),
+    ]);
+    let provider = AzureDevOpsProvider::new(HttpClient::new());
+    let result = ReviewResult::new()
+        .with_summary("Overall OK.")
+        .with_line_comments(vec![LineComment {
+            path: "src/app.cs".to_string(),
+            line: 2,
+            body: "Why uppercase?".to_string(),
+        }])
+        .with_head_sha("feedbeef");
+    provider.post_review(&stub_pr(&stub).into(), &result).expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 2);
+    let thread = posts[0].json();
+    assert_eq!(thread["comments"][0]["content"], "Why uppercase?");
+    assert_eq!(thread["threadContext"]["filePath"], "/src/app.cs");
+    assert_eq!(thread["threadContext"]["rightFileStart"]["line"], 2);
+    assert_eq!(thread["threadContext"]["rightFileEnd"]["line"], 2);
+    let summary = posts[1].json();
+    assert_eq!(summary["comments"][0]["content"], "Overall OK.");
+    assert!(summary.get("threadContext").is_none());
+}
+
+/// Scenario: head_sha reads the PR's last merge source commit.
+#[test]
+fn head_sha_reads_last_merge_source_commit() {
+    let stub = StubServer::start(vec![Route::get(
+        PR_PATH,
+        r#"{"lastMergeSourceCommit":{"commitId":"feedbeef"}}"#,
+    )]);
+    let provider = AzureDevOpsProvider::new(HttpClient::new());
+    let head = provider.head_sha(&stub_pr(&stub).into()).unwrap();
+    assert_eq!(head.as_deref(), Some("feedbeef"));
+}
+
+/// Scenario: Line comments are not placed when the source branch moved since the review.
+#[test]
+fn post_review_refuses_moved_head() {
+    let stub = StubServer::start(vec![Route::get(
+        PR_PATH,
+        r#"{"lastMergeSourceCommit":{"commitId":"c0ffee"}}"#,
+    )]);
+    let provider = AzureDevOpsProvider::new(HttpClient::new());
+    let result = ReviewResult::new()
+        .with_line_comments(vec![LineComment {
+            path: "src/app.cs".to_string(),
+            line: 2,
+            body: "Why uppercase?".to_string(),
+        }])
+        .with_head_sha("feedbeef");
+    let err = provider.post_review(&stub_pr(&stub).into(), &result).unwrap_err();
+    assert!(err.message.contains("moved from feedbeef to c0ffee"), "{}", err.message);
+    assert!(stub.requests_with_method("POST").is_empty());
+}
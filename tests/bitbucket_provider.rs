@@ -0,0 +1,239 @@
+//! Integration tests for BitbucketProvider against a local HTTP stub (Cloud and Data Center).
+//!
+//! BDD-style: given a stub serving Bitbucket REST responses, when we fetch or post a review,
+//! then ReviewInput is filled from the responses (commits, existing comments as threads) and
+//! comments are posted in Bitbucket's format, unless the PR moved since it was reviewed.
+
+mod common;
+
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::BitbucketProvider;
+use quick_review::review_result::{LineComment, ReviewResult};
+use quick_review::review_target::ReviewTarget;
+use quick_review::McpProvider;
+
+fn cloud_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::Bitbucket, "ws".into(), "repo".into(), "7".into())
+        .with_host(stub.host.clone(), format!("{}/2.0", stub.base_url))
+}
+
+fn dc_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::Bitbucket, "PROJ".into(), "repo".into(), "3".into())
+        .with_host(stub.host.clone(), format!("{}/rest/api/1.0", stub.base_url))
+}
+
+fn review() -> ReviewResult {
+    ReviewResult::new()
+        .with_summary("Looks fine.")
+        .with_line_comments(vec![LineComment {
+            path: "src/a.rs".to_string(),
+            line: 4,
+            body: "Nit.".to_string(),
+        }])
+}
+
+/// Scenario: Cloud fetch fills title, description, diff, paginated diffstat files and commits.
+#[test]
+fn cloud_fetch_fills_review_input() {
+    let stub = StubServer::start(vec![
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7",
+            r#"{"title":"Add a","description":"Adds a.rs"}"#,
+        ),
+        Route::get("/2.0/repositories/ws/repo/pullrequests/7/diff", "diff --git a/src/a.rs b/src/a.rs\n"),
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7/diffstat",
+            r#"{"values":[{"status":"added","old":null,"new":{"path":"src/a.rs"}},{"status":"removed","old":{"path":"old.rs"},"new":null}]}"#,
+        ),
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7/commits",
+            r#"{"values":[{"hash":"b2","message":"Drop old.rs\n","author":{"raw":"Ann <ann@example.com>"}},
+                {"hash":"b1","message":"Add a","author":{"user":{"display_name":"Bo"}}}]}"#,
+        ),
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7/comments",
+            r#"{"values":[
+                {"id":12,"parent":{"id":10},"user":{"display_name":"Ann"},"content":{"raw":"Fixed."}},
+                {"id":10,"user":{"display_name":"Bo"},"content":{"raw":"Off by one?"},
+                 "inline":{"path":"src/a.rs","from":null,"to":3,"outdated":true},
+                 "resolution":{"type":"comment_resolution"}},
+                {"id":11,"user":{"display_name":"Bo"},"content":{"raw":"Gone"},"deleted":true},
+                {"id":13,"user":{"display_name":"Cy"},"content":{"raw":"Nice work."}}]}"#,
+        ),
+    ]);
+    let provider = BitbucketProvider::new(HttpClient::new().with_bearer_token("t0k"));
+    let input = provider.fetch(&cloud_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Add a");
+    assert_eq!(input.description, "Adds a.rs");
+    assert!(input.diff.starts_with("diff --git"));
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["src/a.rs", "old.rs"]);
+    // Commits are listed newest first.
+    let shas: Vec<&str> = input.commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, ["b1", "b2"]);
+    assert_eq!(input.commits[0].author, "Bo");
+    assert_eq!(input.commits[1].author, "Ann <ann@example.com>");
+    assert_eq!(input.commits[1].subject(), "Drop old.rs");
+    // Replies join their parent's thread; deleted comments are dropped.
+    assert_eq!(input.threads.len(), 2);
+    let thread = &input.threads[0];
+    assert_eq!((thread.path.as_deref(), thread.line), (Some("src/a.rs"), Some(3)));
+    assert!(thread.resolved && thread.outdated);
+    assert_eq!(thread.comments[1].body, "Fixed.");
+    assert_eq!(input.threads[1].path, None);
+    assert_eq!(stub.requests()[0].header("Authorization"), Some("Bearer t0k"));
+}
+
+/// Scenario: Cloud post sends an inline comment with path/to, then the summary comment; the
+/// reviewed full SHA matches Cloud's abbreviated source commit.
+#[test]
+fn cloud_post_review_sends_inline_then_summary() {
+    let stub = StubServer::start(vec![
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7",
+            r#"{"source":{"commit":{"hash":"abc123def456"}}}"#,
+        ),
+        Route::post("/2.0/repositories/ws/repo/pullrequests/7/comments", "{}"),
+    ]);
+    let provider = BitbucketProvider::new(HttpClient::new());
+    let result = review().with_head_sha("abc123def4567890abc123def4567890abc123de");
+    provider.post_review(&cloud_pr(&stub).into(), &result).expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 2);
+    let inline = posts[0].json();
+    assert_eq!(inline["inline"]["path"], "src/a.rs");
+    assert_eq!(inline["inline"]["to"], 4);
+    assert_eq!(inline["content"]["raw"], "Nit.");
+    assert_eq!(posts[1].json()["content"]["raw"], "Looks fine.");
+}
+
+/// Scenario: Data Center fetch uses the .diff, /changes and /commits endpoints.
+#[test]
+fn data_center_fetch_fills_review_input() {
+    let stub = StubServer::start(vec![
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3",
+            r#"{"title":"DC change","description":"desc"}"#,
+        ),
+        Route::get("/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3.diff", "diff --git a/x b/x\n"),
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3/changes",
+            r#"{"values":[{"path":{"toString":"x"}}],"isLastPage":true}"#,
+        ),
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3/commits",
+            r#"{"isLastPage":true,"values":[
+                {"id":"d2","message":"Fix x","author":{"name":"ann","emailAddress":"ann@example.com"}},
+                {"id":"d1","message":"Add x","author":{"name":"ann"}}]}"#,
+        ),
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3/activities",
+            r#"{"isLastPage":true,"values":[
+                {"action":"APPROVED"},
+                {"action":"COMMENTED","commentAction":"ADDED",
+                 "comment":{"text":"Why x?","author":{"name":"bo","displayName":"Bo"},"state":"OPEN",
+                    "comments":[{"text":"Because.","author":{"name":"ann"},"comments":[]}]},
+                 "commentAnchor":{"path":"x","line":2,"orphaned":true}},
+                {"action":"COMMENTED","commentAction":"ADDED",
+                 "comment":{"text":"First!","author":{"displayName":"Cy"},"threadResolved":true}}]}"#,
+        ),
+    ]);
+    let provider = BitbucketProvider::new(HttpClient::new());
+    let input = provider.fetch(&dc_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "DC change");
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].path, "x");
+    let shas: Vec<&str> = input.commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, ["d1", "d2"]);
+    assert_eq!(input.commits[1].author, "ann <ann@example.com>");
+    // Activities are newest first; threads are listed oldest first.
+    assert_eq!(input.threads.len(), 2);
+    assert!(input.threads[0].resolved);
+    assert_eq!(input.threads[0].author(), "Cy");
+    let thread = &input.threads[1];
+    assert_eq!((thread.path.as_deref(), thread.line), (Some("x"), Some(2)));
+    assert!(thread.outdated && !thread.resolved);
+    let authors: Vec<&str> = thread.comments.iter().map(|c| c.author.as_str()).collect();
+    assert_eq!(authors, ["Bo", "ann"]);
+}
+
+/// Scenario: Data Center post anchors comments to new file lines, as CONTEXT or ADDED lines
+/// according to the PR diff.
+#[test]
+fn data_center_post_review_uses_anchor() {
+    let stub = StubServer::start(vec![
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3",
+            r#"{"fromRef":{"latestCommit":"0123456789abcdef"}}"#,
+        ),
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3.diff",
+            "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -4,1 +4,2 @@\n keep\n+new\n",
+        ),
+        Route::post("/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3/comments", "{}"),
+    ]);
+    let mut result = review().with_head_sha("0123456789abcdef");
+    result.line_comments.push(LineComment {
+        path: "src/a.rs".to_string(),
+        line: 5,
+        body: "Name?".to_string(),
+    });
+    let provider = BitbucketProvider::new(HttpClient::new());
+    provider.post_review(&dc_pr(&stub).into(), &result).expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 3);
+    let anchored = posts[0].json();
+    assert_eq!(anchored["text"], "Nit.");
+    assert_eq!(anchored["anchor"]["path"], "src/a.rs");
+    assert_eq!(anchored["anchor"]["line"], 4);
+    assert_eq!(anchored["anchor"]["lineType"], "CONTEXT");
+    assert_eq!(posts[1].json()["anchor"]["lineType"], "ADDED");
+    assert_eq!(posts[2].json()["text"], "Looks fine.");
+}
+
+/// Scenario: Line comments are not placed when the PR's source commit moved since the review.
+#[test]
+fn post_review_refuses_moved_head() {
+    let stub = StubServer::start(vec![Route::get(
+        "/2.0/repositories/ws/repo/pullrequests/7",
+        r#"{"source":{"commit":{"hash":"fff000fff000"}}}"#,
+    )]);
+    let provider = BitbucketProvider::new(HttpClient::new());
+    let result = review().with_head_sha("abc123def456");
+    let err = provider.post_review(&cloud_pr(&stub).into(), &result).unwrap_err();
+    assert!(err.message.contains("moved from abc123def456 to fff000fff000"), "{}", err.message);
+    assert!(stub.requests_with_method("POST").is_empty());
+}
+
+/// Scenario: HTTP errors surface as McpError with the status code.
+#[test]
+fn fetch_http_error_is_reported() {
+    let stub = StubServer::start(vec![]);
+    let provider = BitbucketProvider::new(HttpClient::new());
+    let err = provider.fetch(&cloud_pr(&stub).into()).unwrap_err();
+    assert!(err.message.contains("404"), "message: {}", err.message);
+}
+
+/// Scenario: head_sha reads the source commit on Cloud and Data Center; other targets have none.
+#[test]
+fn head_sha_reads_source_commit() {
+    let stub = StubServer::start(vec![
+        Route::get(
+            "/2.0/repositories/ws/repo/pullrequests/7",
+            r#"{"source":{"commit":{"hash":"abc123def456"}}}"#,
+        ),
+        Route::get(
+            "/rest/api/1.0/projects/PROJ/repos/repo/pull-requests/3",
+            r#"{"fromRef":{"latestCommit":"0123456789abcdef"}}"#,
+        ),
+    ]);
+    let provider = BitbucketProvider::new(HttpClient::new());
+    let cloud = provider.head_sha(&cloud_pr(&stub).into()).unwrap();
+    assert_eq!(cloud.as_deref(), Some("abc123def456"));
+    let dc = provider.head_sha(&dc_pr(&stub).into()).unwrap();
+    assert_eq!(dc.as_deref(), Some("0123456789abcdef"));
+    let patch = ReviewTarget::Patch { path: "fix.patch".into() };
+    assert_eq!(provider.head_sha(&patch).unwrap(), None);
+}
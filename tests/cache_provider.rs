@@ -0,0 +1,176 @@
+//! Integration tests for CachingProvider (persistent fetch cache) and the cache CLI flags.
+//!
+//! BDD-style: given a counting provider whose head SHA can move, when the same PR is fetched
+//! repeatedly through the cache, then only the first fetch (and fetches after a new head SHA,
+//! expiry, refresh or invalidation) reach the provider; review threads are never served from
+//! disk.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use quick_review::cli::{split_flags, CliFlags};
+use quick_review::mcp_provider::McpError;
+use quick_review::providers::CachingProvider;
+use quick_review::review_input::{ReviewInput, ReviewThread};
+use quick_review::review_result::ReviewResult;
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, PrUrl};
+
+/// Returns the fetch count as the title; head SHA is settable.
+#[derive(Default)]
+struct CountingProvider {
+    fetches: AtomicUsize,
+    head: Mutex<Option<String>>,
+}
+
+impl CountingProvider {
+    fn set_head(&self, sha: &str) {
+        *self.head.lock().unwrap() = Some(sha.to_string());
+    }
+
+    fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+}
+
+impl McpProvider for CountingProvider {
+    fn fetch(&self, _target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        let n = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+        let thread = ReviewThread {
+            path: Some("src/lib.rs".to_string()),
+            line: Some(n as u32),
+            ..ReviewThread::default()
+        };
+        Ok(ReviewInput::new().with_title(format!("fetch {}", n)).with_threads(vec![thread]))
+    }
+
+    fn post_review(&self, _target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        Ok(())
+    }
+
+    fn head_sha(&self, _target: &ReviewTarget) -> Result<Option<String>, McpError> {
+        Ok(self.head.lock().unwrap().clone())
+    }
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("quick-review-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn pr() -> ReviewTarget {
+    PrUrl::parse("https://github.com/owner/repo/pull/3").unwrap().into()
+}
+
+/// Scenario: A second provider instance (a later run) is served from disk for the same head SHA;
+/// a new head SHA misses.
+#[test]
+fn cache_hits_until_head_sha_changes() {
+    let dir = cache_dir("sha");
+    let inner = Arc::new(CountingProvider::default());
+    inner.set_head("aaa111");
+    let first = CachingProvider::new(inner.clone(), &dir);
+    assert_eq!(first.fetch(&pr()).unwrap().title, "fetch 1");
+    let second_run = CachingProvider::new(inner.clone(), &dir);
+    assert_eq!(second_run.fetch(&pr()).unwrap().title, "fetch 1");
+    assert_eq!(inner.fetches(), 1);
+
+    inner.set_head("bbb222");
+    assert_eq!(second_run.fetch(&pr()).unwrap().title, "fetch 2");
+    assert_eq!(second_run.fetch(&pr()).unwrap().title, "fetch 2");
+    assert_eq!(inner.fetches(), 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Scenario: Expired entries, refresh mode and invalidation all refetch.
+#[test]
+fn ttl_refresh_and_invalidate_refetch() {
+    let dir = cache_dir("ttl");
+    let inner = Arc::new(CountingProvider::default());
+    inner.set_head("aaa111");
+    let expired = CachingProvider::new(inner.clone(), &dir).with_ttl(Some(Duration::ZERO));
+    expired.fetch(&pr()).unwrap();
+    expired.fetch(&pr()).unwrap();
+    assert_eq!(inner.fetches(), 2);
+
+    let cache = CachingProvider::new(inner.clone(), &dir).with_ttl(None);
+    assert_eq!(cache.fetch(&pr()).unwrap().title, "fetch 2");
+    let refresh = CachingProvider::new(inner.clone(), &dir).with_refresh(true);
+    assert_eq!(refresh.fetch(&pr()).unwrap().title, "fetch 3");
+    assert_eq!(cache.fetch(&pr()).unwrap().title, "fetch 3");
+
+    cache.invalidate(&pr()).unwrap();
+    assert_eq!(cache.fetch(&pr()).unwrap().title, "fetch 4");
+    cache.clear().unwrap();
+    assert!(!dir.exists());
+}
+
+/// Scenario: A fresh fetch keeps its threads; a cache hit has none and says why.
+#[test]
+fn threads_are_not_served_from_cache() {
+    let dir = cache_dir("threads");
+    let inner = Arc::new(CountingProvider::default());
+    inner.set_head("aaa111");
+    let cache = CachingProvider::new(inner.clone(), &dir);
+    let fresh = cache.fetch(&pr()).unwrap();
+    assert_eq!(fresh.threads.len(), 1);
+    assert_eq!(fresh.threads_unavailable, None);
+
+    let cached = cache.fetch(&pr()).unwrap();
+    assert_eq!(inner.fetches(), 1);
+    assert!(cached.threads.is_empty());
+    let note = cached.threads_unavailable.expect("cache hit should explain missing threads");
+    assert!(note.contains("fetch cache"), "{}", note);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Scenario: Targets whose head SHA the provider cannot tell always reach the provider.
+#[test]
+fn targets_without_head_sha_are_not_cached() {
+    let dir = cache_dir("nohead");
+    let inner = Arc::new(CountingProvider::default());
+    let cache = CachingProvider::new(inner.clone(), &dir);
+    assert_eq!(cache.fetch(&pr()).unwrap().title, "fetch 1");
+    assert_eq!(cache.fetch(&pr()).unwrap().title, "fetch 2");
+    assert!(!dir.exists());
+}
+
+/// Scenario: Local ranges are passed through without touching the cache directory.
+#[test]
+fn local_ranges_are_not_cached() {
+    let dir = cache_dir("local");
+    let inner = Arc::new(CountingProvider::default());
+    let cache = CachingProvider::new(inner.clone(), &dir);
+    let target = ReviewTarget::LocalRange {
+        path: ".".into(),
+        base: "main".to_string(),
+        head: "feature".to_string(),
+    };
+    cache.fetch(&target).unwrap();
+    cache.fetch(&target).unwrap();
+    assert_eq!(inner.fetches(), 2);
+    assert!(!dir.exists());
+}
+
+/// Scenario: `--no-cache` and `--refresh-cache` are removed from the args wherever they appear.
+#[test]
+fn split_flags_extracts_cache_switches() {
+    let args: Vec<String> = ["quick-review", "--no-cache", "#3", "--refresh-cache"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let (rest, flags) = split_flags(&args);
+    assert_eq!(rest, ["quick-review", "#3"]);
+    assert_eq!(
+        flags,
+        CliFlags {
+            no_cache: true,
+            refresh_cache: true,
+            commit_hygiene: false,
+        }
+    );
+    assert_eq!(split_flags(&rest).1, CliFlags::default());
+}
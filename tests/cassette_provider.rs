@@ -0,0 +1,144 @@
+//! Integration tests for RecordingProvider and ReplayProvider.
+//!
+//! BDD-style: given a provider wrapped for recording, when fetches and posts go through it, then
+//! the cassette file holds them; when the cassette is replayed, then the same inputs and errors
+//! come back without the original provider (errors with their kind and requested wait), and
+//! posts are captured for comparison; a cassette that cannot be written does not fail the call.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use quick_review::mcp_provider::{McpError, McpErrorKind};
+use quick_review::providers::{Cassette, RecordingProvider, ReplayProvider};
+use quick_review::review_input::{FileContent, ReviewInput};
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, PrUrl};
+
+/// Serves PR 1; PR 5 is rate limited; every other target fails.
+struct FixedProvider;
+
+impl McpProvider for FixedProvider {
+    fn fetch(&self, target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        if target.to_string().ends_with("/pull/1") {
+            Ok(ReviewInput::new()
+                .with_title("Add cache")
+                .with_diff("diff --git a/c.rs b/c.rs\n")
+                .with_files(vec![FileContent {
+                    path: "c.rs".to_string(),
+                    diff: None,
+                    content: Some("fn c() {}\n".to_string()),
+                    status: None,
+                }]))
+        } else if target.to_string().ends_with("/pull/5") {
+            Err(McpError::new("429 Too Many Requests")
+                .with_kind(McpErrorKind::RateLimited)
+                .with_retry_after(Some(Duration::from_secs(30))))
+        } else {
+            Err(McpError::new("404 Not Found"))
+        }
+    }
+
+    fn post_review(&self, _target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        Ok(())
+    }
+}
+
+fn pr(n: &str) -> ReviewTarget {
+    PrUrl::parse(&format!("https://github.com/owner/repo/pull/{}", n))
+        .unwrap()
+        .into()
+}
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("quick-review-cassette-{}-{}.json", name, std::process::id()))
+}
+
+fn review() -> ReviewResult {
+    ReviewResult::new()
+        .with_summary("Looks good.")
+        .with_line_comments(vec![LineComment {
+            path: "c.rs".to_string(),
+            line: 1,
+            body: "Document this.".to_string(),
+        }])
+        .with_verdict(ReviewVerdict::Approve)
+}
+
+/// Scenario: Fetches (including failures) and posts are written to the cassette file in order.
+#[test]
+fn recording_writes_every_interaction() {
+    let path = cassette_path("record");
+    let recorder = RecordingProvider::new(Arc::new(FixedProvider), &path);
+    let input = recorder.fetch(&pr("1")).unwrap();
+    assert_eq!(input.title, "Add cache");
+    assert!(recorder.fetch(&pr("2")).is_err());
+    recorder.post_review(&pr("1"), &review()).unwrap();
+
+    let cassette = Cassette::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(cassette, recorder.cassette());
+    assert_eq!(cassette.interactions.len(), 3);
+    assert_eq!(cassette.posts(), [(&pr("1"), &review())]);
+}
+
+/// Scenario: Replay answers recorded fetches and errors, and refuses unrecorded targets.
+#[test]
+fn replay_serves_recorded_fetches() {
+    let path = cassette_path("replay");
+    let recorder = RecordingProvider::new(Arc::new(FixedProvider), &path);
+    let recorded = recorder.fetch(&pr("1")).unwrap();
+    let _ = recorder.fetch(&pr("2"));
+
+    let replay = ReplayProvider::from_file(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(replay.fetch(&pr("1")).unwrap(), recorded);
+    assert_eq!(replay.fetch(&pr("1")).unwrap(), recorded);
+    assert_eq!(replay.fetch(&pr("2")).unwrap_err().message, "404 Not Found");
+    assert!(replay.fetch(&pr("3")).unwrap_err().message.contains("no fetch"));
+}
+
+/// Scenario: A recorded error replays with its kind and requested wait, not just its message.
+#[test]
+fn replay_keeps_error_kind_and_retry_after() {
+    let path = cassette_path("error-kind");
+    let recorder = RecordingProvider::new(Arc::new(FixedProvider), &path);
+    let _ = recorder.fetch(&pr("5"));
+
+    let replay = ReplayProvider::from_file(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let err = replay.fetch(&pr("5")).unwrap_err();
+    assert_eq!(err.message, "429 Too Many Requests");
+    assert_eq!(err.kind, McpErrorKind::RateLimited);
+    assert_eq!(err.retry_after, Some(Duration::from_secs(30)));
+}
+
+/// Scenario: A cassette that cannot be written leaves the fetch successful and is reported by
+/// `save_error()`.
+#[test]
+fn cassette_write_failure_does_not_fail_fetch() {
+    let path = cassette_path("unwritable").join("missing-dir").join("cassette.json");
+    let recorder = RecordingProvider::new(Arc::new(FixedProvider), &path);
+    assert_eq!(recorder.fetch(&pr("1")).unwrap().title, "Add cache");
+    let err = recorder.save_error().expect("write failure should be reported");
+    assert!(err.contains("writing cassette"), "{}", err);
+    assert_eq!(recorder.cassette().interactions.len(), 1);
+}
+
+/// Scenario: Posts during replay are captured so they can be compared with the recorded ones.
+#[test]
+fn replay_captures_posts() {
+    let path = cassette_path("posts");
+    let recorder = RecordingProvider::new(Arc::new(FixedProvider), &path);
+    recorder.post_review(&pr("1"), &review()).unwrap();
+
+    let replay = ReplayProvider::from_file(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(replay.supports_posting(&pr("1")));
+    replay.post_review(&pr("1"), &review().with_summary("Changed.")).unwrap();
+    let posted = replay.posted();
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0].1.summary, "Changed.");
+    assert_eq!(replay.cassette().posts()[0].1.summary, "Looks good.");
+}
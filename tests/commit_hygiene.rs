@@ -0,0 +1,107 @@
+//! Integration tests for commit-history checks and the get_commits tool.
+//!
+//! BDD-style: given commits with WIP, fixup, vague or body-less messages or changes spread over
+//! many directories, when we check them,
+//! then each gets the expected flags; when the agent calls get_commits, then it sees every
+//! commit with its author, stats, files and flags, or why the commits could not be listed.
+
+use langgraph::ToolSource;
+use quick_review::commit_hygiene::{check, CommitIssue};
+use quick_review::diff_model::DiffStat;
+use quick_review::review_agent::{ReviewToolSource, TOOL_GET_COMMITS};
+use quick_review::review_input::{CommitInfo, ReviewInput};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+fn commit(message: &str, changed: u32) -> CommitInfo {
+    CommitInfo {
+        sha: "0123456789abcdef".to_string(),
+        author: "Ann <ann@example.com>".to_string(),
+        message: message.to_string(),
+        stats: Some(DiffStat {
+            additions: changed,
+            deletions: 0,
+        }),
+        files: vec!["src/lib.rs".to_string()],
+    }
+}
+
+/// Scenario: WIP, fixup, empty and vague messages and large body-less commits are flagged;
+/// descriptive messages are not.
+#[test]
+fn check_flags_commit_message_problems() {
+    assert_eq!(check(&commit("WIP: parser", 5)), vec![CommitIssue::Wip]);
+    assert_eq!(check(&commit("Parser [wip]", 5)), vec![CommitIssue::Wip]);
+    assert_eq!(check(&commit("fixup! Add parser", 5)), vec![CommitIssue::Fixup]);
+    assert_eq!(check(&commit("", 5)), vec![CommitIssue::EmptyMessage]);
+    assert_eq!(check(&commit("fix", 5)), vec![CommitIssue::VagueSubject]);
+    assert_eq!(check(&commit("chore: updates.", 5)), vec![CommitIssue::VagueSubject]);
+    assert_eq!(
+        check(&commit("Rewrite the parser", 500)),
+        vec![CommitIssue::MissingBody]
+    );
+
+    assert!(check(&commit("Wipe caches on logout", 5)).is_empty());
+    assert!(check(&commit("fix(parser): handle empty input", 5)).is_empty());
+    assert!(check(&commit("Rewrite the parser\n\nThe old one was quadratic.", 500)).is_empty());
+}
+
+/// Scenario: A large commit touching four top-level directories is flagged as mixed; a small
+/// one, or a large one within fewer directories, is not.
+#[test]
+fn check_flags_large_commits_across_directories() {
+    let files = ["api/routes.rs", "web/app.ts", "docs/guide.md", "infra/main.tf", "README.md"];
+    let mut mixed = commit("Add billing\n\nNew endpoint, page, docs and bucket.", 500);
+    mixed.files = files.iter().map(|f| f.to_string()).collect();
+    assert_eq!(check(&mixed), vec![CommitIssue::MixedChanges]);
+
+    let mut small = mixed.clone();
+    small.stats = Some(DiffStat {
+        additions: 20,
+        deletions: 0,
+    });
+    assert!(check(&small).is_empty());
+
+    let mut focused = mixed.clone();
+    focused.files = vec!["src/a.rs".into(), "src/b/c.rs".into(), "tests/a.rs".into()];
+    assert!(check(&focused).is_empty());
+}
+
+/// Scenario: get_commits lists each commit with short SHA, subject, author, stats, files, flags
+/// and body.
+#[tokio::test]
+async fn get_commits_lists_commits_with_flags() {
+    let input = ReviewInput::new().with_commits(vec![
+        commit("Add parser\n\nHandles nested lists.", 40),
+        commit("fixup! Add parser", 2),
+    ]);
+    let tools = ReviewToolSource::new(input, Arc::new(RwLock::new(None)));
+
+    let text = tools
+        .call_tool(TOOL_GET_COMMITS, serde_json::json!({}))
+        .await
+        .unwrap()
+        .text;
+    assert!(text.starts_with("2 commit(s), oldest first:"));
+    assert!(text.contains("0123456789ab Add parser\n  Author: Ann <ann@example.com>"));
+    assert!(text.contains("  Stats: +40 -0\n  Files: src/lib.rs\n\n  Handles nested lists."));
+    assert!(text.contains("0123456789ab fixup! Add parser"));
+    assert!(text.contains("  Flags: fixup/squash commit"));
+
+    let empty = ReviewToolSource::new(ReviewInput::new(), Arc::new(RwLock::new(None)));
+    let text = empty.call_tool(TOOL_GET_COMMITS, serde_json::json!({})).await.unwrap().text;
+    assert_eq!(text, "No commits listed for this change.");
+}
+
+/// Scenario: get_commits says the history was not checked when the source could not list
+/// commits, instead of reporting an empty (clean) history.
+#[tokio::test]
+async fn get_commits_reports_unavailable_commits() {
+    let input = ReviewInput::new().with_commits_unavailable("no commit tool");
+    let tools = ReviewToolSource::new(input, Arc::new(RwLock::new(None)));
+    let text = tools.call_tool(TOOL_GET_COMMITS, serde_json::json!({})).await.unwrap().text;
+    assert_eq!(
+        text,
+        "The commits could not be listed (no commit tool); the commit history was not checked."
+    );
+}
@@ -0,0 +1,178 @@
+//! Shared test support: a tiny local HTTP stub server for provider tests.
+//!
+//! Serves canned responses by method + path (query string ignored unless the route's path has
+//! one, e.g. to serve a specific page), optionally narrowed by the
+//! request's `Accept` header, and records every request so tests can assert on what the provider sent.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// One canned response.
+#[derive(Clone)]
+pub struct Route {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+    /// If set, the request's `Accept` header must contain this.
+    pub accept: Option<String>,
+}
+
+impl Route {
+    pub fn get(path: &str, body: impl Into<String>) -> Self {
+        Self::new("GET", path, 200, body)
+    }
+
+    pub fn post(path: &str, body: impl Into<String>) -> Self {
+        Self::new("POST", path, 200, body)
+    }
+
+    pub fn new(method: &str, path: &str, status: u16, body: impl Into<String>) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            body: body.into(),
+            headers: Vec::new(),
+            accept: None,
+        }
+    }
+
+    /// Only match requests whose `Accept` header contains `accept` (e.g. a diff media type).
+    pub fn with_accept(mut self, accept: &str) -> Self {
+        self.accept = Some(accept.to_string());
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A request the stub received.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path including query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body should be JSON")
+    }
+}
+
+/// Local HTTP server on 127.0.0.1 with an ephemeral port. Runs until the test process exits.
+pub struct StubServer {
+    pub base_url: String,
+    pub host: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubServer {
+    /// Starts the server. Routes are matched in order; each route may match many times.
+    /// Unmatched requests get a 404.
+    pub fn start(routes: Vec<Route>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+        let host = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let Some(req) = read_request(&mut stream) else { continue };
+                let bare_path = req.path.split('?').next().unwrap_or("").to_string();
+                let route = routes
+                    .iter()
+                    .find(|r| {
+                        r.method == req.method
+                            && (r.path == bare_path || r.path == req.path)
+                            && r.accept.as_deref().is_none_or(|a| {
+                                req.header("Accept").is_some_and(|h| h.contains(a))
+                            })
+                    })
+                    .cloned()
+                    .unwrap_or_else(|| Route::new(&req.method, &bare_path, 404, "{\"message\":\"not found\"}"));
+                recorded.lock().unwrap().push(req);
+                let mut head = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    route.status,
+                    route.body.len()
+                );
+                for (name, value) in &route.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(route.body.as_bytes());
+            }
+        });
+        Self {
+            base_url: format!("http://{}", host),
+            host,
+            requests,
+        }
+    }
+
+    /// All requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Requests with the given method, in order.
+    pub fn requests_with_method(&self, method: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.method == method)
+            .collect()
+    }
+}
+
+fn read_request(stream: &mut std::net::TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut headers = Vec::new();
+    let mut content_length = 0usize;
+    loop {
+        let mut h = String::new();
+        reader.read_line(&mut h).ok()?;
+        let h = h.trim_end();
+        if h.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = h.split_once(':') {
+            let (name, value) = (name.trim().to_string(), value.trim().to_string());
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            }
+            headers.push((name, value));
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
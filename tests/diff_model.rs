@@ -0,0 +1,190 @@
+//! Integration tests for the parsed diff model (`diff_model::parse`, `ReviewInput::diff_files`).
+//!
+//! BDD-style: given `git diff` and plain `diff -u` output, when it is parsed (directly or via
+//! `ReviewInput::with_diff`), then files, hunks and lines carry the right paths, line numbers
+//! and kinds, per-file diffs and change statuses are filled in, commentable lines can be looked
+//! up, and binary files and pure renames are kept out of the diff shown to the model.
+
+use quick_review::diff_model::{parse, FileStatus, LineKind};
+use quick_review::review_agent::review_input_to_user_message;
+use quick_review::review_input::{FileContent, ReviewInput};
+
+const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,4 @@ mod a;
+ fn a() {}
+--- removed with dashes
++++ added with pluses
+ fn b() {}
+
+@@ -10,2 +10,3 @@ fn c() {
+ let x = 1;
++let y = 2;
+ let z = 3;
+\\ No newline at end of file
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hello
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+index 4444444..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/old name.md b/docs/new name.md
+similarity index 100%
+rename from old name.md
+rename to docs/new name.md
+diff --git \"a/caf\\303\\251.png\" \"b/caf\\303\\251.png\"
+index 5555555..6666666 100644
+Binary files \"a/caf\\303\\251.png\" and \"b/caf\\303\\251.png\" differ
+";
+
+/// Scenario: Hunks get old/new line numbers and kinds; `---` / `+++` inside a hunk are content;
+/// added, deleted, renamed and quoted binary files get the right paths.
+#[test]
+fn parses_git_diff_files_hunks_and_lines() {
+    let files = parse(DIFF);
+    let paths: Vec<(Option<&str>, Option<&str>)> = files
+        .iter()
+        .map(|f| (f.old_path.as_deref(), f.new_path.as_deref()))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            (Some("src/lib.rs"), Some("src/lib.rs")),
+            (None, Some("new.txt")),
+            (Some("gone.txt"), None),
+            (Some("old name.md"), Some("docs/new name.md")),
+            (Some("café.png"), Some("café.png")),
+        ]
+    );
+
+    let lib = &files[0];
+    assert_eq!(lib.hunks.len(), 2);
+    assert_eq!(lib.hunks[0].section, "mod a;");
+    let first: Vec<(LineKind, Option<u32>, Option<u32>, &str)> = lib.hunks[0]
+        .lines
+        .iter()
+        .map(|l| (l.kind, l.old_line, l.new_line, l.text.as_str()))
+        .collect();
+    assert_eq!(
+        first,
+        [
+            (LineKind::Context, Some(1), Some(1), "fn a() {}"),
+            (LineKind::Removed, Some(2), None, "-- removed with dashes"),
+            (LineKind::Added, None, Some(2), "++ added with pluses"),
+            (LineKind::Context, Some(3), Some(3), "fn b() {}"),
+            (LineKind::Context, Some(4), Some(4), ""),
+        ]
+    );
+    assert_eq!(lib.added_lines(), [2, 11]);
+    assert!(lib.has_new_line(12) && !lib.has_new_line(13));
+    assert!(lib
+        .section(DIFF)
+        .ends_with("\\ No newline at end of file\n"));
+
+    assert_eq!(files[1].added_lines(), [1]);
+    assert_eq!(files[2].path(), "gone.txt");
+    assert!(files[3].hunks.is_empty() && files[4].hunks.is_empty());
+}
+
+/// Scenario: Plain `diff -u` output with timestamps, surrounded by other text, is parsed too.
+#[test]
+fn parses_plain_unified_diff() {
+    let text = "Some mail text\n--- a.c\t2024-01-01 00:00:00\n+++ a.c\t2024-01-02 00:00:00\n@@ -3 +3,2 @@\n-x\n+y\n+z\n-- \n2.43.0\n";
+    let files = parse(text);
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path(), "a.c");
+    assert_eq!(files[0].added_lines(), [3, 4]);
+    assert!(
+        files[0].section(text).starts_with("--- a.c") && files[0].section(text).ends_with("+z\n")
+    );
+}
+
+/// Scenario: ReviewInput parses its diff, fills missing per-file diffs (or the whole file list),
+/// reports commentable lines, and reparses after deserialization.
+#[test]
+fn review_input_links_files_and_lines() {
+    let input = ReviewInput::new().with_diff(DIFF);
+    assert_eq!(input.files.len(), 5);
+    assert!(input.files[1]
+        .diff
+        .as_deref()
+        .unwrap()
+        .starts_with("diff --git a/new.txt"));
+    assert!(input.is_commentable("src/lib.rs", 11));
+    assert!(!input.is_commentable("src/lib.rs", 7));
+    assert!(!input.is_commentable("README.md", 1));
+    assert_eq!(
+        input.diff_file("old name.md").unwrap().path(),
+        "docs/new name.md"
+    );
+
+    let listed = ReviewInput::new()
+        .with_diff(DIFF)
+        .with_files(vec![FileContent {
+            path: "new.txt".to_string(),
+            ..FileContent::default()
+        }]);
+    assert_eq!(listed.files.len(), 1);
+    assert!(listed.files[0].diff.is_some());
+
+    let json = serde_json::to_string(&input).unwrap();
+    assert!(!json.contains("diff_files"));
+    let back: ReviewInput = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, input);
+    assert!(ReviewInput::new().is_commentable("any.rs", 1));
+}
+
+/// Scenario: Files get a change status; binary files and pure renames lose their diff text in
+/// `files` and in the diff shown to the model, and statuses appear in the user message.
+#[test]
+fn file_status_and_reviewable_diff() {
+    let mode = "diff --git a/run.sh b/run.sh\nold mode 100644\nnew mode 100755\n";
+    let copy = "diff --git a/a.rs b/b.rs\nsimilarity index 90%\ncopy from a.rs\ncopy to b.rs\n--- a/a.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-x\n+y\n";
+    let input = ReviewInput::new().with_diff(format!("{}{}{}", DIFF, mode, copy));
+    let statuses: Vec<String> = input
+        .files
+        .iter()
+        .map(|f| format!("{} {}", f.path, f.status.as_ref().unwrap()))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            "src/lib.rs modified",
+            "new.txt added",
+            "gone.txt deleted",
+            "docs/new name.md renamed from old name.md",
+            "café.png binary",
+            "run.sh mode change",
+            "b.rs copied from a.rs",
+        ]
+    );
+    assert_eq!(
+        input.files[3].status,
+        Some(FileStatus::Renamed {
+            from: "old name.md".to_string()
+        })
+    );
+    assert!(input.files[3].diff.is_none() && input.files[4].diff.is_none());
+    assert!(input.files[5].diff.is_some() && input.files[6].diff.is_some());
+
+    let diff = input.reviewable_diff();
+    assert!(diff.contains("docs/new name.md: renamed from old name.md, diff omitted\n"));
+    assert!(diff.contains("café.png: binary, diff omitted\ndiff --git a/run.sh"));
+    assert!(!diff.contains("Binary files") && !diff.contains("rename from"));
+    assert!(diff.contains("+let y = 2;") && diff.contains("copy from a.rs"));
+
+    let msg = review_input_to_user_message(&input);
+    assert!(msg.contains("café.png (binary)"));
+    assert!(msg.contains("new.txt (added, diff)"));
+    assert!(!msg.contains("Binary files"));
+}
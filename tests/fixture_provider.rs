@@ -0,0 +1,119 @@
+//! Integration tests for FixtureProvider.
+//!
+//! BDD-style: given a dataset of fixture directories (`title.txt`, `description.md`,
+//! `diff.patch`, `files/`), when each fixture is fetched or run through `ReviewPipeline`, then
+//! `ReviewInput` mirrors the files on disk and the review lands in the fixture's `result.json`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use quick_review::agent_reviewer::{AgentReviewer, ReviewError};
+use quick_review::providers::FixtureProvider;
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, ReviewPipeline};
+
+const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-pub fn f() {}
++pub fn f() { unsafe { std::hint::unreachable_unchecked() } }
+";
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Dataset with `ub` (full fixture) and `bare` (diff only); `notes` has no diff and is skipped.
+fn dataset(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("quick-review-fixtures-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    write(&root.join("ub/title.txt"), "Make f faster\n");
+    write(&root.join("ub/description.md"), "Skips the body.\n");
+    write(&root.join("ub/diff.patch"), DIFF);
+    write(&root.join("ub/files/src/lib.rs"), "pub fn f() { unsafe { std::hint::unreachable_unchecked() } }\n");
+    write(&root.join("ub/files/src/main.rs"), "fn main() { lib::f() }\n");
+    write(&root.join("bare/diff.patch"), DIFF);
+    write(&root.join("notes/README.md"), "not a fixture\n");
+    root
+}
+
+/// Reviewer stand-in: flags the first changed file and posts through the provider.
+struct FlagFirstFile {
+    mcp: Arc<dyn McpProvider>,
+}
+
+impl AgentReviewer for FlagFirstFile {
+    fn review(
+        &self,
+        _project_path: Option<&Path>,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError> {
+        let input = self.mcp.fetch(target).map_err(|e| ReviewError { message: e.message })?;
+        let result = ReviewResult::new()
+            .with_summary(input.title.clone())
+            .with_line_comments(vec![LineComment {
+                path: input.files[0].path.clone(),
+                line: 1,
+                body: "Undefined behaviour".to_string(),
+            }])
+            .with_verdict(ReviewVerdict::RequestChanges);
+        self.mcp
+            .post_review(target, &result)
+            .map_err(|e| ReviewError { message: e.message })?;
+        Ok(result)
+    }
+}
+
+/// Scenario: A fixture's title, description, diff and files come from its directory; files
+/// outside the diff are context without a diff; a fixture without title.txt uses its directory name.
+#[test]
+fn fetch_reads_fixture_directory() {
+    let root = dataset("fetch");
+    let provider = FixtureProvider::new();
+    let input = provider.fetch(&ReviewTarget::Fixture { dir: root.join("ub") }).unwrap();
+    assert_eq!(input.title, "Make f faster");
+    assert_eq!(input.description, "Skips the body.");
+    assert_eq!(input.diff, DIFF);
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["src/lib.rs", "src/main.rs"]);
+    assert_eq!(input.files[0].diff.as_deref(), Some(DIFF));
+    assert!(input.files[0].content.as_deref().unwrap().contains("unreachable_unchecked"));
+    assert_eq!(input.files[1].diff, None);
+
+    let bare = provider.fetch(&ReviewTarget::Fixture { dir: root.join("bare") }).unwrap();
+    assert_eq!(bare.title, "bare");
+    assert_eq!(bare.files[0].content, None);
+
+    assert!(provider.fetch(&ReviewTarget::Fixture { dir: root.join("notes") }).is_err());
+}
+
+/// Scenario: Every fixture of a dataset runs through ReviewPipeline and gets a result.json.
+#[test]
+fn dataset_runs_through_pipeline() {
+    let root = dataset("pipeline");
+    let targets = FixtureProvider::dataset(&root).unwrap();
+    assert_eq!(
+        targets,
+        [
+            ReviewTarget::Fixture { dir: root.join("bare") },
+            ReviewTarget::Fixture { dir: root.join("ub") },
+        ]
+    );
+    assert_eq!(FixtureProvider::dataset(&root.join("ub")).unwrap().len(), 1);
+
+    let pipeline = ReviewPipeline::new(FlagFirstFile {
+        mcp: Arc::new(FixtureProvider::new()),
+    });
+    for target in &targets {
+        pipeline.run(target).unwrap();
+    }
+    let written: ReviewResult =
+        serde_json::from_str(&std::fs::read_to_string(root.join("ub/result.json")).unwrap()).unwrap();
+    assert_eq!(written.summary, "Make f faster");
+    assert_eq!(written.line_comments[0].path, "src/lib.rs");
+    assert_eq!(written.verdict, Some(ReviewVerdict::RequestChanges));
+    assert!(root.join("bare/result.json").is_file());
+}
@@ -0,0 +1,47 @@
+//! Integration tests for ForgeProvider routing.
+//!
+//! BDD-style: given a ForgeProvider from the environment, when we fetch targets of different
+//! platforms and kinds, then each request reaches that platform's API (or the local source), and
+//! targets no provider can fetch are errors.
+
+mod common;
+
+use common::{Route, StubServer};
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::ForgeProvider;
+use quick_review::review_target::ReviewTarget;
+use quick_review::McpProvider;
+
+/// Scenario: A Gitea PR is fetched through the Gitea API, a patch file from disk.
+#[test]
+fn routes_targets_to_their_provider() {
+    let stub = StubServer::start(vec![
+        Route::get("/api/v1/repos/owner/repo/pulls/5", r#"{"title":"Fix","body":""}"#),
+        Route::get("/api/v1/repos/owner/repo/pulls/5.diff", "diff --git a/m.go b/m.go\n"),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/files", "[]"),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/commits", "[]"),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/reviews", "[]"),
+    ]);
+    let pr = PrUrl::new(Platform::Gitea, "owner".into(), "repo".into(), "5".into())
+        .with_host(stub.host.clone(), format!("{}/api/v1", stub.base_url));
+    let forge = ForgeProvider::from_env().expect("forge provider");
+
+    let input = forge.fetch(&pr.into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Fix");
+    assert!(stub.requests().iter().all(|r| r.path.starts_with("/api/v1/repos/owner/repo/pulls/5")));
+
+    let path = std::env::temp_dir().join(format!("quick-review-forge-{}.diff", std::process::id()));
+    std::fs::write(&path, "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+    let input = forge.fetch(&ReviewTarget::Patch { path: path.clone() }).expect("patch fetch");
+    assert_eq!(input.files[0].path, "x");
+    let _ = std::fs::remove_file(path);
+}
+
+/// Scenario: A supplied diff has no source to fetch from.
+#[test]
+fn supplied_diffs_are_not_fetchable() {
+    let forge = ForgeProvider::from_env().expect("forge provider");
+    let target = ReviewTarget::Diff { title: "local".to_string() };
+    assert!(forge.fetch(&target).is_err());
+    assert!(!forge.supports_posting(&target));
+}
@@ -0,0 +1,168 @@
+//! Integration tests for Gerrit change URLs and GerritProvider against a local HTTP stub.
+//!
+//! BDD-style: given a stub serving `)]}'`-prefixed change detail and a base64 patch, when we
+//! fetch, then the selected patchset's diff and the change's comment threads fill ReviewInput;
+//! when we post, then the review
+//! carries inline comments and a Code-Review vote derived from the verdict.
+
+mod common;
+
+use base64::Engine;
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::known_hosts::KnownHosts;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::GerritProvider;
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::McpProvider;
+
+const CHANGE_PATH: &str = "/a/changes/platform%2Fbuild~12345";
+
+fn stub_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::Gerrit, "platform".into(), "build".into(), "12345".into())
+        .with_host(stub.host.clone(), stub.base_url.clone())
+}
+
+fn detail() -> String {
+    r#")]}'
+{"subject":"Speed up build","current_revision":"bbb","revisions":{
+  "aaa":{"_number":1,"commit":{"message":"Speed up build\n\nOld body.\n"}},
+  "bbb":{"_number":2,"commit":{"message":"Speed up build\n\nCache outputs.\n\nChange-Id: I123\n"}}
+}}"#
+    .to_string()
+}
+
+/// Scenario: Change URLs with nested projects and optional patchsets parse and round-trip.
+#[test]
+fn parse_gerrit_change_urls() {
+    let hosts = KnownHosts::new().with_host("android-review.example", Platform::Gerrit);
+    let pr = PrUrl::parse_with_hosts("https://android-review.example/c/platform/build/+/12345/3", &hosts)
+        .expect("should parse");
+    assert_eq!(pr.platform, Platform::Gerrit);
+    assert_eq!(pr.project_path(), "platform/build");
+    assert_eq!(pr.id, "12345");
+    assert_eq!(pr.patchset, Some(3));
+    assert_eq!(pr.to_web_url(), "https://android-review.example/c/platform/build/+/12345/3");
+    assert_eq!(pr.to_api_url(), "https://android-review.example/changes/platform%2Fbuild~12345");
+
+    let latest = PrUrl::parse_with_hosts("https://android-review.example/c/tools/+/7", &hosts).expect("should parse");
+    assert_eq!(latest.project_path(), "tools");
+    assert_eq!(latest.patchset, None);
+}
+
+/// Scenario: Fetch uses the current revision's patch, skips magic files and groups published
+/// comments into threads.
+#[test]
+fn fetch_current_patchset() {
+    let patch = "From bbb\nSubject: Speed up build\n---\ndiff --git a/Makefile b/Makefile\n";
+    let encoded = base64::engine::general_purpose::STANDARD.encode(patch);
+    let stub = StubServer::start(vec![
+        Route::get(CHANGE_PATH, detail()),
+        Route::get(&format!("{}/revisions/bbb/patch", CHANGE_PATH), encoded),
+        Route::get(
+            &format!("{}/revisions/bbb/files", CHANGE_PATH),
+            ")]}'\n{\"/COMMIT_MSG\":{},\"Makefile\":{\"lines_inserted\":2}}",
+        ),
+        Route::get(
+            &format!("{}/comments", CHANGE_PATH),
+            r#")]}'
+{"Makefile":[
+  {"id":"c2","in_reply_to":"c1","patch_set":1,"line":3,"author":{"username":"bob"},
+   "message":"Done","unresolved":false,"updated":"2026-01-02 10:00:00.000000000"},
+  {"id":"c1","patch_set":1,"line":3,"author":{"name":"Ann"},
+   "message":"Quote $(CC)","unresolved":true,"updated":"2026-01-01 10:00:00.000000000"}],
+ "/PATCHSET_LEVEL":[
+  {"id":"c3","patch_set":2,"author":{"username":"bob"},"message":"Needs a test","unresolved":true,
+   "updated":"2026-01-03 10:00:00.000000000"}]}"#,
+        ),
+    ]);
+    let provider = GerritProvider::new(HttpClient::new().with_basic_auth("bot", "pw"), true);
+    let input = provider.fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Speed up build");
+    assert!(input.description.starts_with("Cache outputs."));
+    assert_eq!(input.diff, patch);
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].path, "Makefile");
+
+    assert_eq!(input.threads.len(), 2);
+    let general = input.threads.iter().find(|t| t.path.is_none()).expect("patch set thread");
+    assert!(!general.resolved && !general.outdated);
+    assert_eq!(general.author(), "bob");
+    let makefile = input.threads.iter().find(|t| t.path.is_some()).expect("file thread");
+    assert_eq!(makefile.path.as_deref(), Some("Makefile"));
+    assert_eq!(makefile.line, Some(3));
+    assert!(makefile.resolved && makefile.outdated);
+    let bodies: Vec<&str> = makefile.comments.iter().map(|c| c.body.as_str()).collect();
+    assert_eq!(bodies, ["Quote $(CC)", "Done"]);
+    assert_eq!(makefile.author(), "Ann");
+}
+
+/// Scenario: An explicit patchset selects that revision.
+#[test]
+fn fetch_explicit_patchset() {
+    let encoded = base64::engine::general_purpose::STANDARD.encode("old patch");
+    let stub = StubServer::start(vec![
+        Route::get(CHANGE_PATH, detail()),
+        Route::get(&format!("{}/revisions/aaa/patch", CHANGE_PATH), encoded),
+        Route::get(&format!("{}/revisions/aaa/files", CHANGE_PATH), ")]}'\n{}"),
+        Route::get(&format!("{}/comments", CHANGE_PATH), ")]}'\n{}"),
+    ]);
+    let provider = GerritProvider::new(HttpClient::new(), true);
+    let input = provider.fetch(&stub_pr(&stub).with_patchset(1).into()).expect("fetch should succeed");
+    assert_eq!(input.diff, "old patch");
+    assert_eq!(input.description, "Old body.");
+}
+
+/// Scenario: post_review sends inline comments grouped by path and a -1 vote for request_changes.
+#[test]
+fn post_review_with_vote() {
+    let stub = StubServer::start(vec![Route::post(
+        &format!("{}/revisions/current/review", CHANGE_PATH),
+        ")]}'\n{\"labels\":{\"Code-Review\":-1}}",
+    )]);
+    let provider = GerritProvider::new(HttpClient::new(), true);
+    let result = ReviewResult::new()
+        .with_summary("Needs work.")
+        .with_line_comments(vec![LineComment {
+            path: "Makefile".to_string(),
+            line: 3,
+            body: "Quote this.".to_string(),
+        }])
+        .with_verdict(ReviewVerdict::RequestChanges);
+    provider.post_review(&stub_pr(&stub).into(), &result).expect("post should succeed");
+    let body = stub.requests_with_method("POST")[0].json();
+    assert_eq!(body["message"], "Needs work.");
+    assert_eq!(body["labels"]["Code-Review"], -1);
+    assert_eq!(body["comments"]["Makefile"][0]["line"], 3);
+    assert_eq!(body["comments"]["Makefile"][0]["message"], "Quote this.");
+}
+
+/// Scenario: Without a verdict no label is sent, so existing votes are left alone.
+#[test]
+fn post_review_without_verdict_omits_labels() {
+    let stub = StubServer::start(vec![Route::post(
+        &format!("{}/revisions/2/review", CHANGE_PATH),
+        ")]}'\n{}",
+    )]);
+    let provider = GerritProvider::new(HttpClient::new(), true);
+    let result = ReviewResult::new().with_summary("FYI.");
+    provider
+        .post_review(&stub_pr(&stub).with_patchset(2).into(), &result)
+        .expect("post should succeed");
+    let body = stub.requests_with_method("POST")[0].json();
+    assert!(body.get("labels").is_none());
+}
+
+/// Scenario: head_sha reads the current revision, or the commit of an explicit patchset.
+#[test]
+fn head_sha_reads_selected_revision() {
+    let stub = StubServer::start(vec![
+        Route::get(CHANGE_PATH, ")]}'\n{\"current_revision\":\"bbb\"}"),
+        Route::get(&format!("{}/revisions/1/commit", CHANGE_PATH), ")]}'\n{\"commit\":\"aaa\"}"),
+    ]);
+    let provider = GerritProvider::new(HttpClient::new(), true);
+    let current = provider.head_sha(&stub_pr(&stub).into()).unwrap();
+    assert_eq!(current.as_deref(), Some("bbb"));
+    let first = provider.head_sha(&stub_pr(&stub).with_patchset(1).into()).unwrap();
+    assert_eq!(first.as_deref(), Some("aaa"));
+}
@@ -0,0 +1,175 @@
+//! Integration tests for GiteaProvider against a local HTTP stub.
+//!
+//! BDD-style: given a stub serving Gitea pulls/files/reviews responses, when we fetch or post,
+//! then ReviewInput is filled (with commits and existing review threads) and a single review with
+//! inline comments (and an event from the verdict) is submitted.
+
+mod common;
+
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::known_hosts::KnownHosts;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::GiteaProvider;
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::review_target::ReviewTarget;
+use quick_review::McpProvider;
+
+fn stub_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::Gitea, "owner".into(), "repo".into(), "5".into())
+        .with_host(stub.host.clone(), format!("{}/api/v1", stub.base_url))
+}
+
+/// Scenario: Gitea/Forgejo PR URLs parse on configured hosts.
+#[test]
+fn parse_gitea_pull_url_on_configured_host() {
+    let hosts = KnownHosts::new().with_spec("forgejo=git.corp.example").expect("spec");
+    let pr = PrUrl::parse_with_hosts("https://git.corp.example/owner/repo/pulls/5", &hosts)
+        .expect("should parse");
+    assert_eq!(pr.platform, Platform::Gitea);
+    assert_eq!(pr.id, "5");
+    assert_eq!(pr.to_api_url(), "https://git.corp.example/api/v1/repos/owner/repo/pulls/5");
+    assert_eq!(pr.to_web_url(), "https://git.corp.example/owner/repo/pulls/5");
+}
+
+/// Scenario: Fetch maps title/body, the .diff endpoint, the files list and the commits into
+/// ReviewInput.
+#[test]
+fn fetch_fills_review_input() {
+    let stub = StubServer::start(vec![
+        Route::get("/api/v1/repos/owner/repo/pulls/5", r#"{"title":"Fix","body":"Fixes it"}"#),
+        Route::get("/api/v1/repos/owner/repo/pulls/5.diff", "diff --git a/m.go b/m.go\n"),
+        Route::get(
+            "/api/v1/repos/owner/repo/pulls/5/files",
+            r#"[{"filename":"m.go","status":"changed"}]"#,
+        ),
+        Route::get(
+            "/api/v1/repos/owner/repo/pulls/5/commits",
+            r#"[{"sha":"g2","commit":{"message":"Handle err","author":{"name":"Ann","email":"ann@example.com"}},
+                 "stats":{"additions":2,"deletions":1},"files":[{"filename":"m.go"}]},
+                {"sha":"g1","commit":{"message":"Fix","author":{"name":"Ann"}}}]"#,
+        ),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/reviews", "[]"),
+    ]);
+    let provider = GiteaProvider::new(HttpClient::new().with_header("Authorization", "token abc"));
+    let input = provider.fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Fix");
+    assert_eq!(input.description, "Fixes it");
+    assert!(input.diff.contains("m.go"));
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].path, "m.go");
+    // Gitea lists commits newest first.
+    let shas: Vec<&str> = input.commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, ["g1", "g2"]);
+    assert_eq!(input.commits[1].author, "Ann <ann@example.com>");
+    assert_eq!(input.commits[1].stats.map(|s| s.additions), Some(2));
+    assert_eq!(input.commits[1].files, ["m.go"]);
+    assert_eq!(stub.requests()[0].header("Authorization"), Some("token abc"));
+}
+
+/// Scenario: Review bodies become general threads; line comments of all reviews on the same line
+/// form one thread, resolved once a comment has a resolver; pending reviews are skipped.
+#[test]
+fn fetch_reads_review_threads() {
+    let stub = StubServer::start(vec![
+        Route::get("/api/v1/repos/owner/repo/pulls/5", r#"{"title":"Fix","body":""}"#),
+        Route::get("/api/v1/repos/owner/repo/pulls/5.diff", "diff --git a/m.go b/m.go\n"),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/files", "[]"),
+        Route::get("/api/v1/repos/owner/repo/pulls/5/commits", "[]"),
+        Route::get(
+            "/api/v1/repos/owner/repo/pulls/5/reviews",
+            r#"[{"id":1,"user":{"login":"ann"},"state":"REQUEST_CHANGES","body":"Needs tests.",
+                 "comments_count":1,"stale":true},
+                {"id":2,"user":{"login":"bo"},"state":"COMMENT","body":"","comments_count":1},
+                {"id":3,"user":{"login":"cy"},"state":"PENDING","body":"Draft","comments_count":1}]"#,
+        ),
+        Route::get(
+            "/api/v1/repos/owner/repo/pulls/5/reviews/1/comments",
+            r#"[{"user":{"login":"ann"},"path":"m.go","position":3,"body":"Check err."}]"#,
+        ),
+        Route::get(
+            "/api/v1/repos/owner/repo/pulls/5/reviews/2/comments",
+            r#"[{"user":{"login":"bo"},"path":"m.go","position":3,"body":"Done.",
+                 "resolver":{"login":"ann"}}]"#,
+        ),
+    ]);
+    let input = GiteaProvider::new(HttpClient::new())
+        .fetch(&stub_pr(&stub).into())
+        .expect("fetch should succeed");
+    assert_eq!(input.threads.len(), 2);
+    assert_eq!(input.threads[0].path, None);
+    assert_eq!(input.threads[0].comments[0].body, "Needs tests.");
+    let thread = &input.threads[1];
+    assert_eq!((thread.path.as_deref(), thread.line), (Some("m.go"), Some(3)));
+    assert!(thread.resolved && thread.outdated);
+    let authors: Vec<&str> = thread.comments.iter().map(|c| c.author.as_str()).collect();
+    assert_eq!(authors, ["ann", "bo"]);
+    assert!(stub.requests().iter().all(|r| !r.path.contains("/reviews/3")));
+}
+
+/// Scenario: post_review submits one COMMENT review with new_position inline comments.
+#[test]
+fn post_review_submits_single_review() {
+    let stub = StubServer::start(vec![Route::post("/api/v1/repos/owner/repo/pulls/5/reviews", "{}")]);
+    let provider = GiteaProvider::new(HttpClient::new());
+    let result = ReviewResult::new()
+        .with_summary("Two nits.")
+        .with_line_comments(vec![LineComment {
+            path: "m.go".to_string(),
+            line: 12,
+            body: "Handle err.".to_string(),
+        }]);
+    provider.post_review(&stub_pr(&stub).into(), &result).expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 1);
+    let body = posts[0].json();
+    assert_eq!(body["event"], "COMMENT");
+    assert_eq!(body["body"], "Two nits.");
+    assert_eq!(body["comments"][0]["path"], "m.go");
+    assert_eq!(body["comments"][0]["new_position"], 12);
+}
+
+/// Scenario: The verdict becomes the review event: APPROVED, REQUEST_CHANGES or COMMENT.
+#[test]
+fn post_review_maps_verdict_to_event() {
+    let cases = [
+        (Some(ReviewVerdict::Approve), "APPROVED"),
+        (Some(ReviewVerdict::RequestChanges), "REQUEST_CHANGES"),
+        (Some(ReviewVerdict::Comment), "COMMENT"),
+    ];
+    for (verdict, event) in cases {
+        let stub =
+            StubServer::start(vec![Route::post("/api/v1/repos/owner/repo/pulls/5/reviews", "{}")]);
+        let mut result = ReviewResult::new().with_summary("Done.");
+        result.verdict = verdict;
+        GiteaProvider::new(HttpClient::new())
+            .post_review(&stub_pr(&stub).into(), &result)
+            .expect("post should succeed");
+        assert_eq!(stub.requests_with_method("POST")[0].json()["event"], event);
+    }
+}
+
+/// Scenario: A GitHub URL is rejected by the Gitea provider.
+#[test]
+fn fetch_rejects_other_platforms() {
+    let provider = GiteaProvider::new(HttpClient::new());
+    let pr = PrUrl::new(Platform::GitHub, "o".into(), "r".into(), "1".into());
+    assert!(provider.fetch(&pr.into()).is_err());
+}
+
+/// Scenario: head_sha reads the PR's head commit; other targets have none.
+#[test]
+fn head_sha_reads_pr_head() {
+    let stub = StubServer::start(vec![Route::get(
+        "/api/v1/repos/owner/repo/pulls/5",
+        r#"{"head":{"sha":"abc123def456"}}"#,
+    )]);
+    let provider = GiteaProvider::new(HttpClient::new());
+    let head = provider.head_sha(&stub_pr(&stub).into()).unwrap();
+    assert_eq!(head.as_deref(), Some("abc123def456"));
+    let patch = ReviewTarget::Patch { path: "fix.patch".into() };
+    assert_eq!(provider.head_sha(&patch).unwrap(), None);
+    let github = PrUrl::new(Platform::GitHub, "o".into(), "r".into(), "1".into());
+    assert_eq!(provider.head_sha(&github.into()).unwrap(), None);
+    assert_eq!(stub.requests().len(), 1);
+}
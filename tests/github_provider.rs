@@ -0,0 +1,218 @@
+//! Integration tests for GitHubProvider against a local HTTP stub.
+//!
+//! BDD-style: given a stub serving a PR's JSON, diff, paginated files and GraphQL review threads,
+//! when we fetch, then ReviewInput carries title, body, diff, per-file patches and existing
+//! threads (or why they could not be loaded); when we post, then one review with inline comments
+//! is submitted to `pulls/{n}/reviews`.
+
+mod common;
+
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::GitHubProvider;
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::review_target::{RepoRef, ReviewTarget};
+use quick_review::McpProvider;
+
+const PR_PATH: &str = "/repos/owner/repo/pulls/9";
+const DIFF: &str = "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1 @@\n-old\n+new\n";
+
+fn stub_pr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::GitHub, "owner".into(), "repo".into(), "9".into())
+        .with_host(stub.host.clone(), stub.base_url.clone())
+}
+
+fn provider() -> GitHubProvider {
+    GitHubProvider::new(HttpClient::new().with_bearer_token("test-token"))
+}
+
+/// Scenario: Fetch combines PR JSON, the diff media type and files with patches.
+#[test]
+fn fetch_reads_pr_diff_and_files() {
+    let files: Vec<String> = (0..100)
+        .map(|i| format!(r#"{{"filename":"gen/f{}.rs","patch":"@@ -0,0 +1 @@\n+x"}}"#, i))
+        .collect();
+    let stub = StubServer::start(vec![
+        Route::get(PR_PATH, DIFF).with_accept("application/vnd.github.diff"),
+        Route::get(
+            PR_PATH,
+            r#"{"title":"Fix main","body":"Replaces old with new.",
+                "base":{"ref":"main","sha":"b45e"},"head":{"ref":"fix","sha":"4ead"}}"#,
+        ),
+        Route::get(
+            &format!("{}/files?per_page=100&page=1", PR_PATH),
+            format!("[{}]", files.join(",")),
+        ),
+        Route::get(&format!("{}/files", PR_PATH), r#"[{"filename":"README.md"}]"#),
+        Route::get(
+            &format!("{}/commits", PR_PATH),
+            r#"[{"sha":"4ead","commit":{"author":{"name":"Ann","email":"ann@example.com"},
+                "message":"Fix main\n\nReplaces old with new."}}]"#,
+        ),
+        Route::get(
+            "/repos/owner/repo/commits/4ead",
+            r#"{"sha":"4ead","stats":{"additions":1,"deletions":1},
+                "files":[{"filename":"src/main.rs"}]}"#,
+        ),
+        Route::post(
+            "/graphql",
+            r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{
+                "pageInfo":{"hasNextPage":false,"endCursor":"Y3Vy"},
+                "nodes":[{"path":"src/main.rs","line":null,"originalLine":1,
+                    "isResolved":true,"isOutdated":true,
+                    "comments":{"nodes":[{"author":{"login":"octocat"},"body":"Why new?"},
+                                         {"author":{"login":"ann"},"body":"See the issue."}]}}]}}}}}"#,
+        ),
+    ]);
+    let input = provider().fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Fix main");
+    assert_eq!(input.description, "Replaces old with new.");
+    assert_eq!(input.diff, DIFF);
+    assert_eq!(input.files[0].path, "gen/f0.rs");
+    assert_eq!(input.files[0].diff.as_deref(), Some("@@ -0,0 +1 @@\n+x"));
+    // A full first page means a second page is requested.
+    assert_eq!(input.files.len(), 101);
+    assert_eq!(input.files[100].path, "README.md");
+    assert_eq!(input.revisions.base_ref.as_deref(), Some("main"));
+    assert_eq!(input.revisions.base_sha.as_deref(), Some("b45e"));
+    assert_eq!(input.revisions.head_ref.as_deref(), Some("fix"));
+    assert_eq!(input.revisions.head_sha.as_deref(), Some("4ead"));
+    assert_eq!(input.revisions.merge_base, None);
+    assert_eq!(input.commits.len(), 1);
+    assert_eq!(input.commits[0].sha, "4ead");
+    assert_eq!(input.commits[0].author, "Ann <ann@example.com>");
+    assert_eq!(input.commits[0].body(), "Replaces old with new.");
+    // Stats and files come from the single-commit endpoint.
+    let stats = input.commits[0].stats.expect("commit stats");
+    assert_eq!((stats.additions, stats.deletions), (1, 1));
+    assert_eq!(input.commits[0].files, ["src/main.rs"]);
+    let thread = &input.threads[0];
+    assert_eq!(thread.path.as_deref(), Some("src/main.rs"));
+    assert_eq!(thread.line, Some(1));
+    assert!(thread.resolved && thread.outdated);
+    assert_eq!(thread.author(), "octocat");
+    assert_eq!(thread.comments[1].body, "See the issue.");
+    let query = stub.requests_with_method("POST")[0].json();
+    assert_eq!(query["variables"]["number"], 9);
+    assert_eq!(query["variables"]["owner"], "owner");
+    let first = &stub.requests()[0];
+    assert_eq!(first.header("Authorization"), Some("Bearer test-token"));
+    assert_eq!(first.header("X-GitHub-Api-Version"), Some("2022-11-28"));
+}
+
+/// Scenario: A null PR body becomes an empty description; a short files page ends pagination;
+/// a GraphQL auth failure (anonymous access) leaves the threads empty and says why.
+#[test]
+fn fetch_handles_null_body_and_binary_files() {
+    let stub = StubServer::start(vec![
+        Route::get(PR_PATH, "").with_accept("application/vnd.github.diff"),
+        Route::get(PR_PATH, r#"{"title":"Logo","body":null}"#),
+        Route::get(&format!("{}/files", PR_PATH), r#"[{"filename":"logo.png"}]"#),
+        Route::get(&format!("{}/commits", PR_PATH), "[]"),
+        Route::new("POST", "/graphql", 401, r#"{"message":"Requires authentication"}"#),
+    ]);
+    let input = provider().fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.description, "");
+    assert!(input.threads.is_empty());
+    let reason = input.threads_unavailable.as_deref().expect("threads_unavailable");
+    assert!(reason.contains("refused access"), "{}", reason);
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].diff, None);
+    assert_eq!(stub.requests().len(), 5);
+}
+
+/// Scenario: GraphQL errors served with HTTP 200 (a token missing a scope, a PR GraphQL cannot
+/// see) leave the threads empty and say why instead of failing the fetch.
+#[test]
+fn fetch_reports_graphql_errors_as_unavailable_threads() {
+    for (kind, expected) in [("FORBIDDEN", "refused access"), ("NOT_FOUND", "could not load")] {
+        let errors = format!(
+            r#"{{"data":{{"repository":null}},"errors":[{{"type":"{}","message":"Nope"}}]}}"#,
+            kind
+        );
+        let stub = StubServer::start(vec![
+            Route::get(PR_PATH, "").with_accept("application/vnd.github.diff"),
+            Route::get(PR_PATH, r#"{"title":"Logo","body":null}"#),
+            Route::get(&format!("{}/files", PR_PATH), "[]"),
+            Route::get(&format!("{}/commits", PR_PATH), "[]"),
+            Route::post("/graphql", &errors),
+        ]);
+        let input = provider().fetch(&stub_pr(&stub).into()).expect("fetch should succeed");
+        assert!(input.threads.is_empty());
+        let reason = input.threads_unavailable.as_deref().expect("threads_unavailable");
+        assert!(reason.contains(expected) && reason.contains("Nope"), "{}", reason);
+    }
+}
+
+/// Scenario: post_review submits one batched review with RIGHT-side inline comments.
+#[test]
+fn post_review_submits_one_batched_review() {
+    let stub = StubServer::start(vec![Route::post(&format!("{}/reviews", PR_PATH), r#"{"id":1}"#)]);
+    let result = ReviewResult::new()
+        .with_summary("Two nits.")
+        .with_line_comments(vec![
+            LineComment {
+                path: "src/main.rs".to_string(),
+                line: 1,
+                body: "Name this.".to_string(),
+            },
+            LineComment {
+                path: "src/lib.rs".to_string(),
+                line: 4,
+                body: "Unused.".to_string(),
+            },
+        ])
+        .with_verdict(ReviewVerdict::RequestChanges)
+        .with_head_sha("4ead");
+    provider()
+        .post_review(&stub_pr(&stub).into(), &result)
+        .expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 1);
+    let body = posts[0].json();
+    assert_eq!(body["body"], "Two nits.");
+    assert_eq!(body["commit_id"], "4ead");
+    assert_eq!(body["event"], "REQUEST_CHANGES");
+    assert_eq!(body["comments"].as_array().unwrap().len(), 2);
+    assert_eq!(body["comments"][0]["path"], "src/main.rs");
+    assert_eq!(body["comments"][0]["line"], 1);
+    assert_eq!(body["comments"][0]["side"], "RIGHT");
+}
+
+/// Scenario: Compare targets read commits and files from the compare endpoint; posting is refused.
+#[test]
+fn fetch_compare_target() {
+    let stub = StubServer::start(vec![
+        Route::get("/repos/owner/repo/compare/main...topic", DIFF).with_accept("application/vnd.github.diff"),
+        Route::get(
+            "/repos/owner/repo/compare/main...topic",
+            r#"{"commits":[{"sha":"c1","commit":{"message":"First\n\nBody one."}},{"sha":"c2","commit":{"message":"Second"}}],
+                "base_commit":{"sha":"b0"},"merge_base_commit":{"sha":"m0"},
+                "files":[{"filename":"src/main.rs","patch":"@@ -1 +1 @@"}]}"#,
+        ),
+    ]);
+    let target = ReviewTarget::Compare {
+        repo: RepoRef::from(&stub_pr(&stub)),
+        base: "main".to_string(),
+        head: "topic".to_string(),
+    };
+    let input = provider().fetch(&target).expect("fetch should succeed");
+    assert_eq!(input.title, "topic (2 commits since main)");
+    assert!(input.description.contains("- First\n\n  Body one.\n- Second"));
+    assert_eq!(input.diff, DIFF);
+    assert_eq!(input.files[0].path, "src/main.rs");
+    assert_eq!(input.revisions.base_ref.as_deref(), Some("main"));
+    assert_eq!(input.revisions.base_sha.as_deref(), Some("b0"));
+    assert_eq!(input.revisions.head_sha.as_deref(), Some("c2"));
+    assert_eq!(input.revisions.merge_base.as_deref(), Some("m0"));
+    assert!(provider().post_review(&target, &ReviewResult::new()).is_err());
+}
+
+/// Scenario: head_sha reads `head.sha` from the PR JSON (used as the fetch cache key).
+#[test]
+fn head_sha_reads_pr_head() {
+    let stub = StubServer::start(vec![Route::get(PR_PATH, r#"{"head":{"sha":"0123abcd"}}"#)]);
+    let sha = provider().head_sha(&stub_pr(&stub).into()).expect("lookup should succeed");
+    assert_eq!(sha.as_deref(), Some("0123abcd"));
+}
@@ -0,0 +1,243 @@
+//! Integration tests for GitLabProvider against a local HTTP stub.
+//!
+//! BDD-style: given a stub serving an MR, its diffs and `diff_refs`, when we fetch, then the
+//! diff is rebuilt with `diff --git` headers; when we post, then each line comment becomes a
+//! discussion with a `position` and the summary becomes a note, and a failure midway is not
+//! posted again.
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::mcp_provider::McpErrorKind;
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::{GitLabProvider, RetryingProvider};
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::McpProvider;
+
+const MR_PATH: &str = "/projects/group%2Fsub%2Fapp/merge_requests/12";
+
+fn stub_mr(stub: &StubServer) -> PrUrl {
+    PrUrl::new(Platform::GitLab, "group/sub".into(), "app".into(), "12".into())
+        .with_host(stub.host.clone(), stub.base_url.clone())
+}
+
+fn provider() -> GitLabProvider {
+    GitLabProvider::new(HttpClient::new().with_header("PRIVATE-TOKEN", "glpat-test"))
+}
+
+const MR_JSON: &str = r#"{"title":"Add app","description":"First cut.",
+    "source_branch":"app","target_branch":"main",
+    "diff_refs":{"base_sha":"aaa","start_sha":"bbb","head_sha":"ccc"}}"#;
+
+/// Scenario: Fetch reads MR metadata and /diffs, adding file headers to each hunk set.
+#[test]
+fn fetch_reads_mr_and_diffs() {
+    let stub = StubServer::start(vec![
+        Route::get(MR_PATH, MR_JSON),
+        Route::get(
+            &format!("{}/diffs", MR_PATH),
+            r#"[{"old_path":"app.rb","new_path":"app.rb","new_file":true,"deleted_file":false,"diff":"@@ -0,0 +1 @@\n+puts 1\n"},
+                {"old_path":"old.rb","new_path":"old.rb","new_file":false,"deleted_file":true,"diff":"@@ -1 +0,0 @@\n-x\n"}]"#,
+        ),
+        Route::get(
+            &format!("{}/commits", MR_PATH),
+            r#"[{"id":"ccc","author_name":"Bo","author_email":"bo@example.com","message":"Drop old.rb\n"},
+                {"id":"c01","author_name":"Bo","message":"Add app"}]"#,
+        ),
+        Route::get(
+            "/projects/group%2Fsub%2Fapp/repository/commits/c01",
+            r#"{"id":"c01","stats":{"additions":1,"deletions":0,"total":1}}"#,
+        ),
+        Route::get(
+            "/projects/group%2Fsub%2Fapp/repository/commits/c01/diff",
+            r#"[{"old_path":"app.rb","new_path":"app.rb"}]"#,
+        ),
+        Route::get(
+            "/projects/group%2Fsub%2Fapp/repository/commits/ccc",
+            r#"{"id":"ccc","stats":{"additions":0,"deletions":1,"total":1}}"#,
+        ),
+        Route::get(
+            "/projects/group%2Fsub%2Fapp/repository/commits/ccc/diff",
+            r#"[{"old_path":"old.rb","new_path":"old.rb"}]"#,
+        ),
+        Route::get(
+            &format!("{}/discussions", MR_PATH),
+            r#"[{"notes":[{"system":true,"body":"added 1 commit"}]},
+                {"notes":[{"author":{"username":"cy"},"body":"Use a logger","resolvable":true,"resolved":false,
+                    "position":{"new_path":"app.rb","new_line":1,"head_sha":"old"}},
+                          {"author":{"username":"bo"},"body":"Later","resolvable":true,"resolved":false}]},
+                {"notes":[{"author":{"username":"cy"},"body":"Nice.","resolvable":false}]}]"#,
+        ),
+    ]);
+    let input = provider().fetch(&stub_mr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Add app");
+    assert_eq!(input.description, "First cut.");
+    assert!(input.diff.starts_with("diff --git a/app.rb b/app.rb\n--- /dev/null\n+++ b/app.rb\n@@ -0,0 +1 @@\n+puts 1\n"));
+    assert!(input.diff.contains("--- a/old.rb\n+++ /dev/null\n"));
+    assert_eq!(input.files.len(), 2);
+    assert_eq!(input.files[0].path, "app.rb");
+    let revisions = &input.revisions;
+    assert_eq!(revisions.base_ref.as_deref(), Some("main"));
+    assert_eq!(revisions.base_sha.as_deref(), Some("bbb"));
+    assert_eq!(revisions.head_ref.as_deref(), Some("app"));
+    assert_eq!(revisions.head_sha.as_deref(), Some("ccc"));
+    assert_eq!(revisions.merge_base.as_deref(), Some("aaa"));
+    // GitLab lists commits newest first.
+    let shas: Vec<&str> = input.commits.iter().map(|c| c.sha.as_str()).collect();
+    assert_eq!(shas, ["c01", "ccc"]);
+    assert_eq!(input.commits[0].author, "Bo");
+    assert_eq!(input.commits[1].subject(), "Drop old.rb");
+    // Stats and files come from the repository's commit endpoints.
+    assert_eq!(input.commits[0].stats.map(|s| s.additions), Some(1));
+    assert_eq!(input.commits[1].files, ["old.rb"]);
+    // System notes are dropped; a position taken on an older head is outdated.
+    assert_eq!(input.threads.len(), 2);
+    let thread = &input.threads[0];
+    assert_eq!((thread.path.as_deref(), thread.line), (Some("app.rb"), Some(1)));
+    assert!(!thread.resolved && thread.outdated);
+    assert_eq!(thread.comments.len(), 2);
+    assert_eq!(input.threads[1].path, None);
+    assert_eq!(input.threads[1].author(), "cy");
+    assert_eq!(stub.requests()[0].header("PRIVATE-TOKEN"), Some("glpat-test"));
+}
+
+/// Scenario: Instances without /diffs fall back to /changes.
+#[test]
+fn fetch_falls_back_to_changes() {
+    let stub = StubServer::start(vec![
+        Route::get(MR_PATH, MR_JSON),
+        Route::get(
+            &format!("{}/changes", MR_PATH),
+            r#"{"changes":[{"old_path":"a.rb","new_path":"a.rb","diff":"@@ -1 +1 @@\n-a\n+b\n"}]}"#,
+        ),
+        Route::get(&format!("{}/commits", MR_PATH), "[]"),
+        Route::get(&format!("{}/discussions", MR_PATH), "[]"),
+    ]);
+    let input = provider().fetch(&stub_mr(&stub).into()).expect("fetch should succeed");
+    assert_eq!(input.files[0].path, "a.rb");
+    assert!(input.diff.contains("--- a/a.rb\n+++ b/a.rb\n@@ -1 +1 @@\n-a\n+b\n"));
+}
+
+/// Scenario: A failing /diffs request other than 404 is reported, without trying /changes.
+#[test]
+fn fetch_does_not_fall_back_on_server_errors() {
+    let stub = StubServer::start(vec![
+        Route::get(MR_PATH, MR_JSON),
+        Route::new("GET", &format!("{}/diffs", MR_PATH), 500, "{}"),
+        Route::get(&format!("{}/changes", MR_PATH), r#"{"changes":[]}"#),
+    ]);
+    let err = provider().fetch(&stub_mr(&stub).into()).unwrap_err();
+    assert_eq!(err.kind, McpErrorKind::Server);
+    assert!(stub.requests().iter().all(|r| !r.path.ends_with("/changes")));
+}
+
+/// Scenario: post_review creates positioned discussions from diff_refs and the MR diff (context
+/// lines carry both line numbers, renamed files their old path), then a note and an approval.
+#[test]
+fn post_review_creates_positioned_discussions() {
+    let stub = StubServer::start(vec![
+        Route::get(MR_PATH, MR_JSON),
+        Route::get(
+            &format!("{}/diffs", MR_PATH),
+            r#"[{"old_path":"app.rb","new_path":"app.rb","new_file":true,"diff":"@@ -0,0 +1 @@\n+puts 1\n"},
+                {"old_path":"lib/old.rb","new_path":"lib/new.rb","diff":"@@ -4,2 +4,2 @@\n keep\n-a\n+b\n"}]"#,
+        ),
+        Route::post(&format!("{}/discussions", MR_PATH), "{}"),
+        Route::post(&format!("{}/notes", MR_PATH), "{}"),
+        Route::post(&format!("{}/approve", MR_PATH), "{}"),
+    ]);
+    let result = ReviewResult::new()
+        .with_summary("LGTM with a nit.")
+        .with_line_comments(vec![
+            LineComment {
+                path: "app.rb".to_string(),
+                line: 1,
+                body: "Use a logger.".to_string(),
+            },
+            LineComment {
+                path: "lib/new.rb".to_string(),
+                line: 4,
+                body: "Why keep this?".to_string(),
+            },
+        ])
+        .with_verdict(ReviewVerdict::Approve);
+    provider()
+        .post_review(&stub_mr(&stub).into(), &result)
+        .expect("post should succeed");
+    let posts = stub.requests_with_method("POST");
+    assert_eq!(posts.len(), 4);
+    let discussion = posts[0].json();
+    assert_eq!(discussion["body"], "Use a logger.");
+    let position = &discussion["position"];
+    assert_eq!(position["position_type"], "text");
+    assert_eq!(position["base_sha"], "aaa");
+    assert_eq!(position["start_sha"], "bbb");
+    assert_eq!(position["head_sha"], "ccc");
+    assert_eq!(position["new_path"], "app.rb");
+    assert_eq!(position["new_line"], 1);
+    assert!(position.get("old_line").is_none());
+    let context = &posts[1].json()["position"];
+    assert_eq!(context["old_path"], "lib/old.rb");
+    assert_eq!(context["new_path"], "lib/new.rb");
+    assert_eq!((context["old_line"].as_u64(), context["new_line"].as_u64()), (Some(4), Some(4)));
+    assert_eq!(posts[2].json()["body"], "LGTM with a nit.");
+    assert!(posts[3].path.ends_with("/approve"));
+}
+
+/// Scenario: Line comments are not placed when the MR head moved since the reviewed SHA.
+#[test]
+fn post_review_refuses_moved_head() {
+    let stub = StubServer::start(vec![Route::get(MR_PATH, MR_JSON)]);
+    let result = ReviewResult::new()
+        .with_line_comments(vec![LineComment {
+            path: "app.rb".to_string(),
+            line: 1,
+            body: "Use a logger.".to_string(),
+        }])
+        .with_head_sha("old");
+    let err = provider()
+        .post_review(&stub_mr(&stub).into(), &result)
+        .unwrap_err();
+    assert!(err.message.contains("moved from old to ccc"), "{}", err.message);
+    assert!(stub.requests_with_method("POST").is_empty());
+}
+
+/// Scenario: A rate-limited note after a posted discussion is a partial failure, so the retry
+/// decorator does not post the discussion again.
+#[test]
+fn post_review_failing_midway_is_not_reposted() {
+    let stub = StubServer::start(vec![
+        Route::get(MR_PATH, MR_JSON),
+        Route::get(
+            &format!("{}/diffs", MR_PATH),
+            r#"[{"old_path":"app.rb","new_path":"app.rb","new_file":true,"diff":"@@ -0,0 +1 @@\n+puts 1\n"}]"#,
+        ),
+        Route::post(&format!("{}/discussions", MR_PATH), "{}"),
+        Route::new("POST", &format!("{}/notes", MR_PATH), 429, "{}"),
+    ]);
+    let result = ReviewResult::new()
+        .with_summary("LGTM.")
+        .with_line_comments(vec![LineComment {
+            path: "app.rb".to_string(),
+            line: 1,
+            body: "Use a logger.".to_string(),
+        }]);
+    let provider = RetryingProvider::new(Arc::new(provider())).with_sleep(|_| {});
+    let err = provider.post_review(&stub_mr(&stub).into(), &result).unwrap_err();
+    assert_eq!(err.kind, McpErrorKind::RateLimited);
+    assert!(err.partial);
+    assert_eq!(stub.requests_with_method("POST").len(), 2);
+}
+
+/// Scenario: A summary-only review posts just the note, without reading diff_refs.
+#[test]
+fn post_summary_only() {
+    let stub = StubServer::start(vec![Route::post(&format!("{}/notes", MR_PATH), "{}")]);
+    provider()
+        .post_review(&stub_mr(&stub).into(), &ReviewResult::new().with_summary("Fine."))
+        .expect("post should succeed");
+    assert_eq!(stub.requests().len(), 1);
+}
@@ -0,0 +1,151 @@
+//! Integration tests for LocalGitProvider against a throwaway git repository.
+//!
+//! BDD-style: given a repository with a `main` and a `feature` branch, when we fetch
+//! `main...feature`, then title, description, diff and files come from git; when we post,
+//! then the report is written to the configured file.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use quick_review::diff_model::DiffStat;
+use quick_review::providers::{LocalGitProvider, ReviewOutput};
+use quick_review::review_result::{LineComment, ReviewResult};
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, PrUrl};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .expect("git should run");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// Repository: `main` with README and old.txt; `feature` (two commits) edits README,
+/// adds src/lib.rs and deletes old.txt. `main` then moves on (not part of the review).
+fn repo(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("quick-review-local-git-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    git(&dir, &["init", "-q", "-b", "main"]);
+    std::fs::write(dir.join("README.md"), "hello\n").unwrap();
+    std::fs::write(dir.join("old.txt"), "bye\n").unwrap();
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "Initial"]);
+    git(&dir, &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(dir.join("README.md"), "hello\nworld\n").unwrap();
+    git(&dir, &["commit", "-q", "-am", "Extend README\n\nAdds a second line."]);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+    std::fs::remove_file(dir.join("old.txt")).unwrap();
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "Add lib, drop old.txt"]);
+    git(&dir, &["checkout", "-q", "main"]);
+    std::fs::write(dir.join("unrelated.txt"), "main only\n").unwrap();
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "Unrelated main change"]);
+    dir
+}
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", rev])
+        .output()
+        .expect("git should run");
+    String::from_utf8(out.stdout).unwrap().trim().to_string()
+}
+
+fn range(dir: &Path, base: &str, head: &str) -> ReviewTarget {
+    ReviewTarget::LocalRange {
+        path: dir.to_path_buf(),
+        base: base.to_string(),
+        head: head.to_string(),
+    }
+}
+
+/// Scenario: Fetching main...feature uses the merge-base diff and lists commits in the description.
+#[test]
+fn fetch_builds_input_from_commits_and_diff() {
+    let dir = repo("fetch");
+    let input = LocalGitProvider::new().fetch(&range(&dir, "main", "feature")).expect("fetch should succeed");
+    assert_eq!(input.title, "feature (2 commits since main)");
+    assert!(input.description.contains("- Extend README\n\n  Adds a second line."));
+    assert!(input.description.contains("- Add lib, drop old.txt"));
+    assert!(input.diff.contains("+world\n"));
+    assert!(!input.diff.contains("unrelated.txt"));
+
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["README.md", "old.txt", "src/lib.rs"]);
+    let readme = &input.files[0];
+    assert_eq!(readme.content.as_deref(), Some("hello\nworld\n"));
+    assert!(readme.diff.as_deref().unwrap().starts_with("diff --git a/README.md b/README.md\n"));
+    assert_eq!(input.files[1].content, None);
+    assert_eq!(input.files[2].content.as_deref(), Some("pub fn f() {}\n"));
+
+    let revisions = &input.revisions;
+    assert_eq!(revisions.base_ref.as_deref(), Some("main"));
+    assert_eq!(revisions.head_ref.as_deref(), Some("feature"));
+    assert_eq!(revisions.base_sha, Some(rev_parse(&dir, "main")));
+    assert_eq!(revisions.head_sha, Some(rev_parse(&dir, "feature")));
+    assert_eq!(revisions.merge_base, Some(rev_parse(&dir, "main~1")));
+
+    let subjects: Vec<&str> = input.commits.iter().map(|c| c.subject()).collect();
+    assert_eq!(subjects, vec!["Extend README", "Add lib, drop old.txt"]);
+    let last = &input.commits[1];
+    assert_eq!(last.sha, rev_parse(&dir, "feature"));
+    assert!(last.author.ends_with('>'));
+    assert_eq!(last.files, vec!["old.txt", "src/lib.rs"]);
+    assert_eq!(last.stats, Some(DiffStat { additions: 1, deletions: 1 }));
+}
+
+/// Scenario: A single-commit range takes title and description from that commit.
+#[test]
+fn single_commit_range_uses_commit_message() {
+    let dir = repo("single");
+    let input = LocalGitProvider::new()
+        .fetch(&range(&dir, "feature~1", "feature"))
+        .expect("fetch should succeed");
+    assert_eq!(input.title, "Add lib, drop old.txt");
+    assert_eq!(input.description, "");
+}
+
+/// Scenario: Unknown refs and non-local targets are errors.
+#[test]
+fn unknown_ref_and_pr_target_are_errors() {
+    let dir = repo("errors");
+    let provider = LocalGitProvider::new();
+    let err = provider.fetch(&range(&dir, "main", "nope")).unwrap_err();
+    assert!(err.message.contains("nope"), "{}", err.message);
+    let pr: ReviewTarget = PrUrl::parse("https://github.com/o/r/pull/1").unwrap().into();
+    assert!(provider.fetch(&pr).is_err());
+    assert!(!provider.supports_posting(&pr));
+}
+
+/// Scenario: post_review writes the target, summary and line comments to a file; without an
+/// output the provider does not post (the CLI prints the review itself).
+#[test]
+fn post_review_writes_report_file() {
+    let dir = repo("post");
+    let out = dir.join("review.txt");
+    assert!(!LocalGitProvider::new().supports_posting(&range(&dir, "main", "feature")));
+    let provider = LocalGitProvider::new().with_output(ReviewOutput::File(out.clone()));
+    let target = range(&dir, "main", "feature");
+    assert!(provider.supports_posting(&target));
+    let result = ReviewResult::new()
+        .with_summary("Looks fine.")
+        .with_line_comments(vec![LineComment {
+            path: "src/lib.rs".to_string(),
+            line: 1,
+            body: "Add docs.".to_string(),
+        }]);
+    provider.post_review(&target, &result).expect("post should succeed");
+    let report = std::fs::read_to_string(&out).unwrap();
+    assert!(report.starts_with("Review of main...feature in "));
+    assert!(report.contains("\nLooks fine.\n"));
+    assert!(report.contains("src/lib.rs:1: Add docs.\n"));
+}
@@ -0,0 +1,209 @@
+//! Integration tests for the `mcp-serve` JSON-RPC server.
+//!
+//! BDD-style: given an McpServer whose pipeline uses a stub agent (fetch via the provider, post
+//! when allowed), when a client calls the review tools, then results come back as serialized
+//! ReviewResult JSON, posting happens only with `post: true`, local targets are refused,
+//! supplied diffs are kept per call only, and the last review is remembered.
+
+use std::sync::{Arc, Mutex};
+
+use quick_review::agent_reviewer::{AgentReviewer, ReviewError};
+use quick_review::mcp_provider::McpError;
+use quick_review::mcp_server::{McpServer, ServeProvider};
+use quick_review::review_input::ReviewInput;
+use quick_review::review_result::{ReviewResult, ReviewVerdict};
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, ReviewPipeline};
+use serde_json::{json, Value};
+
+const PR: &str = "https://github.com/owner/repo/pull/5";
+
+/// Forge stand-in: fixed PR content, records posts.
+#[derive(Default)]
+struct RecordingProvider {
+    posted: Mutex<Vec<String>>,
+}
+
+impl McpProvider for RecordingProvider {
+    fn fetch(&self, _target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        Ok(ReviewInput::new().with_title("Fix parser").with_diff("diff --git a/x b/x\n"))
+    }
+
+    fn post_review(&self, target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        self.posted.lock().unwrap().push(target.to_string());
+        Ok(())
+    }
+}
+
+/// Agent stand-in: summarizes the fetched title and diff size, posts when the provider allows it.
+/// Records the targets it reviews.
+struct StubAgent {
+    mcp: Arc<ServeProvider>,
+    seen: Arc<Mutex<Vec<ReviewTarget>>>,
+}
+
+impl AgentReviewer for StubAgent {
+    fn review(
+        &self,
+        _project_path: Option<&std::path::Path>,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError> {
+        self.seen.lock().unwrap().push(target.clone());
+        let input = self.mcp.fetch(target).map_err(|e| ReviewError { message: e.message })?;
+        let result = ReviewResult::new()
+            .with_summary(format!("{}: {} bytes", input.title, input.diff.len()))
+            .with_verdict(ReviewVerdict::Comment);
+        if self.mcp.supports_posting(target) {
+            self.mcp
+                .post_review(target, &result)
+                .map_err(|e| ReviewError { message: e.message })?;
+        }
+        Ok(result)
+    }
+}
+
+fn server() -> (McpServer<StubAgent>, Arc<RecordingProvider>) {
+    let forge = Arc::new(RecordingProvider::default());
+    let provider = Arc::new(ServeProvider::new(forge.clone()));
+    let pipeline = ReviewPipeline::new(StubAgent {
+        mcp: provider.clone(),
+        seen: Arc::default(),
+    });
+    (McpServer::new(pipeline, provider), forge)
+}
+
+fn call(server: &mut McpServer<StubAgent>, name: &str, arguments: Value) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+        "params": { "name": name, "arguments": arguments }
+    });
+    server.handle(&request).expect("requests get a response")
+}
+
+fn result_of(response: &Value) -> ReviewResult {
+    assert_eq!(response["result"]["isError"], false, "{}", response);
+    serde_json::from_str(response["result"]["content"][0]["text"].as_str().unwrap()).unwrap()
+}
+
+/// Scenario: initialize and tools/list over stdio lines; the initialized notification gets no reply.
+#[test]
+fn serve_handshake_and_tools_list() {
+    let (mut server, _) = server();
+    let input = [
+        json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}),
+        json!({"jsonrpc":"2.0","method":"notifications/initialized"}),
+        json!({"jsonrpc":"2.0","id":2,"method":"tools/list"}),
+    ]
+    .iter()
+    .map(Value::to_string)
+    .collect::<Vec<_>>()
+    .join("\n");
+    let mut output = Vec::new();
+    server.serve(input.as_bytes(), &mut output).unwrap();
+    let replies: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0]["result"]["serverInfo"]["name"], "quick-review");
+    let names: Vec<_> = replies[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["review_pr", "review_diff", "get_last_review"]);
+}
+
+/// Scenario: review_pr returns the ReviewResult and does not post unless `post` is true.
+#[test]
+fn review_pr_posts_only_when_asked() {
+    let (mut server, forge) = server();
+    let result = result_of(&call(&mut server, "review_pr", json!({ "url": PR })));
+    assert_eq!(result.summary, "Fix parser: 19 bytes");
+    assert_eq!(result.verdict, Some(ReviewVerdict::Comment));
+    assert!(forge.posted.lock().unwrap().is_empty());
+
+    result_of(&call(&mut server, "review_pr", json!({ "url": PR, "post": true })));
+    assert_eq!(*forge.posted.lock().unwrap(), [PR.to_string()]);
+
+    // Posting is switched off again after the call.
+    result_of(&call(&mut server, "review_pr", json!({ "url": PR })));
+    assert_eq!(forge.posted.lock().unwrap().len(), 1);
+}
+
+/// Scenario: review_diff reviews the supplied diff and never posts.
+#[test]
+fn review_diff_uses_supplied_diff() {
+    let (mut server, forge) = server();
+    let response = call(
+        &mut server,
+        "review_diff",
+        json!({ "diff": "+added\n", "title": "Snippet", "post": true }),
+    );
+    assert_eq!(result_of(&response).summary, "Snippet: 7 bytes");
+    assert!(forge.posted.lock().unwrap().is_empty());
+}
+
+/// Scenario: Each review_diff call gets its own target, even with the same title, and its diff
+/// is dropped once the review is done.
+#[test]
+fn review_diff_calls_do_not_share_or_keep_diffs() {
+    let provider = Arc::new(ServeProvider::new(Arc::new(RecordingProvider::default())));
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let agent = StubAgent {
+        mcp: provider.clone(),
+        seen: seen.clone(),
+    };
+    let mut server = McpServer::new(ReviewPipeline::new(agent), provider.clone());
+    let first = call(&mut server, "review_diff", json!({ "diff": "+a\n", "title": "Same" }));
+    let second = call(&mut server, "review_diff", json!({ "diff": "+bb\n", "title": "Same" }));
+    assert_eq!(result_of(&first).summary, "Same: 3 bytes");
+    assert_eq!(result_of(&second).summary, "Same: 4 bytes");
+    let seen = seen.lock().unwrap();
+    assert_ne!(seen[0], seen[1]);
+    assert!(seen.iter().all(|target| provider.fetch(target).is_err()));
+}
+
+/// Scenario: get_last_review errors before any review, then returns the last review_pr result.
+#[test]
+fn get_last_review_returns_previous_result() {
+    let (mut server, _) = server();
+    let before = call(&mut server, "get_last_review", json!({ "url": PR }));
+    assert_eq!(before["result"]["isError"], true);
+    call(&mut server, "review_pr", json!({ "url": PR }));
+    let after = call(&mut server, "get_last_review", json!({ "url": PR }));
+    assert_eq!(result_of(&after).summary, "Fix parser: 19 bytes");
+}
+
+/// Scenario: review_pr and get_last_review reject local targets (stdin, patch files, ranges).
+#[test]
+fn local_targets_are_rejected() {
+    let (mut server, _) = server();
+    for url in ["-", "fix.patch", "main..feature"] {
+        let response = call(&mut server, "review_pr", json!({ "url": url }));
+        assert_eq!(response["result"]["isError"], true, "{}", url);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("is not a PR/MR, commit or compare URL"), "{}", text);
+    }
+    let last = call(&mut server, "get_last_review", json!({ "url": "-" }));
+    assert_eq!(last["result"]["isError"], true);
+}
+
+/// Scenario: Bad arguments are tool errors; unknown tools and methods are JSON-RPC errors.
+#[test]
+fn errors_are_reported() {
+    let (mut server, _) = server();
+    let missing = call(&mut server, "review_pr", json!({}));
+    assert_eq!(missing["result"]["isError"], true);
+    assert!(missing["result"]["content"][0]["text"].as_str().unwrap().contains("url"));
+    let invalid = call(&mut server, "review_pr", json!({ "url": "https://example.com/x" }));
+    assert_eq!(invalid["result"]["isError"], true);
+    let unknown = call(&mut server, "merge_pr", json!({}));
+    assert_eq!(unknown["error"]["code"], -32602);
+    let method = server
+        .handle(&json!({"jsonrpc":"2.0","id":3,"method":"resources/list"}))
+        .unwrap();
+    assert_eq!(method["error"]["code"], -32601);
+}
@@ -0,0 +1,119 @@
+//! Integration tests for McpStdioProvider against the fake MCP server binary.
+//!
+//! BDD-style: given `fake-mcp-server` (tests/support) speaking JSON-RPC over stdio, when we fetch,
+//! then the handshake runs and the PR tools fill ReviewInput; when we post, then the review tool
+//! receives summary, event and comments; tool errors and unanswered calls become McpError.
+
+use std::time::{Duration, Instant};
+
+use quick_review::diff_model::FileStatus;
+use quick_review::mcp_client::{McpClient, McpServerConfig};
+use quick_review::pr_url::{Platform, PrUrl};
+use quick_review::providers::{McpStdioProvider, McpToolSet};
+use quick_review::review_result::{LineComment, ReviewResult, ReviewVerdict};
+use quick_review::McpProvider;
+
+const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+
+fn server(mode: &str) -> McpServerConfig {
+    McpServerConfig::new(env!("CARGO_BIN_EXE_fake-mcp-server"))
+        .with_env("FAKE_MCP_MODE", mode)
+        .with_timeout(Duration::from_secs(10))
+}
+
+fn pr() -> PrUrl {
+    PrUrl::new(Platform::GitHub, "owner".into(), "repo".into(), "7".into())
+}
+
+/// Scenario: The handshake reports the server name and listed tools, skipping log lines.
+#[test]
+fn client_handshake_lists_tools() {
+    let client = McpClient::spawn(&server("")).expect("spawn should succeed");
+    assert_eq!(client.server_name(), "fake-mcp");
+    assert!(client.has_tool("get_pull_request"));
+    assert!(client.has_tool("get_pull_request_diff"));
+    assert!(!client.has_tool("merge_pull_request"));
+}
+
+/// Scenario: Fetch calls the PR, files and diff tools with owner / repo / pullNumber; MCP servers
+/// have no thread tool, so no existing threads are listed.
+#[test]
+fn fetch_fills_review_input_from_tools() {
+    let provider = McpStdioProvider::new(server(""), McpToolSet::github());
+    let input = provider.fetch(&pr().into()).expect("fetch should succeed");
+    assert_eq!(input.title, "Fix lib");
+    assert_eq!(input.description, "Replaces old with new.");
+    assert_eq!(input.diff, DIFF);
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].path, "src/lib.rs");
+    assert_eq!(input.files[0].diff.as_deref(), Some("@@ -1 +1 @@\n-old\n+new"));
+    assert!(input.threads.is_empty());
+    assert!(input.commits.is_empty());
+    assert!(input.commits_unavailable.is_some());
+}
+
+/// Scenario: Without a diff tool, the diff is assembled from the per-file patches, with
+/// `/dev/null` on the missing side of added and removed files.
+#[test]
+fn fetch_without_diff_tool_builds_diff_from_files() {
+    let provider = McpStdioProvider::new(server("no-diff-tool"), McpToolSet::github());
+    let input = provider.fetch(&pr().into()).expect("fetch should succeed");
+    assert!(input.diff.starts_with(DIFF), "{}", input.diff);
+    assert!(input.diff.contains("--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+new\n"));
+    assert!(input.diff.contains("--- a/src/gone.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-old\n"));
+    let statuses: Vec<_> = input.files.iter().map(|f| f.status.clone()).collect();
+    assert_eq!(
+        statuses,
+        [Some(FileStatus::Modified), Some(FileStatus::Added), Some(FileStatus::Deleted)]
+    );
+}
+
+/// Scenario: An `isError` tool result is reported with the tool name and its text.
+#[test]
+fn tool_error_becomes_mcp_error() {
+    let provider = McpStdioProvider::new(server("tool-error"), McpToolSet::github());
+    let err = provider.fetch(&pr().into()).unwrap_err();
+    assert!(err.message.contains("get_pull_request"), "{}", err);
+    assert!(err.message.contains("404 Not Found"), "{}", err);
+}
+
+/// Scenario: A tool call that never gets an answer fails after the configured timeout.
+#[test]
+fn unanswered_call_times_out() {
+    let config = server("hang").with_timeout(Duration::from_millis(300));
+    let provider = McpStdioProvider::new(config, McpToolSet::github());
+    let started = Instant::now();
+    let err = provider.fetch(&pr().into()).unwrap_err();
+    assert!(err.message.contains("no response to tools/call"), "{}", err);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+/// Scenario: post_review calls the review tool once with summary, event and RIGHT-side comments.
+#[test]
+fn post_review_calls_review_tool() {
+    let record = std::env::temp_dir().join(format!("fake-mcp-record-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&record);
+    let config = server("").with_env("FAKE_MCP_RECORD", record.to_string_lossy());
+    let provider = McpStdioProvider::new(config, McpToolSet::github());
+    let result = ReviewResult::new()
+        .with_summary("One nit.")
+        .with_line_comments(vec![LineComment {
+            path: "src/lib.rs".to_string(),
+            line: 1,
+            body: "Name this.".to_string(),
+        }])
+        .with_verdict(ReviewVerdict::Approve);
+    provider
+        .post_review(&pr().into(), &result)
+        .expect("post should succeed");
+    let recorded = std::fs::read_to_string(&record).expect("review should be recorded");
+    let _ = std::fs::remove_file(&record);
+    let lines: Vec<_> = recorded.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let args: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(args["pullNumber"], 7);
+    assert_eq!(args["body"], "One nit.");
+    assert_eq!(args["event"], "APPROVE");
+    assert_eq!(args["comments"][0]["path"], "src/lib.rs");
+    assert_eq!(args["comments"][0]["side"], "RIGHT");
+}
@@ -0,0 +1,122 @@
+//! Integration tests for PatchProvider.
+//!
+//! BDD-style: given a bare diff, a `git format-patch` file or an mbox series (with or without a
+//! cover letter), when we fetch it as a patch target, then title, description, diff and files
+//! come from the mail headers, commit messages and diffs; stdin is read via `-`; reviews are
+//! only posted to a configured output.
+
+use std::path::PathBuf;
+
+use quick_review::providers::patch::split_mails;
+use quick_review::providers::{PatchProvider, ReviewOutput};
+use quick_review::review_result::ReviewResult;
+use quick_review::review_target::ReviewTarget;
+use quick_review::McpProvider;
+
+const DIFF_README: &str = "diff --git a/README.md b/README.md
+index 1111111..2222222 100644
+--- a/README.md
++++ b/README.md
+@@ -1 +1,2 @@
+ hello
++world
+";
+
+const DIFF_LIB: &str = "diff --git a/src/lib.rs b/src/lib.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/src/lib.rs
+@@ -0,0 +1 @@
++pub fn f() {}
+";
+
+fn mail(sha: &str, subject: &str, message: &str, diff: &str) -> String {
+    format!(
+        "From {} Mon Sep 17 00:00:00 2001\nFrom: Ada Lovelace <ada@example.com>\nDate: Tue, 1 Oct 2024 10:00:00 +0200\nSubject: {}\n\n{}\n---\n README.md | 1 +\n 1 file changed, 1 insertion(+)\n\n{}-- \n2.43.0\n\n",
+        sha, subject, message, diff
+    )
+}
+
+fn write(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("quick-review-patch-{}-{}", std::process::id(), name));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+fn fetch(name: &str, text: &str) -> quick_review::ReviewInput {
+    let path = write(name, text);
+    PatchProvider::new()
+        .fetch(&ReviewTarget::Patch { path })
+        .expect("fetch should succeed")
+}
+
+/// Scenario: A single format-patch file uses its subject (prefix stripped) and commit message;
+/// the diffstat and signature are not part of the diff; the head SHA is the From line's.
+#[test]
+fn single_patch_uses_subject_and_message() {
+    let sha = "0123456789abcdef0123456789abcdef01234567";
+    let text = mail(sha, "[PATCH v2] readme: add world", "Say hello to the world.\n\nSigned-off-by: Ada", DIFF_README);
+    let input = fetch("single.patch", &text);
+    assert_eq!(input.title, "readme: add world");
+    assert_eq!(input.description, "Say hello to the world.\n\nSigned-off-by: Ada");
+    assert_eq!(input.diff, DIFF_README);
+    assert_eq!(input.files.len(), 1);
+    assert_eq!(input.files[0].path, "README.md");
+
+    let path = write("single-sha.patch", &text);
+    let head = PatchProvider::new().head_sha(&ReviewTarget::Patch { path }).unwrap();
+    assert_eq!(head.as_deref(), Some(sha));
+}
+
+/// Scenario: A series without a cover letter lists its patches and concatenates their diffs;
+/// with a cover letter, the cover letter gives title and description.
+#[test]
+fn series_uses_cover_letter_or_patch_list() {
+    let patches = format!(
+        "{}{}",
+        mail("1111111111111111111111111111111111111111", "[PATCH 1/2] readme: add world", "Body one.", DIFF_README),
+        mail("2222222222222222222222222222222222222222", "[PATCH 2/2] lib: add f", "", DIFF_LIB),
+    );
+    let input = fetch("series.mbox", &patches);
+    assert_eq!(input.title, "readme: add world (2 patches)");
+    assert_eq!(input.description, "- readme: add world\n\n  Body one.\n- lib: add f");
+    assert_eq!(input.diff, format!("{}{}", DIFF_README, DIFF_LIB));
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["README.md", "src/lib.rs"]);
+
+    let cover = "From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001\nFrom: Ada Lovelace <ada@example.com>\nSubject: [PATCH 0/2] Greeting\n =?UTF-8?q?improvements_=E2=9C=93?=\n\nMake the greeting friendlier.\n\n-- \n2.43.0\n\n";
+    let input = fetch("cover.mbox", &format!("{}{}", cover, patches));
+    assert_eq!(input.title, "Greeting improvements ✓");
+    assert_eq!(input.description, "Make the greeting friendlier.");
+    assert_eq!(input.diff, format!("{}{}", DIFF_README, DIFF_LIB));
+    assert_eq!(split_mails(&format!("{}{}", cover, patches)).len(), 3);
+}
+
+/// Scenario: A bare diff on stdin is titled "Diff from stdin" and split into files; plain
+/// `diff -u` output is split too; input without a diff is an error.
+#[test]
+fn bare_diffs_from_stdin_and_files() {
+    let stdin = ReviewTarget::Patch { path: "-".into() };
+    let provider = PatchProvider::new().with_stdin(DIFF_LIB);
+    let input = provider.fetch(&stdin).unwrap();
+    assert_eq!(input.title, "Diff from stdin");
+    assert_eq!(input.diff, DIFF_LIB);
+    assert_eq!(input.files[0].path, "src/lib.rs");
+    assert_eq!(provider.head_sha(&stdin).unwrap(), None);
+    // The CLI prints the review itself; only a configured output enables posting.
+    assert!(!provider.supports_posting(&stdin));
+    assert!(provider.post_review(&stdin, &ReviewResult::new()).is_err());
+    let provider = PatchProvider::new().with_output(ReviewOutput::Stdout);
+    assert!(provider.supports_posting(&stdin));
+
+    let plain = "--- a.c\t2024-01-01 00:00:00\n+++ a.c\t2024-01-02 00:00:00\n@@ -1 +1 @@\n-x\n+y\n--- b.c\n+++ b.c\n@@ -1 +1 @@\n-p\n+q\n";
+    let input = fetch("plain.diff", plain);
+    assert_eq!(input.title, format!("quick-review-patch-{}-plain.diff", std::process::id()));
+    let paths: Vec<&str> = input.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["a.c", "b.c"]);
+    assert_eq!(input.files[1].diff.as_deref(), Some("--- b.c\n+++ b.c\n@@ -1 +1 @@\n-p\n+q\n"));
+
+    let empty = ReviewTarget::Patch { path: write("empty.patch", "just text\n") };
+    assert!(PatchProvider::new().fetch(&empty).is_err());
+}
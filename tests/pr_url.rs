@@ -0,0 +1,217 @@
+//! Integration tests for PrUrl parsing: public hosts and self-hosted instances.
+//!
+//! BDD-style: given a URL and a `KnownHosts` list, when we parse, then platform, host,
+//! API base, owner, repo and id match the expected values (or the expected `PrUrlParseError`).
+
+use quick_review::known_hosts::KnownHosts;
+use quick_review::pr_url::{Platform, PrUrl, PrUrlParseError};
+
+/// Scenario: A github.com PR URL parses with the public host and api.github.com.
+#[test]
+fn parse_github_com_pr_uses_public_api() {
+    let pr = PrUrl::parse("https://github.com/owner/repo/pull/123").expect("should parse");
+    assert_eq!(pr.platform, Platform::GitHub);
+    assert_eq!(pr.host, "github.com");
+    assert_eq!(pr.api_base, "https://api.github.com");
+    assert_eq!(pr.owner, "owner");
+    assert_eq!(pr.repo, "repo");
+    assert_eq!(pr.id, "123");
+}
+
+/// Scenario: A gitlab.com MR URL parses with the gitlab.com v4 API.
+#[test]
+fn parse_gitlab_com_mr_uses_v4_api() {
+    let pr = PrUrl::parse("https://gitlab.com/owner/repo/-/merge_requests/456").expect("should parse");
+    assert_eq!(pr.platform, Platform::GitLab);
+    assert_eq!(pr.host, "gitlab.com");
+    assert_eq!(pr.api_base, "https://gitlab.com/api/v4");
+    assert_eq!(pr.id, "456");
+}
+
+/// Scenario: Unknown hosts are rejected by default.
+#[test]
+fn parse_unknown_host_is_rejected() {
+    assert_eq!(
+        PrUrl::parse("https://ghe.corp.example/owner/repo/pull/1").unwrap_err(),
+        PrUrlParseError::UnknownHost("ghe.corp.example".to_string())
+    );
+}
+
+/// Scenario: A GitHub Enterprise host added to KnownHosts parses with the /api/v3 base.
+#[test]
+fn parse_github_enterprise_host() {
+    let hosts = KnownHosts::new().with_host("ghe.corp.example", Platform::GitHub);
+    let pr = PrUrl::parse_with_hosts("https://ghe.corp.example/team/app/pull/9", &hosts)
+        .expect("should parse");
+    assert_eq!(pr.platform, Platform::GitHub);
+    assert_eq!(pr.host, "ghe.corp.example");
+    assert_eq!(pr.api_base, "https://ghe.corp.example/api/v3");
+    assert_eq!(pr.owner, "team");
+    assert_eq!(pr.repo, "app");
+    assert_eq!(pr.id, "9");
+}
+
+/// Scenario: A host spec string adds a self-managed GitLab with a custom API base.
+#[test]
+fn known_hosts_spec_with_custom_api_base() {
+    let hosts = KnownHosts::new()
+        .with_spec("gitlab=git.corp.example=https://git.corp.example/gitlab/api/v4/")
+        .expect("spec should parse");
+    let pr = PrUrl::parse_with_hosts("https://git.corp.example/group/proj/-/merge_requests/3", &hosts)
+        .expect("should parse");
+    assert_eq!(pr.platform, Platform::GitLab);
+    assert_eq!(pr.api_base, "https://git.corp.example/gitlab/api/v4");
+}
+
+/// Scenario: A host spec with an unknown platform name is an error.
+#[test]
+fn known_hosts_spec_unknown_platform_is_error() {
+    assert!(KnownHosts::new().with_spec("svn=svn.corp.example").is_err());
+}
+
+/// Scenario: A GitLab MR in nested subgroups keeps the full namespace as owner.
+#[test]
+fn parse_gitlab_nested_subgroups_keeps_full_namespace() {
+    let pr = PrUrl::parse("https://gitlab.com/org/team/sub/project/-/merge_requests/7").expect("should parse");
+    assert_eq!(pr.owner, "org/team/sub");
+    assert_eq!(pr.repo, "project");
+    assert_eq!(pr.id, "7");
+    assert_eq!(pr.namespace_groups(), vec!["org", "team", "sub"]);
+    assert_eq!(pr.project_path(), "org/team/sub/project");
+    assert_eq!(pr.encoded_project_path(), "org%2Fteam%2Fsub%2Fproject");
+}
+
+/// Scenario: A GitLab URL without a project before `-` is rejected.
+#[test]
+fn parse_gitlab_without_project_is_rejected() {
+    assert!(matches!(
+        PrUrl::parse("https://gitlab.com/org/-/merge_requests/7"),
+        Err(PrUrlParseError::NotAPullRequest { .. })
+    ));
+}
+
+/// Scenario: A GitHub repo URL without `/pull/` reports NotAPullRequest.
+#[test]
+fn parse_github_without_pull_segment_is_not_a_pull_request() {
+    assert!(matches!(
+        PrUrl::parse("https://github.com/owner/repo/issues/5"),
+        Err(PrUrlParseError::NotAPullRequest { .. })
+    ));
+}
+
+/// Scenario: A non-numeric id reports InvalidId.
+#[test]
+fn parse_non_numeric_id_is_invalid_id() {
+    assert_eq!(
+        PrUrl::parse("https://github.com/owner/repo/pull/abc").unwrap_err(),
+        PrUrlParseError::InvalidId("abc".to_string())
+    );
+}
+
+/// Scenario: `/files` sub-pages, queries and `#discussion` fragments are rejected, since they
+/// would not survive a round trip.
+#[test]
+fn parse_rejects_subpages_queries_and_fragments() {
+    assert_eq!(
+        PrUrl::parse("https://github.com/owner/repo/pull/12/files").unwrap_err(),
+        PrUrlParseError::UnexpectedTrailing("files".to_string())
+    );
+    assert_eq!(
+        PrUrl::parse("https://github.com/owner/repo/pull/12#discussion_r99").unwrap_err(),
+        PrUrlParseError::QueryOrFragment("#discussion_r99".to_string())
+    );
+    assert_eq!(
+        PrUrl::parse("https://gitlab.com/g/p/-/merge_requests/3?view=inline#note_1").unwrap_err(),
+        PrUrlParseError::QueryOrFragment("?view=inline#note_1".to_string())
+    );
+    assert_eq!(
+        PrUrl::parse("https://review.gerrithub.io/c/proj/+/5/2/src/a.rs").unwrap_err(),
+        PrUrlParseError::UnexpectedTrailing("src/a.rs".to_string())
+    );
+    assert_eq!(
+        PrUrl::parse("https://review.gerrithub.io/c/proj/+/5/1..3").unwrap_err(),
+        PrUrlParseError::InvalidId("1..3".to_string())
+    );
+}
+
+/// Scenario: Unknown segments after the id report UnexpectedTrailing.
+#[test]
+fn parse_unknown_trailing_segments_is_error() {
+    assert_eq!(
+        PrUrl::parse("https://github.com/owner/repo/pull/12/bogus/x").unwrap_err(),
+        PrUrlParseError::UnexpectedTrailing("bogus/x".to_string())
+    );
+}
+
+/// Scenario: Display / to_web_url round-trips through FromStr; to_api_url targets the REST resource.
+#[test]
+fn web_url_round_trips_and_api_url_is_rest_resource() {
+    let pr: PrUrl = "https://gitlab.com/org/team/project/-/merge_requests/8"
+        .parse()
+        .expect("should parse");
+    assert_eq!(pr.to_string(), "https://gitlab.com/org/team/project/-/merge_requests/8");
+    let again: PrUrl = pr.to_string().parse().expect("should parse");
+    assert_eq!(again, pr);
+    assert_eq!(
+        pr.to_api_url(),
+        "https://gitlab.com/api/v4/projects/org%2Fteam%2Fproject/merge_requests/8"
+    );
+    let gh = PrUrl::parse("https://github.com/o/r/pull/1").expect("should parse");
+    assert_eq!(gh.to_api_url(), "https://api.github.com/repos/o/r/pulls/1");
+}
+
+/// Scenario: The scheme survives a round trip: a plain-http instance keeps http for its web URL
+/// and API base; http on a host whose API lives elsewhere (github.com) is rejected.
+#[test]
+fn web_url_round_trip_keeps_the_scheme() {
+    let hosts = KnownHosts::new().with_host("git.lab.local", Platform::GitLab);
+    let url = "http://git.lab.local/team/app/-/merge_requests/4";
+    let mr = PrUrl::parse_with_hosts(url, &hosts).expect("should parse");
+    assert_eq!(mr.api_base, "http://git.lab.local/api/v4");
+    assert_eq!(mr.to_web_url(), url);
+    assert_eq!(PrUrl::parse_with_hosts(&mr.to_string(), &hosts).expect("should parse"), mr);
+
+    let https = "https://git.lab.local/team/app/-/merge_requests/4";
+    assert_eq!(PrUrl::parse_with_hosts(https, &hosts).expect("should parse").to_web_url(), https);
+
+    assert_eq!(
+        PrUrl::parse("http://github.com/o/r/pull/1").unwrap_err(),
+        PrUrlParseError::UnsupportedScheme("http://github.com/o/r/pull/1".to_string())
+    );
+}
+
+/// Scenario: A self-hosted PrUrl survives a serde JSON round trip unchanged.
+#[test]
+fn serde_round_trip_keeps_host_and_api_base() {
+    let hosts = KnownHosts::new().with_host("ghe.corp.example", Platform::GitHub);
+    let pr = PrUrl::parse_with_hosts("https://ghe.corp.example/team/app/pull/9", &hosts).expect("should parse");
+    let json = serde_json::to_string(&pr).expect("serialize");
+    let back: PrUrl = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(back, pr);
+    assert_eq!(back.to_web_url(), "https://ghe.corp.example/team/app/pull/9");
+}
+
+/// Scenario: Bitbucket Cloud and Data Center PR URLs parse and rebuild their web/API URLs.
+#[test]
+fn parse_bitbucket_cloud_and_data_center() {
+    let cloud = PrUrl::parse("https://bitbucket.org/ws/repo/pull-requests/7").expect("should parse");
+    assert_eq!(cloud.platform, Platform::Bitbucket);
+    assert_eq!((cloud.owner.as_str(), cloud.repo.as_str(), cloud.id.as_str()), ("ws", "repo", "7"));
+    assert!(!cloud.is_bitbucket_data_center());
+    assert_eq!(cloud.to_web_url(), "https://bitbucket.org/ws/repo/pull-requests/7");
+    assert_eq!(cloud.to_api_url(), "https://api.bitbucket.org/2.0/repositories/ws/repo/pullrequests/7");
+
+    let hosts = KnownHosts::new().with_host("bb.corp.example", Platform::Bitbucket);
+    let dc = PrUrl::parse_with_hosts(
+        "https://bb.corp.example/projects/PROJ/repos/app/pull-requests/12",
+        &hosts,
+    )
+    .expect("should parse");
+    assert!(dc.is_bitbucket_data_center());
+    assert_eq!((dc.owner.as_str(), dc.repo.as_str(), dc.id.as_str()), ("PROJ", "app", "12"));
+    assert_eq!(dc.to_web_url(), "https://bb.corp.example/projects/PROJ/repos/app/pull-requests/12");
+    assert_eq!(
+        dc.to_api_url(),
+        "https://bb.corp.example/rest/api/1.0/projects/PROJ/repos/app/pull-requests/12"
+    );
+}
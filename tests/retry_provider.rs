@@ -0,0 +1,201 @@
+//! Integration tests for McpError kinds from HttpClient and the RetryingProvider decorator.
+//!
+//! BDD-style: given a stub forge answering with error statuses and rate-limit headers, when the
+//! HTTP client calls it, then errors carry the right kind, wait and quota; given a provider that
+//! fails transiently, when wrapped for retry, then it is retried with backoff or server-requested
+//! waits, and non-retryable failures (or non-rate-limit and partial post failures) are returned
+//! at once.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{Route, StubServer};
+use quick_review::http::HttpClient;
+use quick_review::mcp_provider::{McpError, McpErrorKind};
+use quick_review::providers::{RetryPolicy, RetryingProvider};
+use quick_review::review_input::ReviewInput;
+use quick_review::review_result::ReviewResult;
+use quick_review::review_target::ReviewTarget;
+use quick_review::{McpProvider, PrUrl};
+
+/// Fails with the queued errors, then succeeds; counts calls.
+struct FlakyProvider {
+    errors: Mutex<Vec<McpError>>,
+    calls: Mutex<u32>,
+}
+
+impl FlakyProvider {
+    fn failing_with(mut errors: Vec<McpError>) -> Arc<Self> {
+        errors.reverse();
+        Arc::new(Self {
+            errors: Mutex::new(errors),
+            calls: Mutex::new(0),
+        })
+    }
+
+    fn calls(&self) -> u32 {
+        *self.calls.lock().unwrap()
+    }
+
+    fn next(&self) -> Result<(), McpError> {
+        *self.calls.lock().unwrap() += 1;
+        match self.errors.lock().unwrap().pop() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl McpProvider for FlakyProvider {
+    fn fetch(&self, _target: &ReviewTarget) -> Result<ReviewInput, McpError> {
+        self.next().map(|_| ReviewInput::new().with_title("ok"))
+    }
+
+    fn post_review(&self, _target: &ReviewTarget, _result: &ReviewResult) -> Result<(), McpError> {
+        self.next()
+    }
+}
+
+fn error(kind: McpErrorKind) -> McpError {
+    McpError::new(format!("{:?}", kind)).with_kind(kind)
+}
+
+fn retrying(inner: Arc<FlakyProvider>) -> (RetryingProvider, Arc<Mutex<Vec<Duration>>>) {
+    let sleeps = Arc::new(Mutex::new(Vec::new()));
+    let recorded = sleeps.clone();
+    let provider = RetryingProvider::new(inner)
+        .with_policy(RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(250),
+            max_wait: Duration::from_secs(120),
+        })
+        .with_sleep(move |d| recorded.lock().unwrap().push(d));
+    (provider, sleeps)
+}
+
+fn pr() -> ReviewTarget {
+    PrUrl::parse("https://github.com/owner/repo/pull/1").unwrap().into()
+}
+
+/// Scenario: HTTP statuses map to kinds; Retry-After (seconds or HTTP date), exhausted quota and
+/// GitHub's secondary rate limit become rate-limit errors with a wait; the last quota is exposed.
+#[test]
+fn http_errors_carry_kind_wait_and_quota() {
+    let stub = StubServer::start(vec![
+        Route::new("GET", "/429", 429, "slow down").with_header("Retry-After", "7"),
+        Route::new("GET", "/429-date", 429, "slow down")
+            .with_header("Retry-After", "Thu, 01 Jan 2099 00:00:00 GMT"),
+        Route::new("GET", "/429-past", 429, "slow down")
+            .with_header("Retry-After", "Sun, 06 Nov 1994 08:49:37 GMT"),
+        Route::new("GET", "/429-reset", 429, "slow down")
+            .with_header("X-RateLimit-Remaining", "10")
+            .with_header("X-RateLimit-Reset", "0"),
+        Route::new("GET", "/secondary", 403, r#"{"message":"You have exceeded a secondary rate limit."}"#),
+        Route::new("GET", "/exhausted", 403, r#"{"message":"API rate limit exceeded"}"#)
+            .with_header("X-RateLimit-Limit", "5000")
+            .with_header("X-RateLimit-Remaining", "0")
+            .with_header("X-RateLimit-Reset", "0"),
+        Route::new("GET", "/401", 401, "bad credentials"),
+        Route::new("GET", "/403", 403, "forbidden"),
+        Route::new("GET", "/502", 502, "bad gateway"),
+        Route::get("/ok", "{}").with_header("X-RateLimit-Remaining", "4999"),
+    ]);
+    let http = HttpClient::new();
+    let get = |path: &str| http.get_json(&format!("{}{}", stub.base_url, path)).unwrap_err();
+
+    let e = get("/429");
+    assert_eq!((e.kind, e.retry_after), (McpErrorKind::RateLimited, Some(Duration::from_secs(7))));
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let wait = get("/429-date").retry_after.expect("wait from HTTP date").as_secs();
+    assert!((4_070_908_800 - now).abs_diff(wait) <= 5, "{}", wait);
+    assert_eq!(get("/429-past").retry_after, Some(Duration::ZERO));
+    assert_eq!(get("/429-reset").retry_after, Some(Duration::ZERO));
+    let e = get("/secondary");
+    assert_eq!((e.kind, e.retry_after), (McpErrorKind::RateLimited, Some(Duration::from_secs(60))));
+    let e = get("/exhausted");
+    assert_eq!((e.kind, e.retry_after), (McpErrorKind::RateLimited, Some(Duration::ZERO)));
+    let quota = http.rate_limit().expect("quota from headers");
+    assert_eq!((quota.limit, quota.remaining), (Some(5000), 0));
+    assert_eq!(get("/401").kind, McpErrorKind::Auth);
+    assert_eq!(get("/403").kind, McpErrorKind::Auth);
+    assert_eq!(get("/missing").kind, McpErrorKind::NotFound);
+    assert_eq!(get("/502").kind, McpErrorKind::Server);
+
+    http.get_json(&format!("{}/ok", stub.base_url)).unwrap();
+    assert_eq!(http.rate_limit().unwrap().remaining, 4999);
+}
+
+/// Scenario: Transient failures are retried with growing, capped, jittered delays.
+#[test]
+fn transient_failures_are_retried_with_backoff() {
+    let inner = FlakyProvider::failing_with(vec![
+        error(McpErrorKind::Server),
+        error(McpErrorKind::Timeout),
+        error(McpErrorKind::Network),
+    ]);
+    let (provider, sleeps) = retrying(inner.clone());
+    assert_eq!(provider.fetch(&pr()).unwrap().title, "ok");
+    assert_eq!(inner.calls(), 4);
+    let sleeps = sleeps.lock().unwrap();
+    let ms: Vec<u128> = sleeps.iter().map(Duration::as_millis).collect();
+    assert!((50..=100).contains(&ms[0]), "{:?}", ms);
+    assert!((100..=200).contains(&ms[1]), "{:?}", ms);
+    assert!((125..=250).contains(&ms[2]), "{:?}", ms);
+}
+
+/// Scenario: Server-requested waits are honoured exactly; waits beyond max_wait and exhausted
+/// retries give up with the last error.
+#[test]
+fn retry_after_is_honoured_and_limits_apply() {
+    let inner = FlakyProvider::failing_with(vec![
+        error(McpErrorKind::RateLimited).with_retry_after(Some(Duration::from_secs(42))),
+    ]);
+    let (provider, sleeps) = retrying(inner);
+    provider.fetch(&pr()).unwrap();
+    assert_eq!(*sleeps.lock().unwrap(), [Duration::from_secs(42)]);
+
+    let inner = FlakyProvider::failing_with(vec![
+        error(McpErrorKind::RateLimited).with_retry_after(Some(Duration::from_secs(3600))),
+    ]);
+    let (provider, _) = retrying(inner.clone());
+    assert_eq!(provider.fetch(&pr()).unwrap_err().kind, McpErrorKind::RateLimited);
+    assert_eq!(inner.calls(), 1);
+
+    let inner = FlakyProvider::failing_with((0..5).map(|_| error(McpErrorKind::Server)).collect());
+    let (provider, _) = retrying(inner.clone());
+    assert!(provider.fetch(&pr()).is_err());
+    assert_eq!(inner.calls(), 4);
+}
+
+/// Scenario: Non-retryable fetch errors fail at once; posts are retried only when rate limited.
+#[test]
+fn non_retryable_and_post_failures() {
+    let inner = FlakyProvider::failing_with(vec![error(McpErrorKind::Auth)]);
+    let (provider, _) = retrying(inner.clone());
+    assert_eq!(provider.fetch(&pr()).unwrap_err().kind, McpErrorKind::Auth);
+    assert_eq!(inner.calls(), 1);
+
+    let inner = FlakyProvider::failing_with(vec![error(McpErrorKind::Timeout)]);
+    let (provider, _) = retrying(inner.clone());
+    assert!(provider.post_review(&pr(), &ReviewResult::new()).is_err());
+    assert_eq!(inner.calls(), 1);
+
+    let inner = FlakyProvider::failing_with(vec![error(McpErrorKind::RateLimited)]);
+    let (provider, _) = retrying(inner.clone());
+    provider.post_review(&pr(), &ReviewResult::new()).unwrap();
+    assert_eq!(inner.calls(), 2);
+}
+
+/// Scenario: A rate-limited post after part of the review went through is not retried.
+#[test]
+fn partial_post_failures_are_not_retried() {
+    let partial = error(McpErrorKind::RateLimited).with_partial(true);
+    let inner = FlakyProvider::failing_with(vec![partial]);
+    let (provider, sleeps) = retrying(inner.clone());
+    assert!(provider.post_review(&pr(), &ReviewResult::new()).unwrap_err().partial);
+    assert_eq!(inner.calls(), 1);
+    assert!(sleeps.lock().unwrap().is_empty());
+}
@@ -1,10 +1,10 @@
 //! Integration tests for review_agent prompts: ReviewInput → user message text.
 //!
 //! BDD-style: given a ReviewInput, when we build the user message, then the output
-//! matches the expected format (Title, Description, Diff, Files).
+//! matches the expected format (Title, Description, Diff, Files, existing review threads).
 
 use quick_review::review_agent::review_input_to_user_message;
-use quick_review::review_input::{FileContent, ReviewInput};
+use quick_review::review_input::{FileContent, ReviewInput, ReviewThread, ThreadComment};
 
 /// Scenario: Empty ReviewInput produces a message with empty title/description/diff and "(none)" for files.
 #[test]
@@ -38,11 +38,13 @@ fn review_input_to_user_message_with_files_includes_paths_and_hints() {
                 path: "a.rs".to_string(),
                 diff: Some("".to_string()),
                 content: None,
+                status: None,
             },
             FileContent {
                 path: "b.rs".to_string(),
                 diff: None,
                 content: Some("x".to_string()),
+                status: None,
             },
         ]);
     let msg = review_input_to_user_message(&input);
@@ -50,3 +52,45 @@ fn review_input_to_user_message_with_files_includes_paths_and_hints() {
     assert!(msg.contains("a.rs (diff)"));
     assert!(msg.contains("b.rs (content)"));
 }
+
+/// Scenario: Existing review threads are listed after the files with location, state and
+/// comments; without threads the section is absent.
+#[test]
+fn review_input_to_user_message_lists_existing_threads() {
+    let comment = |author: &str, body: &str| ThreadComment {
+        author: author.to_string(),
+        body: body.to_string(),
+    };
+    let input = ReviewInput::new().with_title("T").with_threads(vec![
+        ReviewThread {
+            path: Some("src/lib.rs".to_string()),
+            line: Some(12),
+            comments: vec![comment("alice", "Off by one?"), comment("bob", "Fixed.\nThanks")],
+            resolved: true,
+            ..ReviewThread::default()
+        },
+        ReviewThread {
+            outdated: true,
+            comments: vec![comment("ci-bot", "Coverage dropped")],
+            ..ReviewThread::default()
+        },
+    ]);
+    let msg = review_input_to_user_message(&input);
+    assert!(msg.contains("\n\n2 existing review thread(s):\n\n1. "));
+    assert_eq!(msg.matches("existing review thread").count(), 1, "{}", msg);
+    assert!(msg.contains("1. src/lib.rs:12 (resolved)\n   alice: Off by one?\n   bob: Fixed.\n     Thanks"));
+    assert!(msg.contains("2. general discussion (open, outdated)\n   ci-bot: Coverage dropped"));
+
+    let msg = review_input_to_user_message(&ReviewInput::new());
+    assert!(!msg.contains("review thread"));
+}
+
+/// Scenario: Threads that could not be loaded are reported as such, not as "no threads".
+#[test]
+fn review_input_to_user_message_reports_unavailable_threads() {
+    let input = ReviewInput::new().with_threads_unavailable("token needed");
+    let msg = review_input_to_user_message(&input);
+    assert!(msg.contains("\n\nExisting review threads could not be loaded (token needed)"));
+    assert_eq!(msg.matches("Existing review threads").count(), 1, "{}", msg);
+    assert!(!msg.contains("No existing review threads"));
+}
@@ -6,7 +6,7 @@
 
 use langgraph::ToolSource;
 use quick_review::review_agent::ReviewToolSource;
-use quick_review::review_input::ReviewInput;
+use quick_review::review_input::{ReviewInput, Revisions};
 use quick_review::review_result::ReviewResult;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -29,6 +29,24 @@ async fn submit_review_summary_only_writes_to_slot() {
     assert!(result.line_comments.is_empty());
 }
 
+/// Scenario: submit_review pins the result to the head SHA of the reviewed input.
+#[tokio::test]
+async fn submit_review_records_head_sha() {
+    let input = ReviewInput::new().with_title("PR").with_revisions(Revisions {
+        head_sha: Some("abc123".to_string()),
+        ..Revisions::default()
+    });
+    let result_slot: Arc<RwLock<Option<ReviewResult>>> = Arc::new(RwLock::new(None));
+    let tools = ReviewToolSource::new(input, result_slot.clone());
+
+    let args = serde_json::json!({ "summary": "Looks good." });
+    let _ = tools.call_tool("submit_review", args).await.unwrap();
+
+    let guard = result_slot.read().await;
+    let result = guard.as_ref().expect("slot should have result");
+    assert_eq!(result.head_sha.as_deref(), Some("abc123"));
+}
+
 /// Scenario: Calling submit_review with summary and line_comments writes correct LineComments.
 #[tokio::test]
 async fn submit_review_with_line_comments_writes_to_slot() {
@@ -52,3 +70,31 @@ async fn submit_review_with_line_comments_writes_to_slot() {
     assert_eq!(result.line_comments[0].line, 10);
     assert_eq!(result.line_comments[0].body, "Use Option here.");
 }
+
+/// Scenario: Line comments on lines outside the diff are moved into the summary and reported.
+#[tokio::test]
+async fn submit_review_moves_comments_outside_the_diff() {
+    let input = ReviewInput::new()
+        .with_diff("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n fn a() {}\n+fn b() {}\n");
+    let result_slot: Arc<RwLock<Option<ReviewResult>>> = Arc::new(RwLock::new(None));
+    let tools = ReviewToolSource::new(input, result_slot.clone());
+
+    let args = serde_json::json!({
+        "summary": "Two notes.",
+        "line_comments": [
+            { "path": "src/lib.rs", "line": 2, "body": "Document b." },
+            { "path": "src/lib.rs", "line": 40, "body": "Unrelated." }
+        ]
+    });
+    let reply = tools.call_tool("submit_review", args).await.unwrap();
+    assert!(reply.text.contains("src/lib.rs:40"), "{}", reply.text);
+
+    let guard = result_slot.read().await;
+    let result = guard.as_ref().expect("slot should have result");
+    assert_eq!(result.line_comments.len(), 1);
+    assert_eq!(result.line_comments[0].line, 2);
+    assert_eq!(
+        result.summary,
+        "Two notes.\n\nComments on lines outside the diff:\n- src/lib.rs:40: Unrelated."
+    );
+}
@@ -0,0 +1,147 @@
+//! Integration tests for ReviewTarget: PR, commit, compare and local-range inputs.
+//!
+//! BDD-style: given a URL or CLI argument, when we resolve it, then we get the expected
+//! `ReviewTarget` variant; only PR/MR targets support posting. The pipeline passes the
+//! target through to the `AgentReviewer` unchanged.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use quick_review::agent_reviewer::{AgentReviewer, ReviewError};
+use quick_review::known_hosts::KnownHosts;
+use quick_review::pr_url::{Platform, PrUrlParseError};
+use quick_review::review_agent::review_target_to_user_message;
+use quick_review::review_result::ReviewResult;
+use quick_review::review_target::ReviewTarget;
+use quick_review::ReviewPipeline;
+
+fn resolve(input: &str) -> Result<ReviewTarget, PrUrlParseError> {
+    ReviewTarget::resolve(input, Path::new("/work/app"), &KnownHosts::default())
+}
+
+/// Scenario: A PR URL resolves to a PullRequest target that supports posting.
+#[test]
+fn pr_url_is_pull_request_target() {
+    let target = resolve("https://github.com/owner/repo/pull/5").expect("should parse");
+    let pr = target.as_pull_request().expect("pull request");
+    assert_eq!(pr.id, "5");
+    assert!(target.supports_posting());
+}
+
+/// Scenario: GitHub and GitLab commit URLs resolve to Commit targets that cannot be posted to.
+#[test]
+fn commit_urls_resolve_to_commit_targets() {
+    let target = resolve("https://github.com/owner/repo/commit/0123abcd").expect("should parse");
+    match &target {
+        ReviewTarget::Commit { repo, sha } => {
+            assert_eq!(repo.platform, Platform::GitHub);
+            assert_eq!(repo.project_path(), "owner/repo");
+            assert_eq!(sha, "0123abcd");
+        }
+        other => panic!("expected commit, got {:?}", other),
+    }
+    assert!(!target.supports_posting());
+    assert_eq!(target.to_string(), "https://github.com/owner/repo/commit/0123abcd");
+
+    let gitlab = resolve("https://gitlab.com/org/team/app/-/commit/deadbeef").expect("should parse");
+    assert_eq!(gitlab.to_string(), "https://gitlab.com/org/team/app/-/commit/deadbeef");
+    assert_eq!(gitlab.repo().expect("repo").owner, "org/team");
+}
+
+/// Scenario: Compare URLs keep slashes in branch names and accept two- or three-dot ranges.
+#[test]
+fn compare_urls_resolve_to_compare_targets() {
+    let target = resolve("https://github.com/owner/repo/compare/main...feature/login").expect("should parse");
+    assert_eq!(
+        target,
+        ReviewTarget::Compare {
+            repo: target.repo().expect("repo"),
+            base: "main".to_string(),
+            head: "feature/login".to_string(),
+        }
+    );
+    assert_eq!(target.to_string(), "https://github.com/owner/repo/compare/main...feature/login");
+    assert!(matches!(
+        resolve("https://gitlab.com/g/p/-/compare/v1..v2"),
+        Ok(ReviewTarget::Compare { .. })
+    ));
+}
+
+/// Scenario: Malformed commit SHAs and other repository pages are rejected.
+#[test]
+fn invalid_commit_or_page_is_rejected() {
+    assert_eq!(
+        resolve("https://github.com/owner/repo/commit/not-a-sha").unwrap_err(),
+        PrUrlParseError::InvalidRevision("not-a-sha".to_string())
+    );
+    assert!(matches!(
+        resolve("https://github.com/owner/repo/issues/3"),
+        Err(PrUrlParseError::NotAPullRequest { .. })
+    ));
+}
+
+/// Scenario: `base..head` and `base...head` resolve to a LocalRange in the project path.
+#[test]
+fn local_range_resolves_against_project_path() {
+    for input in ["main..feature", "main...feature"] {
+        let target = resolve(input).expect("should parse");
+        assert_eq!(
+            target,
+            ReviewTarget::LocalRange {
+                path: Path::new("/work/app").to_path_buf(),
+                base: "main".to_string(),
+                head: "feature".to_string(),
+            }
+        );
+        assert!(!target.supports_posting());
+    }
+    assert!(resolve("main..").is_err());
+}
+
+/// Scenario: Patch files (by extension) and `-` resolve to Patch targets.
+#[test]
+fn patch_files_and_stdin_resolve_to_patch_targets() {
+    for input in ["fix.patch", "../out/series.mbox", "-"] {
+        let target = resolve(input).expect("should parse");
+        assert_eq!(target, ReviewTarget::Patch { path: input.into() });
+        assert_eq!(target.kind_name(), "patch");
+    }
+    assert_eq!(resolve("-").unwrap().to_string(), "patch from stdin");
+}
+
+/// Scenario: The agent's opening message names the commit for commit targets.
+#[test]
+fn user_message_describes_commit_target() {
+    let target = resolve("https://github.com/owner/repo/commit/0123abcd").expect("should parse");
+    let msg = review_target_to_user_message(&target);
+    assert!(msg.contains("commit 0123abcd"));
+    assert!(msg.contains("owner / repo"));
+}
+
+/// Records the target it was asked to review.
+struct RecordingReviewer {
+    seen: Mutex<Vec<ReviewTarget>>,
+}
+
+impl AgentReviewer for RecordingReviewer {
+    fn review(
+        &self,
+        _project_path: Option<&Path>,
+        target: &ReviewTarget,
+    ) -> Result<ReviewResult, ReviewError> {
+        self.seen.lock().unwrap().push(target.clone());
+        Ok(ReviewResult::new().with_summary("ok"))
+    }
+}
+
+/// Scenario: ReviewPipeline::run hands any target kind to the reviewer.
+#[test]
+fn pipeline_runs_non_pr_targets() {
+    let pipeline = ReviewPipeline::new(RecordingReviewer {
+        seen: Mutex::new(Vec::new()),
+    });
+    let target = resolve("main..feature").expect("should parse");
+    let result = pipeline.run(&target).expect("run should succeed");
+    assert_eq!(result.summary, "ok");
+    assert_eq!(pipeline.agent.seen.lock().unwrap()[0], target);
+}
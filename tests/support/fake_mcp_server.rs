@@ -0,0 +1,104 @@
+//! Fake MCP server for `tests/mcp_stdio_provider.rs`: a stdio JSON-RPC loop with canned PR tools.
+//!
+//! Behaviour is selected with `FAKE_MCP_MODE`:
+//! - unset: GitHub-style `get_pull_request`, `get_pull_request_diff`, `get_pull_request_files`
+//!   and `create_pull_request_review`
+//! - `no-diff-tool`: same, without `get_pull_request_diff`; the file list also has an added and
+//!   a removed file
+//! - `tool-error`: `get_pull_request` returns an `isError` result
+//! - `hang`: never answers `tools/call`
+//!
+//! Arguments of `create_pull_request_review` are appended as one JSON line to `FAKE_MCP_RECORD`.
+//! A non-JSON log line and a notification are written first to check that clients skip them.
+
+use std::io::{BufRead, Write};
+
+use serde_json::{json, Value};
+
+const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+
+fn main() {
+    let mode = std::env::var("FAKE_MCP_MODE").unwrap_or_default();
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    writeln!(stdout, "fake-mcp-server starting").unwrap();
+    stdout.flush().unwrap();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        let Ok(msg) = serde_json::from_str::<Value>(&line) else { continue };
+        let Some(id) = msg.get("id").cloned() else { continue };
+        let params = &msg["params"];
+        let result = match msg["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "fake-mcp", "version": "0.0.0" }
+            }),
+            "tools/list" => {
+                let mut names = vec!["get_pull_request", "get_pull_request_files", "create_pull_request_review"];
+                if mode != "no-diff-tool" {
+                    names.push("get_pull_request_diff");
+                }
+                let tools: Vec<_> = names
+                    .iter()
+                    .map(|n| json!({ "name": n, "inputSchema": { "type": "object" } }))
+                    .collect();
+                json!({ "tools": tools })
+            }
+            "tools/call" if mode == "hang" => continue,
+            "tools/call" => match call_tool(&mode, params) {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": true }),
+            },
+            other => {
+                let error = json!({ "code": -32601, "message": format!("unknown method {}", other) });
+                reply(&mut stdout, json!({ "jsonrpc": "2.0", "id": id, "error": error }));
+                continue;
+            }
+        };
+        reply(
+            &mut stdout,
+            json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "level": "info" } }),
+        );
+        reply(&mut stdout, json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+}
+
+fn call_tool(mode: &str, params: &Value) -> Result<String, String> {
+    let args = &params["arguments"];
+    if args["owner"] != "owner" || args["repo"] != "repo" || args["pullNumber"] != 7 {
+        return Err(format!("unexpected arguments: {}", args));
+    }
+    match params["name"].as_str().unwrap_or_default() {
+        "get_pull_request" if mode == "tool-error" => Err("404 Not Found".into()),
+        "get_pull_request" => Ok(json!({ "title": "Fix lib", "body": "Replaces old with new." }).to_string()),
+        "get_pull_request_diff" => Ok(DIFF.into()),
+        "get_pull_request_files" if mode == "no-diff-tool" => Ok(json!([
+            { "filename": "src/lib.rs", "status": "modified", "patch": "@@ -1 +1 @@\n-old\n+new" },
+            { "filename": "src/new.rs", "status": "added", "patch": "@@ -0,0 +1 @@\n+new" },
+            { "filename": "src/gone.rs", "status": "removed", "patch": "@@ -1 +0,0 @@\n-old" }
+        ])
+        .to_string()),
+        "get_pull_request_files" => Ok(json!([
+            { "filename": "src/lib.rs", "patch": "@@ -1 +1 @@\n-old\n+new" }
+        ])
+        .to_string()),
+        "create_pull_request_review" => {
+            if let Ok(path) = std::env::var("FAKE_MCP_RECORD") {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| e.to_string())?;
+                writeln!(file, "{}", args).map_err(|e| e.to_string())?;
+            }
+            Ok("review created".into())
+        }
+        other => Err(format!("unknown tool {}", other)),
+    }
+}
+
+fn reply(stdout: &mut std::io::Stdout, msg: Value) {
+    writeln!(stdout, "{}", msg).unwrap();
+    stdout.flush().unwrap();
+}